@@ -9,7 +9,7 @@ use nannou::prelude::*;
 
 mod util;
 use util::args::ArgParser;
-use util::{captured_frame_path, smooth_by, Line2};
+use util::{capture, smooth_by, Line2};
 
 fn main() {
   nannou::app(model).run();
@@ -56,9 +56,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
 }
 
 fn draw_blue(draw: &Draw, win: &Rect) {
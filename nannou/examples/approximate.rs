@@ -0,0 +1,111 @@
+// Approximates a picture with a few hundred shapes using util::approximate.
+// The final frame is the shapes scaled up to the window, and the shapes are also saved as an SVG.
+// With --show-error the right half shows where the approximation is still wrong (brighter = worse).
+//
+// cargo run --release --example approximate -- --image path/to/image.jpg
+// cargo run --release --example approximate -- --image path/to/image.jpg --shapes 500 --kind circle --alpha 0.7
+// cargo run --release --example approximate -- --image path/to/image.jpg --kind triangle,blob,stroke --temperature 0.01
+// cargo run --release --example approximate -- --image path/to/image.jpg --show-error
+extern crate chrono;
+extern crate nannou;
+
+use nannou::prelude::*;
+
+mod util;
+use util::approximate::{Approximation, PrimitiveKind};
+use util::args::ArgParser;
+use util::{capture, capture_model};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+#[derive(Debug)]
+struct Model {
+  approximation: Approximation,
+  show_error: bool,
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  let path = args.get("image", String::from(""));
+  if path.is_empty() {
+    panic!("--image is required");
+  }
+
+  let kinds: Vec<PrimitiveKind> = args
+    .get("kind", String::from("triangle"))
+    .split(',')
+    .map(|kind| match kind {
+      "circle" => PrimitiveKind::Circle,
+      "blob" => PrimitiveKind::Blob,
+      "stroke" => PrimitiveKind::Stroke,
+      _ => PrimitiveKind::Triangle,
+    })
+    .collect();
+
+  let mut approximation = Approximation::open(&path, args.get("max-size", 200))
+    .kinds(kinds)
+    .alpha(args.get("alpha", 0.5))
+    .candidates(args.get("candidates", 100))
+    .climbs(args.get("climbs", 200))
+    .temperature(args.get("temperature", 0.0));
+  approximation.fit(args.get("shapes", 200));
+  println!("final error {:.4}", approximation.error());
+
+  // keep the aspect ratio of the image
+  let scale = 1024.0 / approximation.width.max(approximation.height) as f32;
+  let show_error = args.get("show-error", false);
+  let width = approximation.width as f32 * scale * if show_error { 2.0 } else { 1.0 };
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(width as u32, (approximation.height as f32 * scale) as u32)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+
+  Model {
+    approximation,
+    show_error,
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  draw.background().color(BLACK);
+  let win = app.window_rect();
+  let approximation = &model.approximation;
+
+  if model.show_error {
+    let left = Rect::from_x_y_w_h(-win.w() / 4.0, 0.0, win.w() / 2.0, win.h());
+    let right = Rect::from_x_y_w_h(win.w() / 4.0, 0.0, win.w() / 2.0, win.h());
+    approximation.draw(&draw, &left);
+    let pixel = right.w() / approximation.width as f32;
+    for j in 0..approximation.height {
+      for i in 0..approximation.width {
+        // max error per pixel is 3, but that rarely happens so it's stretched a bit
+        let error = (approximation.pixel_error(i, j) / 3.0).sqrt();
+        draw
+          .rect()
+          .x_y(
+            right.left() + (i as f32 + 0.5) * pixel,
+            right.top() - (j as f32 + 0.5) * pixel,
+          )
+          .w_h(pixel, pixel)
+          .hsl(0.0, 0.0, error);
+      }
+    }
+  } else {
+    approximation.draw(&draw, &win);
+  }
+
+  draw.to_frame(app, &frame).unwrap();
+
+  let path = capture(app, &frame);
+  capture_model(app, &frame, model);
+  approximation.save_svg(
+    &path.with_extension("svg"),
+    1024.0 / approximation.width as f32,
+  );
+}
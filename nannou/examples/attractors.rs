@@ -0,0 +1,177 @@
+// Density renders of strange attractors with util::attractors.
+// Every orbit point is counted in a histogram the size of the window,
+// then the counts are log tone mapped so the faint parts show up next to the dense parts.
+//
+// Maps: clifford, de-jong, henon, henon-phase, lorenz, ikeda, tinkerbell, gumowski-mira
+// --a, --b, --c, --d override the map's parameters (where it has them)
+//
+// cargo run --release --example attractors
+// cargo run --release --example attractors -- --map de-jong --a 1.641 --b 1.902 --c 0.316 --d 1.525
+// cargo run --release --example attractors -- --map gumowski-mira --a 0.008 --b 0.05 --c -0.496 --gamma 3
+// cargo run --release --example attractors -- --map lorenz --rotation 0.8 --iterations 2000000
+// cargo run --release --example attractors -- --map henon-phase --a 1.3 --orbits 20000 --iterations 500
+// cargo run --release --example attractors -- --map clifford --image-size 4096
+extern crate chrono;
+extern crate nannou;
+
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::attractors::*;
+use util::{capture, capture_model};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+#[derive(Debug)]
+struct Model {
+  histogram: Histogram,
+  gamma: f32,
+  hue: f32,
+  hue_range: f32,
+  invert: bool,
+  // extra grayscale render at this size, bigger than the window
+  image: Option<Histogram>,
+}
+
+fn histogram<M: Map2>(map: M, size: usize, orbits: usize, iterations: usize) -> Histogram {
+  let mut histogram = Histogram::for_map(&map, size, size);
+  histogram.accumulate(&map, orbits, iterations);
+  histogram
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+
+  let orbits = args.get("orbits", 16);
+  let iterations = args.get("iterations", 1_000_000);
+  let map_name = args.get("map", String::from("clifford"));
+  let render = |size: usize| -> Histogram {
+    match map_name.as_str() {
+      "de-jong" => histogram(
+        DeJong::new(
+          args.get("a", 1.4),
+          args.get("b", -2.3),
+          args.get("c", 2.4),
+          args.get("d", -2.1),
+        ),
+        size,
+        orbits,
+        iterations,
+      ),
+      "henon" => histogram(
+        Henon::new(args.get("a", 1.4), args.get("b", 0.3)),
+        size,
+        orbits,
+        iterations,
+      ),
+      "henon-phase" => histogram(HenonPhase::new(args.get("a", PI)), size, orbits, iterations),
+      "lorenz" => histogram(
+        Lorenz::new(
+          args.get("a", 10.0),
+          args.get("b", 28.0),
+          args.get("c", 8.0 / 3.0),
+        )
+        .rotate(args.get("rotation", 0.0)),
+        size,
+        orbits,
+        iterations,
+      ),
+      "ikeda" => histogram(Ikeda::new(args.get("a", 0.9)), size, orbits, iterations),
+      "tinkerbell" => histogram(
+        Tinkerbell::new(
+          args.get("a", 0.9),
+          args.get("b", -0.6013),
+          args.get("c", 2.0),
+          args.get("d", 0.5),
+        ),
+        size,
+        orbits,
+        iterations,
+      ),
+      "gumowski-mira" => histogram(
+        GumowskiMira::new(
+          args.get("a", 0.008),
+          args.get("b", 0.05),
+          args.get("c", -0.496),
+        ),
+        size,
+        orbits,
+        iterations,
+      ),
+      _ => histogram(
+        Clifford::new(
+          args.get("a", -1.4),
+          args.get("b", 1.6),
+          args.get("c", 1.0),
+          args.get("d", 0.7),
+        ),
+        size,
+        orbits,
+        iterations,
+      ),
+    }
+  };
+
+  let histogram = render(1024);
+  println!("{:?}", histogram);
+  let image_size = args.get("image-size", 0);
+  let image = if image_size > 0 {
+    Some(render(image_size))
+  } else {
+    None
+  };
+
+  Model {
+    histogram,
+    image,
+    gamma: args.get("gamma", 2.0),
+    hue: args.get("hue", 0.6),
+    hue_range: args.get("hue-range", 0.15),
+    invert: args.get("invert", false),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  let win = app.window_rect();
+  let (background, ink) = if model.invert {
+    (0.96, -1.0)
+  } else {
+    (0.02, 1.0)
+  };
+  draw.background().color(hsl(model.hue, 0.3, background));
+
+  model.histogram.draw(&draw, &win, model.gamma, |tone| {
+    hsla(
+      (model.hue + tone * model.hue_range) % 1.0,
+      0.6,
+      background + ink * tone * 0.9,
+      1.0,
+    )
+  });
+
+  draw.to_frame(app, &frame).unwrap();
+
+  let path = capture(app, &frame);
+  capture_model(app, &frame, model);
+  if let Some(histogram) = &model.image {
+    let path = path.with_file_name(format!(
+      "{}_{}px.png",
+      path.file_stem().unwrap().to_string_lossy(),
+      histogram.width
+    ));
+    if let Err(err) = histogram.to_image(model.gamma).save(path) {
+      println!("{}", err);
+    }
+  }
+}
@@ -0,0 +1,134 @@
+// Flow-field strokes painted with util::brush::BristleBrush.
+// Each stroke is pressed down, dragged along a noise field and lifted again,
+// and the brush is only reloaded every few strokes so the later ones run dry.
+// The paper comes from util::texture, and its height map decides where the paint catches.
+// Paper, paint and the paper's shading are separate layers (util::layers), flattened before capture.
+// --save-layers true also saves each one on its own.
+//
+// Textures: grain, wove, laid, canvas, fiber
+//
+// cargo run --release --example bristle_brush
+// cargo run --release --example bristle_brush -- --strokes 80 --reload-every 4 --palette "muzli3"
+// cargo run --release --example bristle_brush -- --width 60 --bristles 80 --grain 1 --speed 6
+// cargo run --release --example bristle_brush -- --texture canvas --contrast 0.3 --shade 1
+// cargo run --release --example bristle_brush -- --depletion 0.002 --flow 0.35 --seed 12
+extern crate chrono;
+extern crate nannou;
+
+use nannou::noise::{Fbm, MultiFractal, NoiseFn, Seedable};
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::brush::BristleBrush;
+use util::color::get_palette;
+use util::raster::BlendMode;
+use util::rng::random_range;
+use util::texture::{texture_kind, Texture, TEXTURES};
+use util::{capture, capture_model, Layer, Layers, Raster};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+#[derive(Debug)]
+struct Model {
+  layers: Layers,
+  brush: BristleBrush,
+  save_layers: bool,
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+  let win = app.window_rect();
+  let seed = args.get("seed", 1);
+
+  let texture = args.get("texture", String::from("grain"));
+  let kind = texture_kind(&texture).unwrap_or_else(|| {
+    panic!(
+      "Unknown texture \"{}\", expected one of {:?}",
+      texture, TEXTURES
+    )
+  });
+  let paper = Texture::new(kind, 1024, 1024)
+    .seed(seed)
+    .contrast(args.get("contrast", 0.2))
+    .generate();
+
+  let mut brush = BristleBrush::new(args.get("width", 40.0), args.get("bristles", 50))
+    .palette(get_palette(&args.get("palette", String::from("random"))))
+    .depletion(args.get("depletion", 0.0007))
+    .flow(args.get("flow", 0.3))
+    .grain(args.get("grain", 0.8))
+    .paper(paper.heights.clone());
+
+  let fbm = Fbm::new().set_seed(seed).set_frequency(0.8);
+  let mut paint = Raster::new(1024, 1024);
+  let speed = args.get("speed", 3.0);
+  let reload_every = args.get("reload-every", 3);
+  for stroke in 0..args.get("strokes", 40) {
+    if stroke % reload_every.max(1) == 0 {
+      brush.reload();
+    }
+    let mut point = pt2(
+      random_range(win.left(), win.right()) * 0.8,
+      random_range(win.bottom(), win.top()) * 0.8,
+    );
+    let mut path = vec![point];
+    for step in 0..random_range(80, 200) {
+      let angle = fbm.get([point.x as f64 / 300.0, point.y as f64 / 300.0]) as f32 * PI * 2.0;
+      // start slow, speed up through the middle of the stroke
+      let v = speed * (0.5 + (step as f32 / 40.0).min(1.0));
+      point += vec2(angle.cos(), angle.sin()) * v;
+      path.push(point);
+    }
+    // press down, hold, lift off
+    brush.stroke(&mut paint, &win, &path, |t| {
+      (t * 6.0).min(1.0) * ((1.0 - t) * 3.0).min(1.0)
+    });
+    println!("stroke {}, paint left {:.2}", stroke, brush.load());
+  }
+
+  let mut layers = Layers::new(1024, 1024);
+  layers.push(Layer::new("paper", paper.colors(1024, 1024)));
+  layers.push(Layer::new("paint", paint));
+  // the paper's shadows show through the paint too
+  layers.push(
+    Layer::new("shading", paper.shading(1024, 1024))
+      .mode(BlendMode::Multiply)
+      .opacity(args.get("shade", 0.5)),
+  );
+
+  Model {
+    layers,
+    brush,
+    save_layers: args.get("save-layers", false),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  let win = app.window_rect();
+
+  let flat = model.layers.flatten();
+  flat.draw(app, &draw, &win);
+
+  draw.to_frame(app, &frame).unwrap();
+
+  let path = capture(app, &frame);
+  capture_model(app, &frame, model);
+  flat.save(&path.with_file_name(format!(
+    "{}_paint.png",
+    path.file_stem().unwrap().to_string_lossy()
+  )));
+  if model.save_layers {
+    model.layers.save_layers(&path);
+  }
+}
@@ -9,7 +9,7 @@ use nannou::prelude::*;
 mod util;
 use util::args::ArgParser;
 use util::brush::*;
-use util::captured_frame_path;
+use util::capture;
 use util::color::*;
 use util::interp::lerp;
 
@@ -127,7 +127,5 @@ fn view(app: &App, model: &Model, frame: Frame) {
   }
 
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
 }
@@ -23,7 +23,7 @@ use nannou::prelude::*;
 
 mod util;
 use util::args::ArgParser;
-use util::captured_frame_path;
+use util::capture;
 use util::interp::*;
 
 fn main() {
@@ -80,9 +80,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
 }
 
 fn draw_lines(draw: &Draw, model: &Model) {
@@ -27,7 +27,7 @@ use nannou::prelude::*;
 
 mod util;
 use util::args::ArgParser;
-use util::{formatted_frame_path, Line2};
+use util::{embed_provenance, formatted_frame_path, Line2};
 
 const BG_COLOR: Rgb<u8> = DARKSLATEGRAY;
 
@@ -93,11 +93,9 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app.main_window().capture_frame(formatted_frame_path(
-    app,
-    &frame,
-    format!("seed-{}", model.seed),
-  ));
+  let path = formatted_frame_path(app, &frame, format!("seed-{}", model.seed));
+  app.main_window().capture_frame(path.clone());
+  embed_provenance(app, path);
 }
 
 fn draw_lines(draw: &Draw, model: &Model) {
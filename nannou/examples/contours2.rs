@@ -45,7 +45,7 @@ use delaunator::{triangulate, Point};
 
 mod util;
 use util::args::ArgParser;
-use util::{formatted_frame_path, point_cloud, Line2, PointCloud};
+use util::{embed_provenance, formatted_frame_path, point_cloud, Line2, PointCloud};
 
 fn main() {
   nannou::app(model).update(update).run();
@@ -159,14 +159,16 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app.main_window().capture_frame(formatted_frame_path(
+  let path = formatted_frame_path(
     app,
     &frame,
     format!(
       "seed-{}-octaves-{}-freq-{}-lacunarity-{}-persistence-{}",
       model.seed, model.octaves, model.frequency, model.lacunarity, model.persistence
     ),
-  ));
+  );
+  app.main_window().capture_frame(path.clone());
+  embed_provenance(app, path);
 }
 
 /// A simple container for a Triangle that exists in 3 dimensions.
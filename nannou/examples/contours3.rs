@@ -25,7 +25,7 @@ use delaunator::{triangulate, Point};
 
 mod util;
 use util::args::ArgParser;
-use util::{capture_model, captured_frame_path, point_cloud, Line2, PointCloud};
+use util::{capture, capture_model, point_cloud, Line2, PointCloud};
 
 fn main() {
   nannou::app(model).update(update).run();
@@ -152,9 +152,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
   capture_model(app, &frame, model);
 }
 
@@ -24,7 +24,7 @@ use delaunator::{triangulate, Point};
 mod util;
 use util::args::ArgParser;
 use util::draw_paper_texture;
-use util::{capture_model, captured_frame_path, point_cloud, PointCloud};
+use util::{capture, capture_model, point_cloud, PointCloud};
 // use util::{oversample, smooth_by};
 
 type Deque2 = VecDeque<Point2>;
@@ -155,9 +155,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
   capture_model(app, &frame, model);
 }
 
@@ -15,7 +15,7 @@ mod util;
 use util::args::ArgParser;
 use util::contours::*;
 use util::grid;
-use util::{capture_model, captured_frame_path, point_cloud};
+use util::{capture, capture_model, point_cloud};
 
 fn main() {
   nannou::app(model).update(update).run();
@@ -127,9 +127,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
   capture_model(app, &frame, model);
 }
 
@@ -1,6 +1,10 @@
 // implementation of deconbatch's "Poor man's DLA" in Rust
 // https://www.deconbatch.com/2019/10/the-poor-mans-dla-diffusion-limited.html
 // cargo run --release --example diffusion_1 -- --animate true --min-dist 0.5 --max-dist 1.10 --loops 2000 --size 8 --init 0.035 --start-radius 150 --outline true --hue 8
+//
+// with --animate true, --record true saves every animated frame, and encodes them as --format (see util::animation)
+// once the window is closed
+// cargo run --release --example diffusion_1 -- --animate true --record true --format gif --rate 20
 extern crate chrono;
 extern crate nannou;
 
@@ -9,8 +13,9 @@ use nannou::draw::Draw;
 use nannou::prelude::*;
 
 mod util;
+use util::animation::{format, FORMATS};
 use util::args::ArgParser;
-use util::captured_frame_path;
+use util::{capture, Recorder};
 // TODO: implement palette instead of color gradient
 // use util::color::*;
 
@@ -29,6 +34,7 @@ struct Model {
   max_dist: f32,
   start_radius: f32,
   outline: bool,
+  recorder: Recorder,
 }
 
 // this is essentially a "Point2" with a hue
@@ -46,7 +52,7 @@ impl Point {
 }
 
 fn main() {
-  nannou::app(model).update(update).run();
+  nannou::app(model).update(update).exit(exit).run();
 }
 
 fn model(app: &App) -> Model {
@@ -90,6 +96,18 @@ fn model(app: &App) -> Model {
     .unwrap();
   app.set_loop_mode(LoopMode::loop_ntimes(loops));
 
+  let format_name = args.get_string("format", "png");
+  let format = format(&format_name).unwrap_or_else(|| {
+    panic!(
+      "Unknown format \"{}\", expected one of {:?}",
+      format_name, FORMATS
+    )
+  });
+  let recorder = Recorder::new(app)
+    .enabled(animate && args.get_bool("record", false))
+    .every(animation_rate)
+    .format(format);
+
   Model {
     loops,
     point_size,
@@ -101,6 +119,7 @@ fn model(app: &App) -> Model {
     max_dist,
     start_radius,
     outline,
+    recorder,
   }
 }
 
@@ -152,18 +171,14 @@ fn view(app: &App, model: &Model, frame: Frame) {
   if model.animate && frame.nth() > 0 && frame.nth() % model.animation_rate == 0 {
     draw_cluster(&model.cluster, model.point_size, model.outline, &draw);
     draw.to_frame(app, &frame).unwrap();
-    // app
-    // .main_window()
-    // .capture_frame(captured_frame_path(app, &frame));
+    model.recorder.capture(app, &frame);
   }
 
   // draw final frame
   if frame.nth() == model.loops as u64 - 1 {
     draw_cluster(&model.cluster, model.point_size, model.outline, &draw);
     draw.to_frame(app, &frame).unwrap();
-    app
-      .main_window()
-      .capture_frame(captured_frame_path(app, &frame));
+    capture(app, &frame);
   }
 }
 
@@ -210,3 +225,7 @@ fn check_collision(
   }
   return false;
 }
+
+fn exit(_app: &App, model: Model) {
+  model.recorder.finish();
+}
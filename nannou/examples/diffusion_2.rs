@@ -13,7 +13,7 @@ use nannou::prelude::*;
 mod util;
 use util::args::ArgParser;
 use util::color::*;
-use util::{captured_frame_path, oversample, smooth_by};
+use util::{capture, oversample, smooth_by};
 
 const HEIGHT: u32 = 1024;
 const WIDTH: u32 = 1024;
@@ -156,9 +156,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
   if frame.nth() == model.loops as u64 - 1 {
     draw_tree(&model.trees, model.stroke_weight, &model.palette, &draw);
     draw.to_frame(app, &frame).unwrap();
-    app
-      .main_window()
-      .capture_frame(captured_frame_path(app, &frame));
+    capture(app, &frame);
   }
 }
 
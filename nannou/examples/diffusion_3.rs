@@ -10,7 +10,7 @@ use nannou::prelude::*;
 
 mod util;
 use util::args::ArgParser;
-use util::captured_frame_path;
+use util::capture;
 use util::color::*;
 
 const HEIGHT: u32 = 1024;
@@ -145,9 +145,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
   if frame.nth() == model.loops as u64 - 1 {
     draw_tree(&model.trees, model.stroke_weight, &model.palette, &draw);
     draw.to_frame(app, &frame).unwrap();
-    app
-      .main_window()
-      .capture_frame(captured_frame_path(app, &frame));
+    capture(app, &frame);
   }
 }
 
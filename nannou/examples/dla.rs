@@ -0,0 +1,146 @@
+// Diffusion-limited aggregation using util::dla
+// Like diffusion_1..4, but fast enough to grow tens of thousands of particles,
+// with strokes that taper towards the tips and colors that change with depth.
+//
+// cargo run --release --example dla -- --particles 20000
+// cargo run --release --example dla -- --particles 10000 --sticking 0.1 --hue 0.55
+// cargo run --release --example dla -- --particles 8000 --max-angle 30 --max-children 2
+// cargo run --release --example dla -- --particles 8000 --seeds line --bias-y -0.3
+// cargo run --release --example dla -- --particles 8000 --drift noise --noise-scale 200
+extern crate chrono;
+extern crate nannou;
+
+use nannou::noise::{NoiseFn, Perlin, Seedable};
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::dla::Dla;
+use util::{capture, capture_model};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+#[derive(Debug)]
+struct Model {
+  dla: Dla,
+  hue: f32,
+  stroke_weight: f32,
+  // "edges" tapers every segment individually, "paths" draws each branch as one polyline
+  draw_mode: String,
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+  let win = app.window_rect();
+
+  let mut dla = Dla::new(args.get("particle-size", 3.0))
+    .sticking(args.get("sticking", 1.0))
+    .max_angle(deg_to_rad(args.get("max-angle", 180.0)))
+    .max_children(args.get("max-children", 100));
+
+  dla = match args.get("seeds", String::from("point")).as_str() {
+    // a row of seeds along the bottom, good with a downward bias
+    "line" => {
+      let n = args.get("n-seeds", 20);
+      (0..n).fold(dla, |dla, i| {
+        let x = map_range(i, 0, n - 1, win.left() * 0.8, win.right() * 0.8);
+        dla.seed(pt2(x, win.bottom() * 0.8))
+      })
+    }
+    "circle" => {
+      let n = args.get("n-seeds", 12);
+      let radius = win.w() * 0.2;
+      (0..n).fold(dla, |dla, i| {
+        let angle = i as f32 / n as f32 * 2.0 * PI;
+        dla.seed(pt2(angle.cos() * radius, angle.sin() * radius))
+      })
+    }
+    _ => dla.seed(pt2(0.0, 0.0)),
+  };
+
+  dla = match args.get("drift", String::from("bias")).as_str() {
+    "noise" => {
+      let noise = Perlin::new().set_seed(args.get("seed", random_range(0, 100000)));
+      let scale = args.get("noise-scale", 200.0);
+      let strength = args.get("drift-strength", 0.3);
+      dla.drift(move |p| {
+        let angle = noise.get([(p.x / scale) as f64, (p.y / scale) as f64]) as f32 * 2.0 * PI;
+        vec2(angle.cos(), angle.sin()) * strength
+      })
+    }
+    _ => {
+      let (x, y) = (args.get("bias-x", 0.0), args.get("bias-y", 0.0));
+      if x != 0.0 || y != 0.0 {
+        dla.bias(x, y)
+      } else {
+        dla
+      }
+    }
+  };
+
+  dla.grow(args.get("particles", 5000));
+
+  Model {
+    dla,
+    hue: args.get("hue", 0.08),
+    stroke_weight: args.get("stroke-weight", 4.0),
+    draw_mode: args.get("draw-mode", String::from("edges")),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  draw.background().color(hsl(0.6, 0.3, 0.06));
+
+  let nodes = model.dla.nodes();
+  let max_depth = model.dla.max_depth().max(1) as f32;
+  let color = |depth: usize| {
+    let t = depth as f32 / max_depth;
+    hsla(model.hue + t * 0.15, 0.6, 0.75 - t * 0.35, 1.0)
+  };
+
+  if model.draw_mode == "paths" {
+    for branch in model.dla.branches() {
+      let points: Vec<Point2> = branch.iter().map(|i| nodes[*i].position).collect();
+      draw
+        .polyline()
+        .caps_round()
+        .stroke_weight(model.stroke_weight * 0.25)
+        .color(color(nodes[branch[0]].depth))
+        .points(points);
+    }
+  } else {
+    // thick near the trunk, thin at the tips.
+    // Descendant counts span several orders of magnitude so use a log scale
+    let descendants = model.dla.descendants();
+    let max_descendants = (*descendants.iter().max().unwrap_or(&1) as f32)
+      .ln()
+      .max(1.0);
+    for (index, node) in nodes.iter().enumerate() {
+      if let Some(parent) = node.parent {
+        let t = (descendants[index] as f32).ln() / max_descendants;
+        draw
+          .line()
+          .caps_round()
+          .start(nodes[parent].position)
+          .end(node.position)
+          .stroke_weight(model.stroke_weight * (0.15 + 0.85 * t))
+          .color(color(node.depth));
+      }
+    }
+  }
+
+  draw.to_frame(app, &frame).unwrap();
+
+  capture(app, &frame);
+  capture_model(app, &frame, model);
+}
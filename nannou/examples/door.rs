@@ -19,7 +19,7 @@ use nannou::prelude::*;
 
 mod util;
 use util::args::ArgParser;
-use util::captured_frame_path;
+use util::capture;
 use util::interp::lerp;
 
 const WIDTH: u32 = 1024;
@@ -193,6 +193,5 @@ fn view(app: &App, model: &Model, frame: Frame) {
   // Write to the window frame.
   draw.to_frame(app, &frame).unwrap();
 
-  let file_path = captured_frame_path(app, &frame);
-  app.main_window().capture_frame(file_path);
+  capture(app, &frame);
 }
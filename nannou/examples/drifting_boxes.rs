@@ -9,7 +9,7 @@ use nannou::Draw;
 
 mod util;
 use util::args::ArgParser;
-use util::formatted_frame_path;
+use util::{embed_provenance, formatted_frame_path};
 
 fn main() {
   nannou::app(model).view(view).update(update).run();
@@ -52,13 +52,9 @@ fn view(app: &App, model: &Model, frame: Frame) {
   draw_boxes(&draw, model, &win);
 
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame_threaded(formatted_frame_path(
-      app,
-      &frame,
-      format!("seed-{}", model.seed),
-    ));
+  let path = formatted_frame_path(app, &frame, format!("seed-{}", model.seed));
+  app.main_window().capture_frame_threaded(path.clone());
+  embed_provenance(app, path);
 }
 
 fn draw_boxes(draw: &Draw, model: &Model, win: &Rect) {
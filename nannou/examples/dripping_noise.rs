@@ -13,8 +13,8 @@ use nannou::prelude::*;
 mod util;
 use util::args::ArgParser;
 use util::blob::Blob;
+use util::capture;
 use util::capture_model;
-use util::captured_frame_path;
 use util::grid;
 use util::Line2;
 
@@ -127,9 +127,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
   capture_model(app, &frame, model);
 }
 
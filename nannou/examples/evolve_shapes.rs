@@ -0,0 +1,206 @@
+// Evolves a closed shape towards a random target shape, using util::evolve.
+// The genes are the radii of the shape at evenly spaced angles.
+// The final frame shows the best shape of a few generations along the way, faded by age,
+// and a plot of the best and mean fitness of every generation.
+//
+// cargo run --release --example evolve_shapes -- --generations 200
+// cargo run --release --example evolve_shapes -- --selection roulette --crossover single-point
+// cargo run --release --example evolve_shapes -- --selection rank --crossover blend --mutation creep --mutation-rate 0.05
+extern crate chrono;
+extern crate nannou;
+
+use nannou::noise::{NoiseFn, Perlin, Seedable};
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::evolve::{Crossover, Genome, Mutation, Population, Selection};
+use util::{capture, capture_model, Line2};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+#[derive(Debug, Clone)]
+struct Shape {
+  radii: Vec<f32>,
+}
+
+impl Shape {
+  fn random(n: usize) -> Self {
+    Shape {
+      radii: (0..n).map(|_| random_f32()).collect(),
+    }
+  }
+
+  fn points(&self, center: Point2, size: f32) -> Line2 {
+    let n = self.radii.len();
+    (0..=n)
+      .map(|i| {
+        let angle = i as f32 / n as f32 * 2.0 * PI;
+        center + pt2(angle.cos(), angle.sin()) * self.radii[i % n] * size
+      })
+      .collect()
+  }
+}
+
+impl Genome for Shape {
+  type Gene = f32;
+
+  fn genes(&self) -> &[f32] {
+    &self.radii
+  }
+
+  fn genes_mut(&mut self) -> &mut [f32] {
+    &mut self.radii
+  }
+
+  fn random_gene(&self, _index: usize) -> f32 {
+    random_f32()
+  }
+
+  fn repair(&mut self) {
+    for r in self.radii.iter_mut() {
+      *r = r.max(0.0).min(1.0);
+    }
+  }
+}
+
+#[derive(Debug)]
+struct Model {
+  population: Population<Shape>,
+  target: Shape,
+  // best of a few generations along the way
+  snapshots: Vec<Shape>,
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+
+  let n_genes = args.get("genes", 48);
+  let noise = Perlin::new().set_seed(args.get("seed", random_range(0, 100000)));
+  let target = Shape {
+    radii: (0..n_genes)
+      .map(|i| {
+        let angle = i as f32 / n_genes as f32 * 2.0 * PI;
+        let n = noise.get([angle.cos() as f64, angle.sin() as f64]) as f32;
+        map_range(n, -1.0, 1.0, 0.3, 1.0)
+      })
+      .collect(),
+  };
+
+  let selection = match args.get("selection", String::from("tournament")).as_str() {
+    "roulette" => Selection::Roulette,
+    "rank" => Selection::Rank,
+    _ => Selection::Tournament(args.get("tournament-size", 3)),
+  };
+  let crossover = match args.get("crossover", String::from("uniform")).as_str() {
+    "single-point" => Crossover::SinglePoint,
+    "blend" => Crossover::Blend(args.get("alpha", 0.3)),
+    _ => Crossover::Uniform,
+  };
+  let mutation = match args.get("mutation", String::from("reset")).as_str() {
+    "creep" => Mutation::Creep(args.get("creep", 0.05)),
+    _ => Mutation::Reset,
+  };
+
+  let fitness_target = target.clone();
+  let mut population = Population::from_fn(args.get("population", 200), |_| Shape::random(n_genes))
+    .fitness(move |shape: &Shape| {
+      // negative mean squared error, so a perfect match is 0
+      -shape
+        .radii
+        .iter()
+        .zip(fitness_target.radii.iter())
+        .map(|(a, b)| (a - b).powi(2))
+        .sum::<f32>()
+        / n_genes as f32
+    })
+    .selection(selection)
+    .crossover(crossover)
+    .mutation(mutation, args.get("mutation-rate", 0.01))
+    .elitism(args.get("elitism", 2));
+
+  let generations = args.get("generations", 150);
+  let n_snapshots = args.get("snapshots", 6).max(1);
+  let mut snapshots = vec![];
+  for generation in 0..generations {
+    if generation % (generations / n_snapshots).max(1) == 0 {
+      snapshots.push(population.best().genome.clone());
+    }
+    population.next_generation();
+  }
+  snapshots.push(population.best().genome.clone());
+  println!("{:?}", population.stats());
+
+  Model {
+    population,
+    target,
+    snapshots,
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  draw.background().color(hsl(0.1, 0.2, 0.96));
+  let win = app.window_rect();
+  let ink = hsl(0.6, 0.5, 0.2);
+  let center = pt2(0.0, win.h() * 0.12);
+  let size = win.w() * 0.3;
+
+  draw
+    .polyline()
+    .stroke_weight(6.0)
+    .color(hsla(0.0, 0.6, 0.6, 0.4))
+    .points(model.target.points(center, size));
+
+  let n = model.snapshots.len();
+  for (i, shape) in model.snapshots.iter().enumerate() {
+    let alpha = map_range(i, 0, n.max(2) - 1, 0.15, 1.0);
+    draw
+      .polyline()
+      .stroke_weight(1.5)
+      .color(hsla(0.6, 0.5, 0.2, alpha))
+      .points(shape.points(center, size));
+  }
+
+  // fitness plot along the bottom
+  let history = model.population.history();
+  if history.len() > 1 {
+    let plot = Rect::from_x_y_w_h(
+      0.0,
+      win.bottom() + win.h() * 0.15,
+      win.w() * 0.8,
+      win.h() * 0.18,
+    );
+    let worst = history.iter().map(|s| s.mean).fold(std::f32::MAX, f32::min);
+    let to_point = |i: usize, value: f32| {
+      pt2(
+        map_range(i, 0, history.len() - 1, plot.left(), plot.right()),
+        map_range(value, worst, 0.0, plot.bottom(), plot.top()),
+      )
+    };
+    draw
+      .polyline()
+      .stroke_weight(2.0)
+      .color(ink)
+      .points(history.iter().enumerate().map(|(i, s)| to_point(i, s.best)));
+    draw
+      .polyline()
+      .stroke_weight(1.0)
+      .color(hsla(0.6, 0.5, 0.2, 0.5))
+      .points(history.iter().enumerate().map(|(i, s)| to_point(i, s.mean)));
+  }
+
+  draw.to_frame(app, &frame).unwrap();
+
+  capture(app, &frame);
+  capture_model(app, &frame, model);
+}
@@ -7,7 +7,7 @@ use nannou::prelude::*;
 
 mod util;
 use util::args::ArgParser;
-use util::captured_frame_path;
+use util::capture;
 
 const HEIGHT: u32 = 1024;
 const WIDTH: u32 = 1024;
@@ -147,18 +147,14 @@ fn view(app: &App, model: &Model, frame: Frame) {
   if model.animate && frame.nth() > 0 && frame.nth() % model.animation_rate == 0 {
     draw_blobs(&draw, model);
     draw.to_frame(app, &frame).unwrap();
-    app
-      .main_window()
-      .capture_frame(captured_frame_path(app, &frame));
+    capture(app, &frame);
   }
 
   // draw final frame
   if frame.nth() == model.loops as u64 - 1 {
     draw_blobs(&draw, model);
     draw.to_frame(app, &frame).unwrap();
-    app
-      .main_window()
-      .capture_frame(captured_frame_path(app, &frame));
+    capture(app, &frame);
   }
 }
 
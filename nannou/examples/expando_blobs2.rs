@@ -8,7 +8,7 @@ use nannou::prelude::*;
 
 mod util;
 use util::args::ArgParser;
-use util::{captured_frame_path, oversample, smooth_by, Line2};
+use util::{capture, oversample, smooth_by, Line2};
 
 const HEIGHT: u32 = 1024;
 const WIDTH: u32 = 1024;
@@ -159,9 +159,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
   if frame.nth() == model.loops as u64 - 1 {
     draw_blobs(&draw, model);
     draw.to_frame(app, &frame).unwrap();
-    app
-      .main_window()
-      .capture_frame(captured_frame_path(app, &frame));
+    capture(app, &frame);
   }
 }
 
@@ -10,7 +10,7 @@ use nannou::prelude::*;
 mod util;
 use util::args::ArgParser;
 use util::blob::Blob;
-use util::captured_frame_path;
+use util::capture;
 use util::color::*;
 // use util::interp::{Interp, Interpolate}; // necessary for the alternate y spacing option
 
@@ -71,9 +71,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
 }
 
 // draw textured background:
@@ -12,7 +12,7 @@ mod util;
 use util::args::ArgParser;
 use util::draw_paper_texture;
 use util::interp::{Interp, Interpolate};
-use util::{capture_model, captured_frame_path};
+use util::{capture_model, capture_threaded};
 
 const WIDTH: u32 = 1024;
 const HEIGHT: u32 = 1024;
@@ -69,9 +69,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
   draw_lines(&draw, &win, model);
 
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame_threaded(captured_frame_path(app, &frame));
+  capture_threaded(app, &frame);
   capture_model(app, &frame, model);
 }
 
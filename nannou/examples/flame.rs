@@ -0,0 +1,146 @@
+// Fractal flames and plain IFS fractals with util::ifs.
+// Random flames are different every run, so the transforms are printed and saved with the model.
+//
+// Variations: linear, sinusoidal, spherical, swirl, horseshoe, polar, handkerchief, heart, disc,
+// spiral, hyperbolic, diamond, ex, julia, bent, fisheye, exponential, cosine, bubble, cylinder, eyefish
+//
+// cargo run --release --example flame
+// cargo run --release --example flame -- --variations swirl,julia,spherical --transforms 4 --palette "pink green yellow"
+// cargo run --release --example flame -- --final-variation spherical --vibrancy 0.5 --gamma 3
+// cargo run --release --example flame -- --preset barnsley-fern --palette random
+// cargo run --release --example flame -- --preset sierpinski --image-size 4096 --exr true
+extern crate chrono;
+extern crate nannou;
+
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::color::get_palette;
+use util::ifs::{variation, Affine, Flame, FlameHistogram, Transform, VARIATIONS};
+use util::{capture, capture_model};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+#[derive(Debug)]
+struct Model {
+  flame: Flame,
+  histogram: FlameHistogram,
+  gamma: f32,
+  vibrancy: f32,
+  background: [f32; 3],
+  // extra render at this size, bigger than the window
+  image: Option<FlameHistogram>,
+  exr: bool,
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+
+  let variations: Vec<_> = args
+    .get(
+      "variations",
+      String::from("linear,sinusoidal,spherical,swirl,julia"),
+    )
+    .split(',')
+    .map(|name| {
+      variation(name).unwrap_or_else(|| {
+        panic!(
+          "Unknown variation \"{}\", expected one of {:?}",
+          name, VARIATIONS
+        )
+      })
+    })
+    .collect();
+
+  let flame = match args.get("preset", String::from("random")).as_str() {
+    "sierpinski" => Flame::sierpinski(),
+    "barnsley-fern" => Flame::barnsley_fern(),
+    _ => Flame::random(args.get("transforms", 3), &variations),
+  };
+  let final_variation = args.get("final-variation", String::from(""));
+  let flame = match variation(&final_variation) {
+    Some(v) => flame.final_transform(
+      Transform::new(Affine::identity())
+        .variation(v, 1.0)
+        .color_speed(0.0),
+    ),
+    None => flame,
+  }
+  .palette(get_palette(&args.get("palette", String::from("random"))));
+  println!("{:#?}", flame.transforms());
+
+  let orbits = args.get("orbits", 32);
+  let iterations = args.get("iterations", 1_000_000);
+  let render = |size: usize| {
+    let mut histogram = FlameHistogram::for_flame(&flame, size, size);
+    histogram.accumulate(&flame, orbits, iterations);
+    histogram
+  };
+  let histogram = render(1024);
+  let image_size = args.get("image-size", 0);
+  let image = if image_size > 0 {
+    Some(render(image_size))
+  } else {
+    None
+  };
+
+  let background = args.get("background", 0.02);
+  Model {
+    flame,
+    histogram,
+    image,
+    gamma: args.get("gamma", 2.2),
+    vibrancy: args.get("vibrancy", 1.0),
+    background: [background, background, background],
+    exr: args.get("exr", false),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  let win = app.window_rect();
+  let [r, g, b] = model.background;
+  draw.background().color(rgb(r, g, b));
+
+  model
+    .histogram
+    .draw(&draw, &win, model.gamma, model.vibrancy, model.background);
+
+  draw.to_frame(app, &frame).unwrap();
+
+  let path = capture(app, &frame);
+  capture_model(app, &frame, model);
+
+  let histogram = model.image.as_ref().unwrap_or(&model.histogram);
+  let stem = format!(
+    "{}_{}px",
+    path.file_stem().unwrap().to_string_lossy(),
+    histogram.width
+  );
+  if model.image.is_some() {
+    histogram.save_png(
+      &path.with_file_name(format!("{}.png", stem)),
+      model.gamma,
+      model.vibrancy,
+      model.background,
+    );
+  }
+  if model.exr {
+    histogram.save_exr(
+      &path.with_file_name(format!("{}.exr", stem)),
+      model.gamma,
+      model.vibrancy,
+      model.background,
+    );
+  }
+}
@@ -20,7 +20,7 @@ use nannou::Draw;
 
 mod util;
 use util::args::ArgParser;
-use util::captured_frame_path;
+use util::capture_threaded;
 use util::circle::Circle;
 use util::color::*;
 
@@ -158,9 +158,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
   draw_field(&draw, model, &win);
 
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame_threaded(captured_frame_path(app, &frame));
+  capture_threaded(app, &frame);
 }
 
 fn draw_field(draw: &Draw, model: &Model, win: &Rect) {
@@ -8,7 +8,7 @@ use nannou::prelude::*;
 
 mod util;
 use util::args::ArgParser;
-use util::{formatted_frame_path, Line2};
+use util::{embed_provenance, formatted_frame_path, Line2};
 
 fn main() {
   nannou::app(model).view(view).run();
@@ -117,11 +117,11 @@ fn view(app: &App, model: &Model, frame: Frame) {
   }
 
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame_threaded(formatted_frame_path(
-      app,
-      &frame,
-      format!("seed-{}-noisescale-{}", model.seed, model.noise_scale),
-    ));
+  let path = formatted_frame_path(
+    app,
+    &frame,
+    format!("seed-{}-noisescale-{}", model.seed, model.noise_scale),
+  );
+  app.main_window().capture_frame_threaded(path.clone());
+  embed_provenance(app, path);
 }
@@ -24,7 +24,7 @@ mod util;
 use util::args::ArgParser;
 use util::color::*;
 use util::draw_paper_texture;
-use util::{capture_model, captured_frame_path, point_cloud, Line2};
+use util::{capture_model, capture_threaded, point_cloud, Line2};
 use util::{GravitationalBody, GravitySystem};
 
 fn main() {
@@ -103,9 +103,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
   draw_field(&draw, model, &win);
 
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame_threaded(captured_frame_path(app, &frame));
+  capture_threaded(app, &frame);
   capture_model(app, &frame, model);
 }
 
@@ -14,7 +14,7 @@ use nannou::Draw;
 mod util;
 use util::args::ArgParser;
 use util::color::*;
-use util::{capture_model, captured_frame_path, Line2};
+use util::{capture_model, capture_threaded, Line2};
 use util::{GravitationalBody, GravitySystem};
 
 fn main() {
@@ -102,9 +102,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
   draw_field(&draw, model);
 
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame_threaded(captured_frame_path(app, &frame));
+  capture_threaded(app, &frame);
   capture_model(app, &frame, model);
 }
 
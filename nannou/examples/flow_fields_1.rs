@@ -9,8 +9,8 @@ use nannou::prelude::*;
 
 mod util;
 
-use util::captured_frame_path_multi;
 use util::interp::{Interp, Interpolate};
+use util::{captured_frame_path_multi, embed_provenance};
 
 fn main() {
   nannou::app(model).update(update).view(view).run();
@@ -181,5 +181,9 @@ fn magnetic_field(
 
   // credit: https://github.com/nannou-org/nannou/blob/6dd78a5a7c966d46f25a4f56aeedfc3f4e54c7f5/examples/simple_capture.rs
   let file_path = captured_frame_path_multi(app, &frame, char_id, String::from(""));
-  app.window(id).unwrap().capture_frame_threaded(file_path);
+  app
+    .window(id)
+    .unwrap()
+    .capture_frame_threaded(file_path.clone());
+  embed_provenance(app, file_path);
 }
@@ -0,0 +1,155 @@
+// Differential line growth and space colonization, using util::growth
+//
+// cargo run --release --example growth -- --mode differential --steps 600
+// cargo run --release --example growth -- --mode differential --open true --injection 0.01 --steps 800
+// cargo run --release --example growth -- --mode colonization --attractors poisson --spacing 12
+// cargo run --release --example growth -- --mode colonization --attractors grid --roots 5 --tip-width 0.5 --exponent 2.5
+extern crate chrono;
+extern crate nannou;
+
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::growth::{Branch, DifferentialGrowth, SpaceColonization};
+use util::{capture, capture_model, point_cloud, poisson_disk, Line2};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+#[derive(Debug)]
+struct Model {
+  growth: Option<DifferentialGrowth>,
+  colonization: Option<SpaceColonization>,
+  tip_width: f32,
+  exponent: f32,
+  stroke_weight: f32,
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+  let win = app.window_rect();
+  let steps = args.get("steps", 500);
+
+  let mut growth = None;
+  let mut colonization = None;
+  if args.get("mode", String::from("differential")) == "colonization" {
+    let spacing = args.get("spacing", 15.0);
+    let inner = win.pad(win.w() * 0.1);
+    let attractors = match args.get("attractors", String::from("poisson")).as_str() {
+      "grid" => {
+        let n = (inner.w() / spacing) as usize;
+        point_cloud(
+          n,
+          n,
+          inner.left(),
+          inner.right(),
+          inner.bottom(),
+          inner.top(),
+        )
+      }
+      _ => poisson_disk(&inner, spacing, 30),
+    };
+    let n_roots = args.get("roots", 1);
+    let mut tree = SpaceColonization::new(attractors)
+      .attraction_distance(args.get("attraction-distance", spacing * 4.0))
+      .kill_distance(args.get("kill-distance", spacing * 0.6))
+      .segment_length(args.get("segment-length", 4.0));
+    for i in 0..n_roots {
+      let x = map_range(i as f32 + 0.5, 0.0, n_roots as f32, win.left(), win.right());
+      tree = tree.root(pt2(x, win.bottom()));
+    }
+    tree.grow(steps);
+    colonization = Some(tree);
+  } else {
+    let open = args.get("open", false);
+    let start: Line2 = if open {
+      (0..20)
+        .map(|i| pt2(map_range(i, 0, 19, -300.0, 300.0), 0.0))
+        .collect()
+    } else {
+      (0..30)
+        .map(|i| {
+          let angle = i as f32 / 30.0 * 2.0 * PI;
+          pt2(angle.cos(), angle.sin()) * 30.0
+        })
+        .collect()
+    };
+    let bounds: Line2 = (0..100)
+      .map(|i| {
+        let angle = i as f32 / 100.0 * 2.0 * PI;
+        pt2(angle.cos(), angle.sin()) * win.w() * 0.45
+      })
+      .collect();
+    let mut line = DifferentialGrowth::new(start, !open)
+      .repulsion(
+        args.get("repulsion-radius", 10.0),
+        args.get("repulsion", 1.0),
+      )
+      .attraction(args.get("attraction", 0.1))
+      .alignment(args.get("alignment", 0.1))
+      .max_edge(args.get("max-edge", 5.0))
+      .injection(args.get("injection", 0.005))
+      .max_nodes(args.get("max-nodes", 10000))
+      .bounds(bounds);
+    for i in 0..steps {
+      if i % 100 == 0 {
+        println!("step {} of {} ...", i, steps);
+      }
+      line.step();
+    }
+    growth = Some(line);
+  }
+
+  Model {
+    growth,
+    colonization,
+    tip_width: args.get("tip-width", 0.75),
+    exponent: args.get("exponent", 2.0),
+    stroke_weight: args.get("stroke-weight", 1.5),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  draw.background().color(hsl(0.1, 0.3, 0.95));
+  let ink = hsl(0.6, 0.4, 0.15);
+
+  if let Some(growth) = &model.growth {
+    draw
+      .polyline()
+      .stroke_weight(model.stroke_weight)
+      .color(ink)
+      .points(growth.line());
+  }
+
+  let branches: Vec<Branch> = match &model.colonization {
+    Some(colonization) => colonization.branches(model.tip_width, model.exponent),
+    None => vec![],
+  };
+  // polylines can't change width, so draw the branches segment by segment
+  for branch in &branches {
+    for i in 1..branch.points.len() {
+      draw
+        .line()
+        .caps_round()
+        .start(branch.points[i - 1])
+        .end(branch.points[i])
+        .stroke_weight(branch.widths[i - 1].max(branch.widths[i]))
+        .color(ink);
+    }
+  }
+
+  draw.to_frame(app, &frame).unwrap();
+
+  capture(app, &frame);
+  capture_model(app, &frame, model);
+}
@@ -9,110 +9,11 @@ use nannou::color::*;
 use nannou::prelude::*;
 
 mod util;
-use std::f64::consts::E;
 use util::args::ArgParser;
-use util::captured_frame_path;
+use util::capture_threaded;
+use util::harmonograph::{Curve, Harmonograph, Pendulum};
 use util::interp::lerp;
 
-// https://en.wikipedia.org/wiki/Harmonograph
-struct Pendulum {
-  amplitude: f32,
-  frequency: f32,
-  phase: f32,
-  damping: f32,
-}
-
-impl Pendulum {
-  pub fn new(amplitude: f32, frequency: f32, phase: f32, damping: f32) -> Self {
-    Pendulum {
-      amplitude,
-      frequency,
-      phase,
-      damping,
-    }
-  }
-
-  pub fn eval(&self, t: f32) -> f32 {
-    self.amplitude * (t * self.frequency + self.phase).sin() * (E as f32).powf(-self.damping * t)
-  }
-}
-
-struct Harmonograph {
-  // origin of harmonograph
-  x: f32,
-  y: f32,
-  x_pendulums: Vec<Pendulum>,
-  y_pendulums: Vec<Pendulum>,
-  resolution: f32,
-  length: f32,
-  start: f32,
-}
-
-impl Harmonograph {
-  pub fn new() -> Self {
-    Harmonograph {
-      x: 0.0,
-      y: 0.0,
-      x_pendulums: vec![],
-      y_pendulums: vec![],
-      resolution: 100.,
-      length: 100.,
-      start: PI / 12.,
-    }
-  }
-
-  pub fn x_y(mut self, x: f32, y: f32) -> Self {
-    self.x = x;
-    self.y = y;
-    self
-  }
-
-  pub fn x_pendulums(mut self, pendulums: Vec<Pendulum>) -> Self {
-    self.x_pendulums = pendulums;
-    self
-  }
-
-  pub fn y_pendulums(mut self, pendulums: Vec<Pendulum>) -> Self {
-    self.y_pendulums = pendulums;
-    self
-  }
-
-  pub fn resolution(mut self, resolution: f32) -> Self {
-    self.resolution = resolution;
-    self
-  }
-
-  pub fn length(mut self, length: f32) -> Self {
-    self.length = length;
-    self
-  }
-
-  pub fn start(mut self, start: f32) -> Self {
-    self.start = start;
-    self
-  }
-
-  pub fn points(&self) -> Vec<Point2> {
-    let n_points = (self.length * self.resolution).floor() as i32;
-    (0..n_points)
-      .map(|n| {
-        let t = self.start + n as f32 / self.resolution;
-        let x = self
-          .x_pendulums
-          .iter()
-          .fold(0.0, |sum, pendulum| sum + pendulum.eval(t))
-          + self.x;
-        let y = self
-          .y_pendulums
-          .iter()
-          .fold(0.0, |sum, pendulum| sum + pendulum.eval(t))
-          + self.y;
-        pt2(x, y)
-      })
-      .collect()
-  }
-}
-
 fn main() {
   nannou::app(model).view(view).size(1024, 1024).run();
 }
@@ -239,6 +140,5 @@ fn view(app: &App, model: &Model, frame: Frame) {
   // Write to the window frame.
   draw.to_frame(app, &frame).unwrap();
 
-  let file_path = captured_frame_path(app, &frame);
-  app.main_window().capture_frame_threaded(file_path);
+  capture_threaded(app, &frame);
 }
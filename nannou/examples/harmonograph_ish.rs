@@ -18,31 +18,13 @@ extern crate nannou;
 
 use nannou::color::*;
 use nannou::prelude::*;
-use std::collections::HashMap;
 
 mod util;
 use util::args::ArgParser;
-use util::captured_frame_path;
+use util::capture_threaded;
+use util::harmonograph::{oscillator, OSCILLATORS};
 use util::interp::lerp;
 
-type Oscillator = fn(f32) -> f32;
-
-fn osc_sin(t: f32) -> f32 {
-  t.sin()
-}
-
-fn osc_cos(t: f32) -> f32 {
-  t.cos()
-}
-
-fn osc_half_sin(t: f32) -> f32 {
-  (t.sin() + 1.) / 2.
-}
-
-fn osc_collapse(t: f32) -> f32 {
-  t.sin() * t.powf(-0.35)
-}
-
 fn main() {
   nannou::app(model).view(view).size(1024, 1024).run();
 }
@@ -114,35 +96,8 @@ fn view(app: &App, model: &Model, frame: Frame) {
   // Clear the background to black.
   draw.background().color(BLACK);
 
-  // not sure how to make this work the way I want it
-  let mut oscillator_map: HashMap<String, Oscillator> = HashMap::new();
-  oscillator_map.insert(String::from("sin"), osc_sin);
-  oscillator_map.insert(String::from("cos"), osc_cos);
-  oscillator_map.insert(String::from("half-sin"), osc_half_sin);
-  oscillator_map.insert(String::from("collapse"), osc_collapse);
-
-  let x_modulator = match oscillator_map.get(&model.x_oscillator) {
-    Some(osc) => osc,
-    None => {
-      println!(
-        "Oscillator value '{}' is invald. Valid options are:",
-        &model.x_oscillator
-      );
-      oscillator_map.keys().for_each(|key| println!("{}", key));
-      std::process::exit(0);
-    }
-  };
-  let y_modulator = match oscillator_map.get(&model.y_oscillator) {
-    Some(osc) => osc,
-    None => {
-      println!(
-        "Oscillator value '{}' is invald. Valid options are:",
-        &model.y_oscillator
-      );
-      oscillator_map.keys().for_each(|key| println!("{}", key));
-      std::process::exit(0);
-    }
-  };
+  let x_modulator = get_oscillator(&model.x_oscillator);
+  let y_modulator = get_oscillator(&model.y_oscillator);
 
   let points = (0..=(model.line_length * 360 * model.resolution as i32)).map(|i| {
     let theta: f32 = deg_to_rad(i as f32) / model.resolution;
@@ -183,6 +138,16 @@ fn view(app: &App, model: &Model, frame: Frame) {
   // Write to the window frame.
   draw.to_frame(app, &frame).unwrap();
 
-  let file_path = captured_frame_path(app, &frame);
-  app.main_window().capture_frame_threaded(file_path);
+  capture_threaded(app, &frame);
+}
+
+fn get_oscillator(name: &str) -> util::harmonograph::Oscillator {
+  match oscillator(name) {
+    Some(osc) => osc,
+    None => {
+      println!("Oscillator value '{}' is invald. Valid options are:", name);
+      OSCILLATORS.iter().for_each(|key| println!("{}", key));
+      std::process::exit(0);
+    }
+  }
 }
@@ -8,7 +8,7 @@ use nannou::Draw;
 
 mod util;
 use util::args::ArgParser;
-use util::captured_frame_path;
+use util::capture_threaded;
 use util::color::*;
 use util::hexagon::*;
 
@@ -76,9 +76,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
   // Write to the window frame.
   draw.to_frame(app, &frame).unwrap();
 
-  app
-    .main_window()
-    .capture_frame_threaded(captured_frame_path(app, &frame));
+  capture_threaded(app, &frame);
 }
 
 fn generate_tree(model: &Model) -> HexTree {
@@ -8,7 +8,7 @@ use nannou::prelude::*;
 
 mod util;
 use util::args::ArgParser;
-use util::captured_frame_path;
+use util::capture_threaded;
 use util::color::*;
 use util::hexagon::*;
 use util::interp::{lerp, nextf};
@@ -120,7 +120,5 @@ fn view(app: &App, model: &Model, frame: Frame) {
   // Write to the window frame.
   draw.to_frame(app, &frame).unwrap();
 
-  app
-    .main_window()
-    .capture_frame_threaded(captured_frame_path(app, &frame));
+  capture_threaded(app, &frame);
 }
@@ -0,0 +1,142 @@
+// Composing a frame from named util::layers::Layers.
+// A noise "ground", a layer of overlapping discs blended onto it with --mode,
+// and a "glow" copy of the discs, blurred and screened on top.
+// The discs are masked to a soft circle in the middle of the frame, then the flattened frame gets
+// displaced, vignetted and grained.
+//
+// Modes: normal, multiply, screen, overlay, soft-light, darken, lighten, difference
+//
+// cargo run --release --example layer_compositing
+// cargo run --release --example layer_compositing -- --mode difference --opacity 0.8 --glow 0
+// cargo run --release --example layer_compositing -- --mode soft-light --displace 20 --vignette 0.7 --film-grain 0.06
+// cargo run --release --example layer_compositing -- --save-layers true
+extern crate chrono;
+extern crate nannou;
+
+use nannou::noise::{Fbm, MultiFractal, NoiseFn, Seedable};
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::color::{get_palette, rgb_from_hex};
+use util::raster::{blend_mode, BlendMode, BLEND_MODES};
+use util::{capture, capture_model, DensityMap, Layer, Layers, Raster};
+
+const SIZE: usize = 1024;
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+#[derive(Debug)]
+struct Model {
+  layers: Layers,
+  // the flattened and filtered frame
+  frame: Raster,
+  save_layers: bool,
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(SIZE as u32, SIZE as u32)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+  let seed = args.get("seed", 1);
+  let fbm = Fbm::new().set_seed(seed).set_frequency(1.5);
+
+  let ground = Raster::from_fn(SIZE, SIZE, |i, j| {
+    let n = fbm.get([i as f64 / SIZE as f64, j as f64 / SIZE as f64]) as f32;
+    let v = 0.5 + n * 0.4;
+    [v * 0.9, v * 0.85, v, 1.0]
+  });
+
+  let palette_name = args.get("palette", String::from("random"));
+  let palette = get_palette(&palette_name);
+  let mut discs = Raster::new(SIZE, SIZE);
+  for _ in 0..args.get("discs", 40) {
+    let rgb = rgb_from_hex(palette[random_range(0, palette.len())]);
+    fill_disc(
+      &mut discs,
+      random_range(0.1, 0.9) * SIZE as f32,
+      random_range(0.1, 0.9) * SIZE as f32,
+      random_range(0.03, 0.15) * SIZE as f32,
+      [rgb.red, rgb.green, rgb.blue, random_range(0.5, 1.0)],
+    );
+  }
+  let mut glow = discs.clone();
+  glow.blur(args.get("glow", 24.0));
+
+  // fades out from the middle of the frame
+  let mask = DensityMap::from_fn(SIZE, SIZE, |i, j| {
+    let (dx, dy) = (i as f32 / SIZE as f32 - 0.5, j as f32 / SIZE as f32 - 0.5);
+    let distance = (dx * dx + dy * dy).sqrt();
+    (1.0 - (distance - 0.3) / 0.2).max(0.0).min(1.0)
+  });
+
+  let mode = args.get("mode", String::from("multiply"));
+  let mode = blend_mode(&mode).unwrap_or_else(|| {
+    panic!(
+      "Unknown blend mode \"{}\", expected one of {:?}",
+      mode, BLEND_MODES
+    )
+  });
+  let mut layers = Layers::new(SIZE, SIZE).background([1.0, 1.0, 1.0, 1.0]);
+  layers.push(Layer::new("ground", ground));
+  layers.push(
+    Layer::new("discs", discs)
+      .mode(mode)
+      .opacity(args.get("opacity", 1.0))
+      .mask(mask),
+  );
+  layers.push(
+    Layer::new("glow", glow)
+      .mode(BlendMode::Screen)
+      .opacity(0.6),
+  );
+
+  let mut frame = layers.flatten();
+  let displace = args.get("displace", 8.0);
+  if displace > 0.0 {
+    frame.displace(&fbm, displace, 120.0);
+  }
+  frame.vignette(args.get("vignette", 0.4), 0.3);
+  frame.grain(args.get("film-grain", 0.03), seed);
+
+  Model {
+    layers,
+    frame,
+    save_layers: args.get("save-layers", false),
+  }
+}
+
+fn fill_disc(raster: &mut Raster, x: f32, y: f32, radius: f32, color: [f32; 4]) {
+  let (i0, i1) = ((x - radius).max(0.0) as usize, (x + radius).ceil() as usize);
+  let (j0, j1) = ((y - radius).max(0.0) as usize, (y + radius).ceil() as usize);
+  for j in j0..j1.min(raster.height) {
+    for i in i0..i1.min(raster.width) {
+      let (dx, dy) = (i as f32 + 0.5 - x, j as f32 + 0.5 - y);
+      if dx * dx + dy * dy <= radius * radius {
+        raster.blend(i, j, color);
+      }
+    }
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  let win = app.window_rect();
+
+  model.frame.draw(app, &draw, &win);
+
+  draw.to_frame(app, &frame).unwrap();
+
+  let path = capture(app, &frame);
+  capture_model(app, &frame, model);
+  if model.save_layers {
+    model.layers.save_layers(&path);
+  }
+}
@@ -9,7 +9,7 @@ use nannou::prelude::*;
 mod util;
 use util::args::ArgParser;
 use util::blob::Blob;
-use util::captured_frame_path;
+use util::capture;
 use util::interp::{lerp, Interp, Interpolate};
 
 fn main() {
@@ -139,6 +139,5 @@ fn view(app: &App, model: &Model, frame: Frame) {
   // Write to the window frame.
   draw.to_frame(app, &frame).unwrap();
 
-  let file_path = captured_frame_path(app, &frame);
-  app.main_window().capture_frame(file_path);
+  capture(app, &frame);
 }
@@ -0,0 +1,141 @@
+// A seamlessly looping noise flow field, to show off util::animation.
+// The field is sampled with loop_noise, so the last frame of the loop flows straight back into the first.
+// Time comes from a fixed-step Clock, so with --record true every frame is saved
+// (assets/loop_noise_field/{run id}/...) and the encoded animation plays at --fps even if rendering is slow.
+// When recording, the sketch stops after one loop, and encodes the frames when the window is closed.
+//
+// Formats: png (frames only), gif, apng, webm, mp4. Everything but gif needs ffmpeg installed.
+//
+// cargo run --release --example loop_noise_field
+// cargo run --release --example loop_noise_field -- --record true --format gif --seconds 3 --fps 25
+// cargo run --release --example loop_noise_field -- --record true --format webm --lines 2000 --radius 1.2
+extern crate chrono;
+extern crate nannou;
+
+use nannou::noise::{NoiseFn, Perlin, Seedable};
+use nannou::prelude::*;
+
+mod util;
+use util::animation::{format, loop_noise, loop_value, FORMATS};
+use util::args::ArgParser;
+use util::rng::random_range;
+use util::{Clock, Recorder};
+
+fn main() {
+  nannou::app(model).view(view).exit(exit).run();
+}
+
+struct Model {
+  clock: Clock,
+  recorder: Recorder,
+  perlin: Perlin,
+  // starting points of the flow lines
+  starts: Vec<Point2>,
+  steps: usize,
+  noise_scale: f64,
+  radius: f64,
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every start point
+impl std::fmt::Debug for Model {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Model")
+      .field("clock", &self.clock)
+      .field("recorder", &self.recorder)
+      .field("n_lines", &self.starts.len())
+      .field("steps", &self.steps)
+      .field("noise_scale", &self.noise_scale)
+      .field("radius", &self.radius)
+      .finish()
+  }
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  let size = args.get("size", 720);
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(size, size)
+    .build()
+    .unwrap();
+  let win = app.window_rect();
+
+  let fps = args.get("fps", 30.0);
+  let name = args.get("format", String::from("png"));
+  let format = format(&name)
+    .unwrap_or_else(|| panic!("Unknown format \"{}\", expected one of {:?}", name, FORMATS));
+  let recording = args.get("record", false);
+  let clock = Clock::new(fps).duration(args.get("seconds", 4.0));
+  if recording {
+    // one loop as fast as it renders, the clock takes care of timing
+    app.set_loop_mode(LoopMode::loop_ntimes(clock.frames() as usize));
+  } else {
+    app.set_loop_mode(LoopMode::rate_fps(fps as f64));
+  }
+
+  let starts = (0..args.get("lines", 1200))
+    .map(|_| {
+      pt2(
+        random_range(win.left(), win.right()),
+        random_range(win.bottom(), win.top()),
+      )
+    })
+    .collect();
+
+  Model {
+    clock,
+    recorder: Recorder::new(app)
+      .enabled(recording)
+      .fps(fps)
+      .format(format),
+    perlin: Perlin::new().set_seed(args.get("seed", 1)),
+    starts,
+    steps: args.get("steps", 40),
+    noise_scale: args.get("noise-scale", 0.004),
+    radius: args.get("radius", 0.8),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  draw.background().color(hsl(0.6, 0.3, 0.08));
+  let phase = model.clock.phase(frame.nth());
+
+  // the hue drifts and comes back around too
+  let hue = 0.55 + loop_value(&model.perlin, phase, 0.5, 10.5) as f32 * 0.3;
+  for start in &model.starts {
+    let mut point = *start;
+    let mut line = vec![point];
+    for _ in 0..model.steps {
+      let angle = loop_noise(
+        &model.perlin,
+        point.x as f64 * model.noise_scale,
+        point.y as f64 * model.noise_scale,
+        phase,
+        model.radius,
+      ) as f32
+        * PI
+        * 2.0;
+      point += vec2(angle.cos(), angle.sin()) * 3.0;
+      line.push(point);
+    }
+    draw
+      .polyline()
+      .weight(1.0)
+      .color(hsla(hue, 0.6, 0.6, 0.4))
+      .points(line);
+  }
+
+  draw.to_frame(app, &frame).unwrap();
+
+  // the window can still redraw after the last update, those frames aren't part of the loop
+  if frame.nth() < model.clock.frames() {
+    model.recorder.capture(app, &frame);
+  }
+}
+
+fn exit(_app: &App, model: Model) {
+  model.recorder.finish();
+}
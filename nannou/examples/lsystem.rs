@@ -0,0 +1,117 @@
+// L-systems drawn with util::lsystem's turtle.
+// Branches are thinner and lighter the deeper they are.
+//
+// Presets: koch, koch-snowflake, dragon, hilbert, sierpinski, sierpinski-arrowhead,
+// plant, bush, stochastic-plant, parametric-tree, context-plant
+//
+// cargo run --release --example lsystem
+// cargo run --release --example lsystem -- --preset dragon --iterations 14
+// cargo run --release --example lsystem -- --preset stochastic-plant --angle-jitter 0.2 --length-jitter 0.3
+// cargo run --release --example lsystem -- --preset parametric-tree --angle 25 --width 12
+// cargo run --release --example lsystem -- --axiom "X" --rules "X=F[+X][-X]FX,F=FF" --angle 25.7 --iterations 6
+extern crate chrono;
+extern crate nannou;
+
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::lsystem::{fit, preset, LSystem, Rule, Turtle, TurtlePath};
+use util::{capture, capture_model};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+struct Model {
+  paths: Vec<TurtlePath>,
+  max_depth: usize,
+  hue: f32,
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every point
+impl std::fmt::Debug for Model {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Model")
+      .field("n_paths", &self.paths.len())
+      .field("max_depth", &self.max_depth)
+      .field("hue", &self.hue)
+      .finish()
+  }
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+
+  // --rules is "symbol=successor" pairs separated by commas, for trying out grammars without a preset
+  let rules = args.get("rules", String::from(""));
+  let preset = if rules.is_empty() {
+    preset(&args.get("preset", String::from("plant")))
+  } else {
+    let mut system = LSystem::new(&args.get("axiom", String::from("F")));
+    for rule in rules.split(',') {
+      let mut parts = rule.splitn(2, '=');
+      let symbol = parts.next().and_then(|s| s.chars().next()).unwrap();
+      system = system.rule(Rule::new(symbol, parts.next().unwrap_or("")));
+    }
+    util::lsystem::Preset {
+      system,
+      angle: 25.0,
+      iterations: 5,
+    }
+  };
+
+  let modules = preset
+    .system
+    .generate(args.get("iterations", preset.iterations));
+  let turtle = Turtle::new(10.0, args.get("angle", preset.angle))
+    .width_factor(args.get("width-factor", 0.7))
+    .jitter(
+      args.get("angle-jitter", 0.0),
+      args.get("length-jitter", 0.0),
+    );
+  let mut paths = turtle.paths(&modules);
+  let win = app.window_rect();
+  fit(&mut paths, &win.pad(win.w() * 0.05));
+  // fit scales the widths along with everything else, so scale them back to --width for the trunk
+  let width = args.get("width", 4.0);
+  let max_width = paths.iter().map(|p| p.width).fold(0.0, f32::max);
+  for path in paths.iter_mut() {
+    path.width = path.width / max_width.max(1e-6) * width;
+  }
+  let max_depth = paths.iter().map(|p| p.depth).max().unwrap_or(0);
+  println!("{} modules, {} paths", modules.len(), paths.len());
+
+  Model {
+    paths,
+    max_depth,
+    hue: args.get("hue", 0.3),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  draw.background().color(hsl(0.1, 0.2, 0.96));
+
+  for path in &model.paths {
+    let t = path.depth as f32 / model.max_depth.max(1) as f32;
+    draw
+      .polyline()
+      .stroke_weight(path.width.max(0.5))
+      .color(hsla(model.hue + t * 0.1, 0.5, 0.2 + t * 0.35, 0.9))
+      .points(path.points.clone());
+  }
+
+  draw.to_frame(app, &frame).unwrap();
+
+  capture(app, &frame);
+  capture_model(app, &frame, model);
+}
@@ -10,7 +10,7 @@ use nannou::prelude::*;
 mod util;
 use util::args::ArgParser;
 use util::blob::*;
-use util::captured_frame_path;
+use util::capture;
 
 fn main() {
   nannou::app(model).run();
@@ -103,9 +103,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
 }
 
 // Finite subdivision rule algorithm to create a "fractal" line.
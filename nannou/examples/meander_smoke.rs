@@ -18,7 +18,7 @@ mod util;
 use util::args::ArgParser;
 use util::blob::*;
 use util::interp::*;
-use util::{captured_frame_path, smooth};
+use util::{capture, smooth};
 
 type Line2 = Vec<Point2>;
 
@@ -89,9 +89,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
 }
 
 fn generate_primary_line(model: &Model) -> Line2 {
@@ -30,7 +30,7 @@ use nannou::prelude::*;
 mod util;
 use util::args::ArgParser;
 use util::blob::*;
-use util::captured_frame_path;
+use util::capture;
 use util::interp::*;
 
 type Line2 = Vec<Point2>;
@@ -97,9 +97,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
 }
 
 fn generate_primary_line(model: &Model) -> Line2 {
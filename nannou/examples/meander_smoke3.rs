@@ -16,7 +16,7 @@ mod util;
 use util::args::ArgParser;
 use util::blob::*;
 use util::interp::*;
-use util::{captured_frame_path, oversample, smooth};
+use util::{capture, oversample, smooth};
 
 type Line2 = Vec<Point2>;
 
@@ -100,9 +100,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
 }
 
 fn generate_primary_line(model: &Model) -> Line2 {
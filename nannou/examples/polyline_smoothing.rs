@@ -0,0 +1,157 @@
+// util::polyline side by side: the same rough lines simplified, smoothed and offset.
+// The original lines are drawn in gray underneath.
+//
+// cargo run --release --example polyline_smoothing
+// cargo run --release --example polyline_smoothing -- --divergence 0.5 --epsilon 8 --min-area 200
+// cargo run --release --example polyline_smoothing -- --points 12 --iterations 5 --samples 16 --offset 12
+extern crate chrono;
+extern crate nannou;
+
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::polyline::*;
+use util::{capture, capture_model, meander, Line2};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+#[derive(Debug)]
+struct Model {
+  // jagged open line and closed loop, centered on the origin
+  open: Line2,
+  closed: Line2,
+  epsilon: f32,
+  min_area: f32,
+  iterations: usize,
+  samples: usize,
+  offset: f32,
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1536, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+
+  let n = args.get("points", 6);
+  let divergence = args.get("divergence", 0.3);
+  let start: Line2 = (0..n)
+    .map(|i| {
+      let x = map_range(i, 0, n - 1, -200.0, 200.0);
+      pt2(x, random_range(-40.0, 40.0) + 120.0)
+    })
+    .collect();
+  let open = meander(&start, args.get("depth", 3), divergence);
+  let closed = (0..n * 2)
+    .map(|i| {
+      let angle = i as f32 / (n * 2) as f32 * 2.0 * PI;
+      let radius = random_range(60.0, 110.0);
+      pt2(angle.cos() * radius, angle.sin() * radius - 90.0)
+    })
+    .collect();
+
+  Model {
+    open,
+    closed,
+    epsilon: args.get("epsilon", 6.0),
+    min_area: args.get("min-area", 150.0),
+    iterations: args.get("iterations", 3),
+    samples: args.get("samples", 8),
+    offset: args.get("offset", 8.0),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  draw.background().color(hsl(0.1, 0.2, 0.97));
+  let win = app.window_rect();
+
+  let panels: Vec<(&str, Box<dyn Fn(&Line2, bool) -> Vec<Line2>>)> = vec![
+    (
+      "resample",
+      Box::new(|line, closed| vec![resample(line, 15.0, closed)]),
+    ),
+    (
+      "rdp",
+      Box::new(move |line, closed| vec![simplify_rdp(line, model.epsilon, closed)]),
+    ),
+    (
+      "visvalingam",
+      Box::new(move |line, closed| vec![simplify_visvalingam(line, model.min_area, closed)]),
+    ),
+    (
+      "chaikin",
+      Box::new(move |line, closed| vec![chaikin(line, model.iterations, closed)]),
+    ),
+    (
+      "catmull-rom",
+      Box::new(move |line, closed| {
+        let simplified = simplify_rdp(line, model.epsilon, closed);
+        vec![catmull_rom(&simplified, model.samples, closed)]
+      }),
+    ),
+    (
+      "b-spline + offset",
+      Box::new(move |line, closed| {
+        let smooth = b_spline(
+          &simplify_rdp(line, model.epsilon, closed),
+          model.samples,
+          closed,
+        );
+        vec![
+          offset(&smooth, model.offset, 4.0, closed),
+          smooth.clone(),
+          offset(&smooth, -model.offset, 4.0, closed),
+        ]
+      }),
+    ),
+  ];
+
+  let (columns, rows) = (3, 2);
+  let (w, h) = (win.w() / columns as f32, win.h() / rows as f32);
+  for (i, (name, process)) in panels.iter().enumerate() {
+    let center = pt2(
+      win.left() + w * ((i % columns) as f32 + 0.5),
+      win.top() - h * ((i / columns) as f32 + 0.5),
+    );
+    let to_panel = |line: &Line2| -> Line2 { line.iter().map(|p| *p + center).collect() };
+    println!("{}", name);
+    for (line, closed) in &[(&model.open, false), (&model.closed, true)] {
+      let original = if *closed {
+        util::polygon::close_ring(line)
+      } else {
+        (*line).clone()
+      };
+      draw
+        .polyline()
+        .weight(1.0)
+        .color(hsla(0.0, 0.0, 0.5, 0.4))
+        .points(to_panel(&original));
+      for processed in process(line, *closed) {
+        println!("  {} -> {} points", original.len(), processed.len());
+        let processed = to_panel(&processed);
+        draw
+          .polyline()
+          .weight(1.5)
+          .join_round()
+          .color(hsl(0.6, 0.5, 0.25))
+          .points(processed.clone());
+        for p in processed {
+          draw.ellipse().xy(p).radius(1.5).color(hsl(0.0, 0.6, 0.5));
+        }
+      }
+    }
+  }
+
+  draw.to_frame(app, &frame).unwrap();
+
+  capture(app, &frame);
+  capture_model(app, &frame, model);
+}
@@ -0,0 +1,101 @@
+// Re-runs the sketch that made a captured PNG, with the same arguments.
+// util::capture and capture_model embed the sketch name and every resolved argument into the PNG (see util::provenance),
+// this reads them back, prints them and runs `cargo run --release --example {sketch} -- --key value ...`.
+// Any extra arguments after the path are passed on too, and win over the recorded ones.
+//
+// The recorded args include "rng-seed", so everything the util modules generate comes out the same.
+// Randomness a sketch gets straight from nannou's random_* isn't seeded and will differ,
+// unless the sketch imports the util::rng versions instead.
+//
+// cargo run --release --example reproduce -- assets/bristle_brush/bristle_brush_2021-03-01T10-00-00_000_.png
+// cargo run --release --example reproduce -- path/to/capture.png --rng-seed 12
+// cargo run --release --example reproduce -- path/to/capture.png --dry-run true
+extern crate chrono;
+extern crate nannou;
+
+use std::path::Path;
+use std::process::Command;
+
+mod util;
+use util::args::ArgParser;
+use util::Provenance;
+
+fn main() {
+  let args: Vec<String> = std::env::args().collect();
+  let path = match args.get(1) {
+    Some(path) => Path::new(path),
+    None => panic!("Usage: cargo run --release --example reproduce -- path/to/capture.png"),
+  };
+  let provenance = Provenance::read(path).unwrap_or_else(|| {
+    panic!(
+      "No provenance found in \"{}\", only PNGs captured with util::capture or capture_model have it",
+      path.display()
+    )
+  });
+
+  println!("sketch:  {}", provenance.sketch);
+  println!("version: {}", provenance.version);
+  if let Some(ref seed) = provenance.seed {
+    println!("seed:    {}", seed);
+  }
+  if let Some(ref palette) = provenance.palette {
+    println!("palette: {}", palette);
+  }
+  for (key, value) in &provenance.params {
+    println!("  --{} {}", key, value);
+  }
+
+  let root = env!("CARGO_MANIFEST_DIR");
+  if let Some(ref commit) = provenance.commit {
+    let head = Command::new("git")
+      .args(&["rev-parse", "HEAD"])
+      .current_dir(root)
+      .output()
+      .ok()
+      .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string());
+    if commit.ends_with("-dirty") {
+      println!(
+        "warning: captured with uncommitted changes on top of {}, the result may differ",
+        commit.trim_end_matches("-dirty")
+      );
+    } else if head.as_ref() != Some(commit) {
+      println!(
+        "warning: captured at commit {}, but the checkout is at {}, the result may differ",
+        commit,
+        head.unwrap_or_else(|| String::from("an unknown commit"))
+      );
+    }
+  }
+
+  // overrides, skipping the reproduce-only flag
+  let overrides: Vec<String> = args[2..].to_vec();
+  let mut sketch_args = provenance.args();
+  let mut i = 0;
+  while i < overrides.len() {
+    if overrides[i] == "--dry-run" {
+      i += 2;
+      continue;
+    }
+    sketch_args.push(overrides[i].clone());
+    i += 1;
+  }
+
+  if ArgParser::new().peek("dry-run").unwrap_or(false) {
+    println!(
+      "cargo run --release --example {} -- {}",
+      provenance.sketch,
+      sketch_args.join(" ")
+    );
+    return;
+  }
+  let status = Command::new("cargo")
+    .args(&["run", "--release", "--example", &provenance.sketch, "--"])
+    .args(&sketch_args)
+    .current_dir(root)
+    .status();
+  match status {
+    Ok(status) if !status.success() => println!("{} exited with {}", provenance.sketch, status),
+    Ok(_) => (),
+    Err(err) => println!("{}", err),
+  }
+}
@@ -10,7 +10,7 @@ mod util;
 use util::args::ArgParser;
 use util::circle::Circle;
 use util::color::*;
-use util::{captured_frame_path, smooth_by, Line2};
+use util::{capture, smooth_by, Line2};
 
 fn main() {
   nannou::app(model).run();
@@ -64,9 +64,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
 }
 
 fn generate_circles(model: &Model, win: &Rect) -> Vec<Circle> {
@@ -0,0 +1,132 @@
+// Space-filling curves with util::space_filling, drawn as a single line for plotting.
+// With --image or --noise the subdivision depth follows the darkness,
+// so the curve becomes a one-line rendering of the image.
+//
+// Curves: hilbert, moore, peano, gosper, z-order
+// Masks: circle, star
+//
+// cargo run --release --example space_filling
+// cargo run --release --example space_filling -- --curve moore --depth 6
+// cargo run --release --example space_filling -- --curve hilbert --depth 8 --min-depth 3 --image path/to/photo.png
+// cargo run --release --example space_filling -- --curve peano --depth 5 --min-depth 2 --noise 0.004 --seed 12
+// cargo run --release --example space_filling -- --curve gosper --depth 5 --noise 0.003 --mask star
+extern crate chrono;
+extern crate nannou;
+
+use nannou::noise::{NoiseFn, Perlin, Seedable};
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::space_filling::{curve_kind, CURVES};
+use util::{capture, capture_model, DensityMap, Line2, SpaceFillingCurve};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+struct Model {
+  line: Line2,
+  stroke_weight: f32,
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every point
+impl std::fmt::Debug for Model {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Model")
+      .field("n_points", &self.line.len())
+      .field("stroke_weight", &self.stroke_weight)
+      .finish()
+  }
+}
+
+fn mask(name: &str, win: &Rect) -> Option<Line2> {
+  let radius = win.w().min(win.h()) * 0.45;
+  match name {
+    "circle" => Some(
+      (0..360)
+        .map(|i| {
+          let angle = deg_to_rad(i as f32);
+          pt2(angle.cos(), angle.sin()) * radius
+        })
+        .collect(),
+    ),
+    "star" => Some(
+      (0..10)
+        .map(|i| {
+          let angle = PI / 2.0 + i as f32 * PI / 5.0;
+          let r = if i % 2 == 0 { radius } else { radius * 0.45 };
+          pt2(angle.cos(), angle.sin()) * r
+        })
+        .collect(),
+    ),
+    _ => None,
+  }
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+
+  let win = app.window_rect();
+  let name = args.get("curve", String::from("hilbert"));
+  let kind = curve_kind(&name)
+    .unwrap_or_else(|| panic!("Unknown curve \"{}\", expected one of {:?}", name, CURVES));
+  let mut curve = SpaceFillingCurve::new(kind, win.pad(win.w() * 0.05))
+    .depth(args.get("depth", 5))
+    .min_depth(args.get("min-depth", 0));
+
+  let image = args.get_string("image", "");
+  let noise_scale = args.get("noise", 0.0);
+  if !image.is_empty() {
+    // the image is stretched to the window, so square images work best
+    let density = DensityMap::open(&image, 512).gamma(args.get("gamma", 1.0));
+    curve = curve.density(move |point| density.sample(point, &win));
+  } else if noise_scale > 0.0 {
+    let perlin = Perlin::new().set_seed(args.get("seed", 0));
+    curve = curve.density(move |point| {
+      let n = perlin.get([
+        (point.x * noise_scale) as f64,
+        (point.y * noise_scale) as f64,
+      ]);
+      (n as f32 * 0.5 + 0.5).max(0.0).min(1.0)
+    });
+  }
+  if let Some(polygon) = mask(&args.get("mask", String::from("")), &win) {
+    curve = curve.mask(polygon);
+  }
+
+  println!("{:?}", curve);
+  let line = curve.line();
+  println!("{} points", line.len());
+
+  Model {
+    line,
+    stroke_weight: args.get("stroke-weight", 1.5),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  draw.background().color(WHITE);
+
+  draw
+    .polyline()
+    .caps_round()
+    .join_round()
+    .weight(model.stroke_weight)
+    .color(BLACK)
+    .points(model.line.clone());
+
+  draw.to_frame(app, &frame).unwrap();
+
+  capture(app, &frame);
+  capture_model(app, &frame, model);
+}
@@ -0,0 +1,105 @@
+// Weighted Voronoi stippling of an input image.
+// Dots are sized by how dark their Voronoi cell is.
+// With `--tsp true` the dots are chained into a single line for one-stroke plotting.
+//
+// cargo run --release --example stipple -- --image path/to/photo.png
+// cargo run --release --example stipple -- --image path/to/photo.png --points 10000 --iterations 50 --max-radius 2.5
+// cargo run --release --example stipple -- --image path/to/photo.png --points 6000 --tsp true --passes 3
+extern crate chrono;
+extern crate nannou;
+
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::{capture, capture_model, DensityMap, Stippler};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+#[derive(Debug)]
+struct Model {
+  density: DensityMap,
+  // number of stipples
+  n_points: usize,
+  // rounds of Lloyd relaxation
+  iterations: usize,
+  // dot radius for the lightest and darkest areas
+  min_radius: f32,
+  max_radius: f32,
+  // chain the dots into a single continuous line
+  tsp: bool,
+  // rounds of 2-opt to untangle the TSP line
+  passes: usize,
+  stroke_weight: f32,
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  let image = args.get_string("image", "");
+  if image.is_empty() {
+    panic!("an input image is required, e.g. --image path/to/photo.png");
+  }
+  // downsampling the image keeps the relaxation fast, and stipples can't show much more detail anyway
+  let density = DensityMap::open(&image, args.get("resolution", 400)).gamma(args.get("gamma", 1.0));
+
+  // match the window to the aspect ratio of the image
+  let size = args.get("size", 1024.0);
+  let scale = size / density.width.max(density.height) as f32;
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(
+      (density.width as f32 * scale) as u32,
+      (density.height as f32 * scale) as u32,
+    )
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+
+  Model {
+    density,
+    n_points: args.get("points", 4000),
+    iterations: args.get("iterations", 30),
+    min_radius: args.get("min-radius", 0.5),
+    max_radius: args.get("max-radius", 3.0),
+    tsp: args.get("tsp", false),
+    passes: args.get("passes", 2),
+    stroke_weight: args.get("stroke-weight", 1.0),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  draw.background().color(WHITE);
+  let win = app.window_rect();
+
+  let stippler = Stippler::new(&model.density)
+    .points(model.n_points)
+    .radius(model.min_radius, model.max_radius)
+    .relax(model.iterations);
+
+  if model.tsp {
+    draw
+      .polyline()
+      .caps_round()
+      .join_round()
+      .weight(model.stroke_weight)
+      .color(BLACK)
+      .points(stippler.tsp(&win, model.passes));
+  } else {
+    for circle in stippler.circles(&win) {
+      draw
+        .ellipse()
+        .x_y(circle.x, circle.y)
+        .radius(circle.radius)
+        .color(BLACK);
+    }
+  }
+
+  draw.to_frame(app, &frame).unwrap();
+
+  capture(app, &frame);
+  capture_model(app, &frame, model);
+}
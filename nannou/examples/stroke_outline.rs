@@ -0,0 +1,164 @@
+// Variable-width strokes with util::stroke.
+// The top half shows every join (rows) with every cap (columns) on a zigzag,
+// the bottom half is noise flow lines that start thick and taper to a point.
+// Everything is drawn as filled outlines, with the center lines on top for reference.
+//
+// cargo run --release --example stroke_outline
+// cargo run --release --example stroke_outline -- --width 24 --lines 60 --noise-scale 0.004
+// cargo run --release --example stroke_outline -- --svg true
+extern crate chrono;
+extern crate nannou;
+
+use nannou::noise::{NoiseFn, Perlin, Seedable};
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::polyline::resample;
+use util::stroke::{svg_path, Cap, Join, Stroke};
+use util::{capture, capture_model, Line2};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+struct Model {
+  // center line and outline
+  strokes: Vec<(Line2, Line2)>,
+  svg: bool,
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every point
+impl std::fmt::Debug for Model {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Model")
+      .field("n_strokes", &self.strokes.len())
+      .field("svg", &self.svg)
+      .finish()
+  }
+}
+
+fn zigzag(center: Point2, w: f32, h: f32) -> Line2 {
+  (0..5)
+    .map(|i| {
+      let x = map_range(i, 0, 4, -w / 2.0, w / 2.0);
+      let y = if i % 2 == 0 { -h / 2.0 } else { h / 2.0 };
+      center + vec2(x, y)
+    })
+    .collect()
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+  let win = app.window_rect();
+  let width = args.get("width", 16.0);
+  let mut strokes = vec![];
+
+  let joins = [Join::Miter(4.0), Join::Round, Join::Bevel];
+  let caps = [Cap::Butt, Cap::Round, Cap::Square, Cap::Taper(60.0)];
+  let cell_w = win.w() / caps.len() as f32;
+  let cell_h = win.h() / 2.0 / joins.len() as f32;
+  for (row, join) in joins.iter().enumerate() {
+    for (column, cap) in caps.iter().enumerate() {
+      let center = pt2(
+        win.left() + cell_w * (column as f32 + 0.5),
+        win.top() - cell_h * (row as f32 + 0.5),
+      );
+      let line = zigzag(center, cell_w * 0.6, cell_h * 0.5);
+      let outline = Stroke::new(width).join(*join).cap(*cap).outline(&line);
+      strokes.push((line, outline));
+    }
+  }
+
+  let perlin = Perlin::new().set_seed(args.get("seed", 1));
+  let noise_scale = args.get("noise-scale", 0.003);
+  let bottom = Rect::from_corners(win.bottom_left(), pt2(win.right(), win.y()));
+  let bottom = bottom.pad(width * 2.0);
+  for _ in 0..args.get("lines", 30) {
+    let mut point = pt2(
+      random_range(bottom.left(), bottom.right()),
+      random_range(bottom.bottom(), bottom.top()),
+    );
+    let mut line = vec![point];
+    for _ in 0..args.get("steps", 80) {
+      let angle = perlin.get([
+        (point.x * noise_scale) as f64,
+        (point.y * noise_scale) as f64,
+      ]) as f32
+        * PI
+        * 2.0;
+      point += vec2(angle.cos(), angle.sin()) * 4.0;
+      if !bottom.contains(point) {
+        break;
+      }
+      line.push(point);
+    }
+    if line.len() < 10 {
+      continue;
+    }
+    // flow lines are jittery at the pixel level, the outline looks better on evenly spaced points
+    let line = resample(&line, 4.0, false);
+    let outline = Stroke::new(width)
+      .width_fn(move |t| width * (1.0 - t * 0.7))
+      .join(Join::Round)
+      .start_cap(Cap::Round)
+      .end_cap(Cap::Taper(width * 3.0))
+      .outline(&line);
+    strokes.push((line, outline));
+  }
+
+  Model {
+    strokes,
+    svg: args.get("svg", false),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  draw.background().color(hsl(0.1, 0.2, 0.96));
+  let win = app.window_rect();
+
+  for (line, outline) in &model.strokes {
+    draw
+      .polygon()
+      .color(hsla(0.6, 0.4, 0.25, 0.85))
+      .points(outline.clone());
+    draw
+      .polyline()
+      .weight(1.0)
+      .color(hsl(0.0, 0.7, 0.6))
+      .points(line.clone());
+  }
+
+  draw.to_frame(app, &frame).unwrap();
+
+  let path = capture(app, &frame);
+  capture_model(app, &frame, model);
+  if model.svg {
+    let mut svg = vec![format!(
+      "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+      win.w(),
+      win.h(),
+      win.w(),
+      win.h()
+    )];
+    for (_, outline) in &model.strokes {
+      svg.push(format!(
+        "<path d=\"{}\" fill=\"black\" />",
+        svg_path(outline, &win)
+      ));
+    }
+    svg.push(String::from("</svg>"));
+    if let Err(err) = std::fs::write(path.with_extension("svg"), svg.join("\n")) {
+      println!("{}", err);
+    }
+  }
+}
@@ -9,7 +9,7 @@ use nannou::noise::{NoiseFn, Perlin};
 use nannou::prelude::*;
 
 mod util;
-use util::captured_frame_path;
+use util::capture_threaded;
 use util::interp::{Interp, Interpolate};
 
 fn main() {
@@ -112,6 +112,5 @@ fn view(app: &App, frame: Frame) {
   draw.to_frame(app, &frame).unwrap();
 
   // credit: https://github.com/nannou-org/nannou/blob/6dd78a5a7c966d46f25a4f56aeedfc3f4e54c7f5/examples/simple_capture.rs
-  let file_path = captured_frame_path(app, &frame);
-  app.main_window().capture_frame_threaded(file_path);
+  capture_threaded(app, &frame);
 }
@@ -8,7 +8,7 @@ use nannou::prelude::*;
 mod util;
 use util::args::ArgParser;
 use util::blob::Blob;
-use util::captured_frame_path;
+use util::capture_threaded;
 use util::interp::lerp;
 
 fn main() {
@@ -93,6 +93,5 @@ fn view(app: &App, model: &Model, frame: Frame) {
   // Write to the window frame.
   draw.to_frame(app, &frame).unwrap();
 
-  let file_path = captured_frame_path(app, &frame);
-  app.main_window().capture_frame_threaded(file_path);
+  capture_threaded(app, &frame);
 }
@@ -22,7 +22,7 @@ use nannou::prelude::*;
 mod util;
 use util::args::ArgParser;
 use util::blob::*;
-use util::captured_frame_path;
+use util::capture_threaded;
 use util::interp::{Interp, Interpolate};
 
 use delaunator::{triangulate, Point};
@@ -109,7 +109,5 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame_threaded(captured_frame_path(app, &frame));
+  capture_threaded(app, &frame);
 }
@@ -0,0 +1,326 @@
+// Tools for animated sketches.
+//
+// - Clock: time that advances a fixed step per frame instead of following the wall clock,
+//   so a recording plays back at the right speed no matter how slow each frame was to render
+// - Recorder: saves frames as `assets/{exe}/{run id}/{run id}_00001.png` and so on,
+//   then optionally encodes them to a GIF (pure Rust) or APNG/WebM/MP4 (with ffmpeg, which has to be on the PATH)
+// - loop helpers: noise and values that come back to where they started, for seamless loops
+//
+// Typical setup:
+//
+//   nannou::app(model).view(view).exit(exit).run();
+//   // model: clock: Clock::new(30.0).duration(4.0), recorder: Recorder::new(app).enabled(args.get("record", false))
+//   // view:  draw with model.clock.phase(frame.nth()), then model.recorder.capture(app, &frame)
+//   //        with app.set_loop_mode(LoopMode::loop_ntimes(clock.frames() as usize)) when recording
+//   // exit:  model.recorder.finish(), which runs when the window is closed
+extern crate image;
+
+use nannou::noise::NoiseFn;
+use nannou::prelude::*;
+
+use self::image::codecs::gif::{GifEncoder, Repeat};
+use self::image::{Delay, Frame as ImageFrame};
+use std::cell::Cell;
+use std::fs::File;
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use super::provenance::is_complete;
+use super::run_id;
+
+// how long `finish` waits for nannou to write the last frames
+const FRAME_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+  // just the frames
+  Png,
+  Gif,
+  Apng,
+  WebM,
+  Mp4,
+}
+
+pub const FORMATS: [&str; 5] = ["png", "gif", "apng", "webm", "mp4"];
+
+/// Looks up an output format by name, see FORMATS
+pub fn format(name: &str) -> Option<Format> {
+  match name {
+    "png" => Some(Format::Png),
+    "gif" => Some(Format::Gif),
+    "apng" => Some(Format::Apng),
+    "webm" => Some(Format::WebM),
+    "mp4" => Some(Format::Mp4),
+    _ => None,
+  }
+}
+
+// Time comes from the frame number (frame.nth() in view, app.elapsed_frames() in update)
+// instead of being stored, because view only gets to read the model
+#[derive(Debug, Clone)]
+pub struct Clock {
+  pub fps: f32,
+  // frames in one loop of the animation, if it has a length
+  loop_frames: Option<u64>,
+}
+
+impl Clock {
+  pub fn new(fps: f32) -> Self {
+    Clock {
+      fps: fps.max(1.0),
+      loop_frames: None,
+    }
+  }
+
+  /// Length of one loop in seconds, rounded to a whole number of frames
+  pub fn duration(mut self, seconds: f32) -> Self {
+    self.loop_frames = Some(((seconds * self.fps).round() as u64).max(1));
+    self
+  }
+
+  /// Frames in one loop, or one second's worth without a duration
+  pub fn frames(&self) -> u64 {
+    self.loop_frames.unwrap_or(self.fps.round() as u64)
+  }
+
+  /// Seconds since the start at frame `nth`
+  pub fn time(&self, nth: u64) -> f32 {
+    nth as f32 / self.fps
+  }
+
+  /// Seconds per frame
+  pub fn dt(&self) -> f32 {
+    1.0 / self.fps
+  }
+
+  /// How far through the loop frame `nth` is, in [0, 1).
+  /// Without a duration it wraps every second
+  pub fn phase(&self, nth: u64) -> f32 {
+    (nth % self.frames()) as f32 / self.frames() as f32
+  }
+
+  /// True on the last frame of the first loop, so a recording of exactly one loop can stop there
+  pub fn done(&self, nth: u64) -> bool {
+    self.loop_frames.map_or(false, |frames| nth + 1 >= frames)
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Recorder {
+  run_id: String,
+  dir: PathBuf,
+  enabled: bool,
+  every: u64,
+  fps: f32,
+  format: Format,
+  // frames handed to nannou so far, so `finish` knows how many to wait for
+  captured: Cell<usize>,
+}
+
+impl Recorder {
+  /// Frames go in `assets/{exe}/{run id}/`. Recording is off until `.enabled(true)`
+  pub fn new(app: &App) -> Self {
+    let run_id = run_id(app);
+    let dir = app
+      .project_path()
+      .expect("failed to locate `project_path`")
+      .join("assets")
+      .join(app.exe_name().unwrap())
+      .join(&run_id);
+    Recorder {
+      run_id,
+      dir,
+      enabled: false,
+      every: 1,
+      fps: 30.0,
+      format: Format::Png,
+      captured: Cell::new(0),
+    }
+  }
+
+  pub fn enabled(mut self, enabled: bool) -> Self {
+    self.enabled = enabled;
+    self
+  }
+
+  /// Only keep every nth frame, e.g. for a simulation that changes slowly
+  pub fn every(mut self, every: u64) -> Self {
+    self.every = every.max(1);
+    self
+  }
+
+  /// Playback rate of the encoded animation
+  pub fn fps(mut self, fps: f32) -> Self {
+    self.fps = fps.max(1.0);
+    self
+  }
+
+  pub fn format(mut self, format: Format) -> Self {
+    self.format = format;
+    self
+  }
+
+  pub fn is_enabled(&self) -> bool {
+    self.enabled
+  }
+
+  /// Path of the nth recorded frame (counting recorded frames, not sketch frames)
+  pub fn frame_path(&self, n: u64) -> PathBuf {
+    self
+      .dir
+      .join(format!("{}_{:05}", self.run_id, n))
+      .with_extension("png")
+  }
+
+  /// Captures the window if recording is on and this frame is one to keep
+  pub fn capture(&self, app: &App, frame: &Frame) {
+    if !self.enabled || frame.nth() % self.every != 0 {
+      return;
+    }
+    if !self.dir.exists() {
+      if let Err(err) = std::fs::create_dir_all(&self.dir) {
+        println!("{}", err);
+      }
+    }
+    app
+      .main_window()
+      .capture_frame(self.frame_path(frame.nth() / self.every));
+    self.captured.set(self.captured.get() + 1);
+  }
+
+  /// Waits for the frames to be written, then encodes them.
+  /// Call it from the app's exit function
+  pub fn finish(&self) {
+    if !self.enabled {
+      return;
+    }
+    self.wait_for_frames();
+    let extension = match self.format {
+      Format::Png => return,
+      Format::Gif => "gif",
+      Format::Apng => "apng",
+      Format::WebM => "webm",
+      Format::Mp4 => "mp4",
+    };
+    let output = self.dir.with_extension(extension);
+    match self.format {
+      Format::Gif => self.encode_gif(&output),
+      _ => self.encode_ffmpeg(&output),
+    }
+  }
+
+  // nannou writes captured frames in the background, so the last few may still be in progress
+  fn wait_for_frames(&self) {
+    let start = Instant::now();
+    let complete = || {
+      self
+        .frames()
+        .iter()
+        .filter(|path| is_complete(path))
+        .count()
+    };
+    while complete() < self.captured.get() {
+      if start.elapsed() > FRAME_TIMEOUT {
+        println!("timed out waiting for frames to be written, the animation may be missing some");
+        return;
+      }
+      std::thread::sleep(Duration::from_millis(100));
+    }
+  }
+
+  // recorded frames in order
+  fn frames(&self) -> Vec<PathBuf> {
+    let mut frames: Vec<PathBuf> = match std::fs::read_dir(&self.dir) {
+      Ok(entries) => entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().map_or(false, |ext| ext == "png"))
+        .collect(),
+      Err(err) => {
+        println!("{}", err);
+        vec![]
+      }
+    };
+    // zero-padded, so sorting by name is sorting by frame
+    frames.sort();
+    frames
+  }
+
+  fn encode_gif(&self, output: &std::path::Path) {
+    let file = match File::create(output) {
+      Ok(file) => file,
+      Err(err) => {
+        println!("{}", err);
+        return;
+      }
+    };
+    let mut encoder = GifEncoder::new_with_speed(file, 10);
+    if let Err(err) = encoder.set_repeat(Repeat::Infinite) {
+      println!("{}", err);
+    }
+    let delay = Delay::from_numer_denom_ms(1000, self.fps.round() as u32);
+    for path in self.frames() {
+      let image = match image::open(&path) {
+        Ok(image) => image.to_rgba8(),
+        Err(err) => {
+          println!("{}: {}", path.display(), err);
+          continue;
+        }
+      };
+      if let Err(err) = encoder.encode_frame(ImageFrame::from_parts(image, 0, 0, delay)) {
+        println!("{}", err);
+        return;
+      }
+    }
+    println!("saved {}", output.display());
+  }
+
+  fn encode_ffmpeg(&self, output: &std::path::Path) {
+    let pattern = self.dir.join(format!("{}_%05d.png", self.run_id));
+    let mut command = Command::new("ffmpeg");
+    command
+      .arg("-y")
+      .args(&["-framerate", &self.fps.to_string()])
+      .arg("-i")
+      .arg(&pattern);
+    match self.format {
+      Format::Apng => command.args(&["-plays", "0", "-f", "apng"]),
+      Format::WebM => command.args(&["-c:v", "libvpx-vp9", "-b:v", "0", "-crf", "30"]),
+      // yuv420p is the pixel format every player can handle
+      _ => command.args(&["-c:v", "libx264", "-pix_fmt", "yuv420p", "-crf", "18"]),
+    };
+    match command.arg(output).status() {
+      Ok(status) if status.success() => println!("saved {}", output.display()),
+      Ok(status) => println!(
+        "ffmpeg failed ({}), the frames are in {}",
+        status,
+        self.dir.display()
+      ),
+      Err(err) => println!(
+        "couldn't run ffmpeg ({}), the frames are in {}",
+        err,
+        self.dir.display()
+      ),
+    }
+  }
+}
+
+/// Noise that loops over `phase` in [0, 1]: the phase goes round a circle through
+/// the 3rd and 4th dimensions, so phase 0 and 1 sample the same point.
+/// A bigger `radius` means the noise changes more over one loop
+pub fn loop_noise(noise: &impl NoiseFn<[f64; 4]>, x: f64, y: f64, phase: f32, radius: f64) -> f64 {
+  let angle = phase as f64 * 2.0 * std::f64::consts::PI;
+  noise.get([x, y, radius * angle.cos(), radius * angle.sin()])
+}
+
+/// A single looping value, e.g. to wobble a parameter.
+/// `offset` picks a different, independent wobble from the same noise
+pub fn loop_value(noise: &impl NoiseFn<[f64; 3]>, phase: f32, radius: f64, offset: f64) -> f64 {
+  let angle = phase as f64 * 2.0 * std::f64::consts::PI;
+  noise.get([radius * angle.cos(), radius * angle.sin(), offset])
+}
+
+/// 0 -> 1 -> 0 over one loop, easing in and out at both ends
+pub fn ping_pong(phase: f32) -> f32 {
+  0.5 - (phase * 2.0 * PI).cos() / 2.0
+}
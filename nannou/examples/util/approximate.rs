@@ -0,0 +1,676 @@
+// Approximates a picture with a pile of simple shapes, one shape at a time.
+// Inspired by Michael Fogleman's "primitive" https://github.com/fogleman/primitive
+// and the Nature of Code genetic algorithm sketches (noc_genetic5 evolves colors at target points,
+// this evolves the shapes too).
+//
+// Algorithm in a nutshell:
+// 1. start with a canvas filled with the average color of the target
+// 2. try a bunch of random shapes and keep the one that lowers the error the most
+// 3. hill climb: nudge the shape a little, keep the nudge if the error went down.
+//    With a temperature > 0 some worse nudges are accepted too (simulated annealing),
+//    which helps it get out of local minima
+// 4. paint the shape onto the canvas and go back to 2
+//
+// Each shape's color isn't searched for, it's solved: given where the shape is and its alpha,
+// the best color is just the average of what each covered pixel "wants".
+//
+// Everything happens on a small CPU raster in pixel coordinates (0, 0 is the top left),
+// then `draw` and `svg` scale the shapes up to whatever size you like.
+extern crate image;
+extern crate rayon;
+
+use nannou::prelude::*;
+
+use self::image::imageops::FilterType;
+use self::image::RgbImage;
+use self::rayon::prelude::*;
+
+use super::blob::Blob;
+use super::brush::Brush;
+use super::circle::Circle;
+use super::rng::{random_f32, random_range};
+use super::Line2;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PrimitiveKind {
+  Triangle,
+  Circle,
+  Blob,
+  Stroke,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub enum Primitive {
+  Triangle([Point2; 3]),
+  Circle(Circle),
+  // uses util::blob for the outline
+  Blob {
+    center: Point2,
+    radius: f32,
+    seed: f32,
+    rotation: f32,
+  },
+  // a straight stroke with round ends, drawn with util::brush
+  Stroke {
+    start: Point2,
+    end: Point2,
+    width: f32,
+  },
+}
+
+// horizontal run of pixels: (row, first column, one past the last column)
+type Span = (usize, usize, usize);
+
+impl Primitive {
+  /// A random shape somewhere inside a `width` x `height` image
+  pub fn random(kind: PrimitiveKind, width: usize, height: usize) -> Self {
+    let (w, h) = (width as f32, height as f32);
+    let size = w.max(h);
+    let point = || pt2(random_range(0.0, w), random_range(0.0, h));
+    match kind {
+      PrimitiveKind::Triangle => {
+        let center = point();
+        let corner =
+          || center + vec2(random_range(-1.0, 1.0), random_range(-1.0, 1.0)) * size * 0.1;
+        Primitive::Triangle([corner(), corner(), corner()])
+      }
+      PrimitiveKind::Circle => {
+        let center = point();
+        Primitive::Circle(Circle::new(
+          center.x,
+          center.y,
+          random_range(1.0, size * 0.1),
+        ))
+      }
+      PrimitiveKind::Blob => Primitive::Blob {
+        center: point(),
+        radius: random_range(2.0, size * 0.15),
+        seed: random_range(0.0, 1000.0),
+        rotation: random_range(0.0, 2.0 * PI),
+      },
+      PrimitiveKind::Stroke => {
+        let start = point();
+        let angle = random_range(0.0, 2.0 * PI);
+        Primitive::Stroke {
+          start,
+          end: start + vec2(angle.cos(), angle.sin()) * random_range(2.0, size * 0.2),
+          width: random_range(1.0, size * 0.03),
+        }
+      }
+    }
+  }
+
+  /// A slightly different version of this shape. Points stay within `margin` of the image.
+  pub fn mutate(&self, width: usize, height: usize) -> Self {
+    let (w, h) = (width as f32, height as f32);
+    let step = w.max(h) * 0.04;
+    let margin = w.max(h) * 0.1;
+    let nudge = |p: Point2| {
+      let p = p + vec2(random_range(-step, step), random_range(-step, step));
+      pt2(
+        p.x.max(-margin).min(w + margin),
+        p.y.max(-margin).min(h + margin),
+      )
+    };
+    let resize = |r: f32, min: f32| (r + random_range(-step, step) * 0.5).max(min);
+    match *self {
+      Primitive::Triangle(mut corners) => {
+        let i = random_range(0, 3);
+        corners[i] = nudge(corners[i]);
+        Primitive::Triangle(corners)
+      }
+      Primitive::Circle(circle) => {
+        if random_f32() < 0.5 {
+          let center = nudge(pt2(circle.x, circle.y));
+          Primitive::Circle(Circle::new(center.x, center.y, circle.radius))
+        } else {
+          Primitive::Circle(Circle::new(circle.x, circle.y, resize(circle.radius, 1.0)))
+        }
+      }
+      Primitive::Blob {
+        center,
+        radius,
+        seed,
+        rotation,
+      } => match random_range(0, 3) {
+        0 => Primitive::Blob {
+          center: nudge(center),
+          radius,
+          seed,
+          rotation,
+        },
+        1 => Primitive::Blob {
+          center,
+          radius: resize(radius, 2.0),
+          seed,
+          rotation,
+        },
+        _ => Primitive::Blob {
+          center,
+          radius,
+          seed,
+          rotation: rotation + random_range(-0.5, 0.5),
+        },
+      },
+      Primitive::Stroke { start, end, width } => match random_range(0, 3) {
+        0 => Primitive::Stroke {
+          start: nudge(start),
+          end,
+          width,
+        },
+        1 => Primitive::Stroke {
+          start,
+          end: nudge(end),
+          width,
+        },
+        _ => Primitive::Stroke {
+          start,
+          end,
+          width: resize(width, 1.0),
+        },
+      },
+    }
+  }
+
+  // outline for the shapes that are filled as polygons
+  fn outline(&self) -> Option<Line2> {
+    match *self {
+      Primitive::Triangle(corners) => Some(corners.to_vec()),
+      Primitive::Blob {
+        center,
+        radius,
+        seed,
+        rotation,
+      } => Some(
+        // not Blob::new, the outline is built on rayon's threads and mustn't touch util::rng
+        Blob::seeded(seed)
+          .x_y(center.x, center.y)
+          .radius(radius)
+          .noise_scale(0.9)
+          .rotate_rad(rotation)
+          .resolution(48)
+          .points(),
+      ),
+      _ => None,
+    }
+  }
+
+  // the pixels covered by the shape, clipped to the image
+  fn spans(&self, width: usize, height: usize) -> Vec<Span> {
+    match *self {
+      Primitive::Circle(circle) => {
+        let (cx, cy, r) = (circle.x, circle.y, circle.radius);
+        row_range(cy - r, cy + r, height)
+          .filter_map(|j| {
+            let dy = j as f32 + 0.5 - cy;
+            if dy.abs() > r {
+              return None;
+            }
+            let half = (r * r - dy * dy).sqrt();
+            column_span(j, cx - half, cx + half, width)
+          })
+          .collect()
+      }
+      Primitive::Stroke {
+        start,
+        end,
+        width: w,
+      } => {
+        let half = w / 2.0;
+        let top = start.y.min(end.y) - half;
+        let bottom = start.y.max(end.y) + half;
+        let left = ((start.x.min(end.x) - half).floor().max(0.0)) as usize;
+        let right = ((start.x.max(end.x) + half).ceil().max(0.0) as usize).min(width);
+        // a capsule is convex, so each row is a single run
+        row_range(top, bottom, height)
+          .filter_map(|j| {
+            let inside = |i: usize| {
+              let p = pt2(i as f32 + 0.5, j as f32 + 0.5);
+              distance_to_segment(p, start, end) <= half
+            };
+            let first = (left..right).find(|i| inside(*i))?;
+            let last = (first..right).rev().find(|i| inside(*i))?;
+            Some((j, first, last + 1))
+          })
+          .collect()
+      }
+      _ => polygon_spans(&self.outline().unwrap(), width, height),
+    }
+  }
+
+  /// Draws the shape, with `to_window` mapping pixel coordinates to the window
+  pub fn draw(&self, draw: &Draw, color: [f32; 4], to_window: &dyn Fn(Point2) -> Point2) {
+    let [r, g, b, a] = color;
+    match *self {
+      Primitive::Circle(circle) => {
+        let center = to_window(pt2(circle.x, circle.y));
+        let edge = to_window(pt2(circle.x + circle.radius, circle.y));
+        draw
+          .ellipse()
+          .xy(center)
+          .radius(center.distance(edge))
+          .rgba(r, g, b, a);
+      }
+      Primitive::Stroke { start, end, width } => {
+        let scale = to_window(pt2(1.0, 0.0)).distance(to_window(pt2(0.0, 0.0)));
+        let (h, s, l) = Hsl::from(Rgb::new(r, g, b)).into_components();
+        Brush::new()
+          .width(width * scale / 2.0)
+          .hsla(h.to_positive_degrees() / 360.0, s, l, a)
+          .stroke(to_window(start), to_window(end), draw);
+      }
+      _ => {
+        let points: Line2 = self.outline().unwrap().into_iter().map(to_window).collect();
+        draw.polygon().rgba(r, g, b, a).points(points);
+      }
+    }
+  }
+
+  /// An SVG element for the shape, in pixel coordinates
+  pub fn svg(&self, color: [f32; 4]) -> String {
+    let fill = format!(
+      "rgb({},{},{})",
+      (color[0] * 255.0).round(),
+      (color[1] * 255.0).round(),
+      (color[2] * 255.0).round()
+    );
+    match *self {
+      Primitive::Circle(circle) => format!(
+        "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\" fill=\"{}\" fill-opacity=\"{:.3}\" />",
+        circle.x, circle.y, circle.radius, fill, color[3]
+      ),
+      Primitive::Stroke { start, end, width } => format!(
+        "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"{}\" stroke-opacity=\"{:.3}\" stroke-width=\"{:.2}\" stroke-linecap=\"round\" />",
+        start.x, start.y, end.x, end.y, fill, color[3], width
+      ),
+      _ => {
+        let points: Vec<String> = self
+          .outline()
+          .unwrap()
+          .iter()
+          .map(|p| format!("{:.2},{:.2}", p.x, p.y))
+          .collect();
+        format!(
+          "<polygon points=\"{}\" fill=\"{}\" fill-opacity=\"{:.3}\" />",
+          points.join(" "),
+          fill,
+          color[3]
+        )
+      }
+    }
+  }
+}
+
+pub struct Approximation {
+  pub width: usize,
+  pub height: usize,
+  // row-major rgb, j = 0 at the top
+  target: Vec<[f32; 3]>,
+  canvas: Vec<[f32; 3]>,
+  background: [f32; 3],
+  shapes: Vec<(Primitive, [f32; 4])>,
+  kinds: Vec<PrimitiveKind>,
+  alpha: f32,
+  // random shapes to try before hill climbing
+  candidates: usize,
+  // mutations tried per shape
+  climbs: usize,
+  // starting temperature for simulated annealing, relative to the current error.
+  // 0 is plain hill climbing, 0.01 accepts a nudge that's 1% worse about a third of the time
+  temperature: f32,
+  // sum of squared errors over every pixel and channel
+  error: f32,
+}
+
+impl Approximation {
+  /// Loads the target from disk, scaled so the longest side is at most `max_size` pixels.
+  /// Keep it small (100-300px), the shapes scale up fine when they're drawn.
+  pub fn open(path: &str, max_size: u32) -> Self {
+    let img = image::open(path)
+      .unwrap_or_else(|err| panic!("failed to open image {}: {}", path, err))
+      .to_rgb8();
+    let (w, h) = img.dimensions();
+    let scale = (max_size as f32 / w.max(h) as f32).min(1.0);
+    let img = if scale < 1.0 {
+      image::imageops::resize(
+        &img,
+        (w as f32 * scale).round() as u32,
+        (h as f32 * scale).round() as u32,
+        FilterType::Triangle,
+      )
+    } else {
+      img
+    };
+    Self::from_rgb(&img)
+  }
+
+  pub fn from_rgb(img: &RgbImage) -> Self {
+    let (w, h) = img.dimensions();
+    Self::from_fn(w as usize, h as usize, |i, j| {
+      let pixel = img.get_pixel(i as u32, j as u32);
+      [
+        pixel[0] as f32 / 255.0,
+        pixel[1] as f32 / 255.0,
+        pixel[2] as f32 / 255.0,
+      ]
+    })
+  }
+
+  /// Target from a function of (i, j) pixel coordinates returning rgb in [0, 1]
+  pub fn from_fn(width: usize, height: usize, f: impl Fn(usize, usize) -> [f32; 3]) -> Self {
+    let mut target = Vec::with_capacity(width * height);
+    for j in 0..height {
+      for i in 0..width {
+        target.push(f(i, j));
+      }
+    }
+    let n = target.len().max(1) as f32;
+    let background = target.iter().fold([0.0; 3], |sum, c| {
+      [sum[0] + c[0] / n, sum[1] + c[1] / n, sum[2] + c[2] / n]
+    });
+    let canvas = vec![background; target.len()];
+    let error = target.iter().map(|t| pixel_error(*t, background)).sum();
+    Approximation {
+      width,
+      height,
+      target,
+      canvas,
+      background,
+      shapes: vec![],
+      kinds: vec![PrimitiveKind::Triangle],
+      alpha: 0.5,
+      candidates: 100,
+      climbs: 200,
+      temperature: 0.0,
+      error,
+    }
+  }
+
+  /// Which kinds of shapes to use. Each new shape picks one at random.
+  pub fn kinds(mut self, kinds: Vec<PrimitiveKind>) -> Self {
+    self.kinds = kinds;
+    self
+  }
+
+  pub fn alpha(mut self, alpha: f32) -> Self {
+    self.alpha = alpha.max(0.01).min(1.0);
+    self
+  }
+
+  pub fn candidates(mut self, candidates: usize) -> Self {
+    self.candidates = candidates.max(1);
+    self
+  }
+
+  pub fn climbs(mut self, climbs: usize) -> Self {
+    self.climbs = climbs;
+    self
+  }
+
+  pub fn temperature(mut self, temperature: f32) -> Self {
+    self.temperature = temperature;
+    self
+  }
+
+  pub fn shapes(&self) -> &[(Primitive, [f32; 4])] {
+    &self.shapes
+  }
+
+  pub fn background(&self) -> [f32; 3] {
+    self.background
+  }
+
+  /// Root mean squared error over every pixel and channel, in [0, 1]
+  pub fn error(&self) -> f32 {
+    (self.error / (self.target.len() * 3).max(1) as f32).sqrt()
+  }
+
+  /// Squared error of a single pixel, summed over the channels
+  pub fn pixel_error(&self, i: usize, j: usize) -> f32 {
+    let index = j * self.width + i;
+    pixel_error(self.target[index], self.canvas[index])
+  }
+
+  /// The current canvas color of a pixel
+  pub fn pixel(&self, i: usize, j: usize) -> [f32; 3] {
+    self.canvas[j * self.width + i]
+  }
+
+  /// Adds `n` shapes, printing progress as it goes
+  pub fn fit(&mut self, n: usize) {
+    for i in 0..n {
+      self.add_shape();
+      if i % 10 == 0 {
+        println!("{} of {} shapes, error {:.4}", i, n, self.error());
+      }
+    }
+  }
+
+  /// Searches for the best next shape and paints it onto the canvas.
+  /// Returns false if nothing it tried made the picture any better.
+  pub fn add_shape(&mut self) -> bool {
+    let kind = self.kinds[random_range(0, self.kinds.len())];
+    let (width, height) = (self.width, self.height);
+
+    // the candidates are drawn up front so the seeded util::rng is used in the same order every run,
+    // only scoring them happens in parallel
+    let candidates: Vec<Primitive> = (0..self.candidates)
+      .map(|_| Primitive::random(kind, width, height))
+      .collect();
+    let (mut current, mut current_energy) = candidates
+      .into_par_iter()
+      .map(|primitive| (primitive, self.energy(&primitive).0))
+      .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+      .unwrap();
+    let (mut best, mut best_energy) = (current, current_energy);
+
+    for climb in 0..self.climbs {
+      let candidate = current.mutate(width, height);
+      let (energy, _) = self.energy(&candidate);
+      // cools down linearly to 0 over the climbs
+      let temperature = self.temperature * self.error * (1.0 - climb as f32 / self.climbs as f32);
+      let accept = energy < current_energy
+        || (temperature > 0.0 && random_f32() < ((current_energy - energy) / temperature).exp());
+      if accept {
+        current = candidate;
+        current_energy = energy;
+        if energy < best_energy {
+          best = candidate;
+          best_energy = energy;
+        }
+      }
+    }
+
+    if best_energy >= self.error {
+      return false;
+    }
+    let (energy, color) = self.energy(&best);
+    for (j, start, end) in best.spans(width, height) {
+      for index in (j * width + start)..(j * width + end) {
+        self.canvas[index] = blend(self.canvas[index], color);
+      }
+    }
+    self.error = energy;
+    self.shapes.push((best, color));
+    true
+  }
+
+  // total error if this shape were added with its best color, and that color
+  fn energy(&self, primitive: &Primitive) -> (f32, [f32; 4]) {
+    let spans = primitive.spans(self.width, self.height);
+    let a = self.alpha;
+    // best color is the average of (target - canvas * (1 - a)) / a over the covered pixels
+    let mut sum = [0.0; 3];
+    let mut count = 0.0;
+    for (j, start, end) in &spans {
+      for index in (j * self.width + start)..(j * self.width + end) {
+        let (t, c) = (self.target[index], self.canvas[index]);
+        for k in 0..3 {
+          sum[k] += (t[k] - c[k] * (1.0 - a)) / a;
+        }
+        count += 1.0;
+      }
+    }
+    if count == 0.0 {
+      return (self.error, [0.0, 0.0, 0.0, a]);
+    }
+    let color = [
+      (sum[0] / count).max(0.0).min(1.0),
+      (sum[1] / count).max(0.0).min(1.0),
+      (sum[2] / count).max(0.0).min(1.0),
+      a,
+    ];
+    let mut error = self.error;
+    for (j, start, end) in &spans {
+      for index in (j * self.width + start)..(j * self.width + end) {
+        let (t, c) = (self.target[index], self.canvas[index]);
+        error += pixel_error(t, blend(c, color)) - pixel_error(t, c);
+      }
+    }
+    (error, color)
+  }
+
+  /// Draws the background and every shape, stretched to fill `win`
+  pub fn draw(&self, draw: &Draw, win: &Rect) {
+    let [r, g, b] = self.background;
+    draw.rect().xy(win.xy()).wh(win.wh()).rgb(r, g, b);
+    let (w, h) = (self.width as f32, self.height as f32);
+    let to_window = |p: Point2| {
+      pt2(
+        map_range(p.x, 0.0, w, win.left(), win.right()),
+        map_range(p.y, 0.0, h, win.top(), win.bottom()),
+      )
+    };
+    for (primitive, color) in &self.shapes {
+      primitive.draw(draw, *color, &to_window);
+    }
+  }
+
+  /// The background and every shape as an SVG document, `scale` times the size of the raster
+  pub fn svg(&self, scale: f32) -> String {
+    let (w, h) = (self.width as f32, self.height as f32);
+    let [r, g, b] = self.background;
+    let mut svg = vec![
+      format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">",
+        w * scale,
+        h * scale,
+        w,
+        h
+      ),
+      format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"rgb({},{},{})\" />",
+        w,
+        h,
+        (r * 255.0).round(),
+        (g * 255.0).round(),
+        (b * 255.0).round()
+      ),
+    ];
+    for (primitive, color) in &self.shapes {
+      svg.push(primitive.svg(*color));
+    }
+    svg.push(String::from("</svg>"));
+    svg.join("\n")
+  }
+
+  pub fn save_svg(&self, path: &std::path::Path, scale: f32) {
+    if let Some(dir) = path.parent() {
+      std::fs::create_dir_all(dir).ok();
+    }
+    if let Err(err) = std::fs::write(path, self.svg(scale)) {
+      println!("{}", err);
+    }
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every pixel
+impl std::fmt::Debug for Approximation {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Approximation")
+      .field("width", &self.width)
+      .field("height", &self.height)
+      .field("n_shapes", &self.shapes.len())
+      .field("kinds", &self.kinds)
+      .field("alpha", &self.alpha)
+      .field("candidates", &self.candidates)
+      .field("climbs", &self.climbs)
+      .field("temperature", &self.temperature)
+      .field("error", &self.error())
+      .finish()
+  }
+}
+
+fn pixel_error(a: [f32; 3], b: [f32; 3]) -> f32 {
+  (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)
+}
+
+fn blend(canvas: [f32; 3], color: [f32; 4]) -> [f32; 3] {
+  let a = color[3];
+  [
+    canvas[0] * (1.0 - a) + color[0] * a,
+    canvas[1] * (1.0 - a) + color[1] * a,
+    canvas[2] * (1.0 - a) + color[2] * a,
+  ]
+}
+
+fn distance_to_segment(p: Point2, a: Point2, b: Point2) -> f32 {
+  let ab = b - a;
+  let length_squared = ab.x * ab.x + ab.y * ab.y;
+  if length_squared < std::f32::EPSILON {
+    return p.distance(a);
+  }
+  let t = (((p - a).x * ab.x + (p - a).y * ab.y) / length_squared)
+    .max(0.0)
+    .min(1.0);
+  p.distance(a + ab * t)
+}
+
+// rows whose pixel centers are between top and bottom
+fn row_range(top: f32, bottom: f32, height: usize) -> std::ops::Range<usize> {
+  let first = (top - 0.5).ceil().max(0.0) as usize;
+  let last = ((bottom - 0.5).floor() + 1.0).max(0.0) as usize;
+  first.min(height)..last.min(height)
+}
+
+// pixels in row j whose centers are between left and right
+fn column_span(j: usize, left: f32, right: f32, width: usize) -> Option<Span> {
+  let first = (left - 0.5).ceil().max(0.0) as usize;
+  let last = (((right - 0.5).floor() + 1.0).max(0.0) as usize).min(width);
+  if first < last {
+    Some((j, first, last))
+  } else {
+    None
+  }
+}
+
+// scanline fill with the even-odd rule
+fn polygon_spans(polygon: &Line2, width: usize, height: usize) -> Vec<Span> {
+  if polygon.len() < 3 {
+    return vec![];
+  }
+  let top = polygon.iter().map(|p| p.y).fold(std::f32::MAX, f32::min);
+  let bottom = polygon.iter().map(|p| p.y).fold(std::f32::MIN, f32::max);
+  let mut spans = vec![];
+  let mut crossings = vec![];
+  for j in row_range(top, bottom, height) {
+    let y = j as f32 + 0.5;
+    crossings.clear();
+    for i in 0..polygon.len() {
+      let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+      if (a.y <= y && b.y > y) || (b.y <= y && a.y > y) {
+        crossings.push(a.x + (y - a.y) / (b.y - a.y) * (b.x - a.x));
+      }
+    }
+    crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    for pair in crossings.chunks(2) {
+      if pair.len() == 2 {
+        if let Some(span) = column_span(j, pair[0], pair[1], width) {
+          spans.push(span);
+        }
+      }
+    }
+  }
+  spans
+}
@@ -1,7 +1,26 @@
 // AGAINST ALL ODDS, THIS WORKS.....
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
+use std::sync::Mutex;
+
+// every argument a sketch asked for and the value it ended up with (including defaults),
+// so captures can record exactly how they were made, see util::provenance.
+// Global because most sketches make a new ArgParser wherever they need one
+static RESOLVED: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// Remembers the value used for an argument
+pub fn record(key: &str, value: &str) {
+  RESOLVED
+    .lock()
+    .unwrap()
+    .insert(String::from(key), String::from(value));
+}
+
+/// Every argument read so far with its resolved value
+pub fn resolved() -> BTreeMap<String, String> {
+  RESOLVED.lock().unwrap().clone()
+}
 
 #[derive(Debug)]
 pub struct ArgParser {
@@ -33,66 +52,88 @@ impl ArgParser {
     ArgParser { map }
   }
 
+  /// The value of an argument if it was given and parses, without recording it (see `resolved`).
+  /// For arguments about how the sketch runs rather than what it draws
+  pub fn peek<T: FromStr>(&self, key: &str) -> Option<T> {
+    self.map.get(key).and_then(|value| value.parse::<T>().ok())
+  }
+
   // Ok, obviously use this instead of the type-specific methods below.
   // I didn't realize generics could be used this way, this is obviously better.
   // The other methods are still around for earlier examples that are using them
-  pub fn get<T: FromStr>(&self, key: &str, default: T) -> T {
-    match self.map.get(key) {
+  pub fn get<T: FromStr + ToString>(&self, key: &str, default: T) -> T {
+    let value = match self.map.get(key) {
       Some(thing) => match thing.parse::<T>() {
         Ok(val) => val,
         Err(_err) => default,
       },
       None => default,
-    }
+    };
+    record(key, &value.to_string());
+    value
   }
 
   pub fn get_f32(&self, key: &str, default: f32) -> f32 {
-    match self.map.get(key) {
+    let value = match self.map.get(key) {
       Some(num) => num.parse::<f32>().unwrap(),
       None => default,
-    }
+    };
+    record(key, &value.to_string());
+    value
   }
 
   pub fn get_f64(&self, key: &str, default: f64) -> f64 {
-    match self.map.get(key) {
+    let value = match self.map.get(key) {
       Some(num) => num.parse::<f64>().unwrap(),
       None => default,
-    }
+    };
+    record(key, &value.to_string());
+    value
   }
 
   pub fn get_i32(&self, key: &str, default: i32) -> i32 {
-    match self.map.get(key) {
+    let value = match self.map.get(key) {
       Some(num) => num.parse::<i32>().unwrap(),
       None => default,
-    }
+    };
+    record(key, &value.to_string());
+    value
   }
 
   pub fn get_usize(&self, key: &str, default: usize) -> usize {
-    match self.map.get(key) {
+    let value = match self.map.get(key) {
       Some(num) => num.parse::<usize>().unwrap(),
       None => default,
-    }
+    };
+    record(key, &value.to_string());
+    value
   }
 
   pub fn get_u64(&self, key: &str, default: u64) -> u64 {
-    match self.map.get(key) {
+    let value = match self.map.get(key) {
       Some(num) => num.parse::<u64>().unwrap(),
       None => default,
-    }
+    };
+    record(key, &value.to_string());
+    value
   }
 
   pub fn get_bool(&self, key: &str, default: bool) -> bool {
-    match self.map.get(key) {
+    let value = match self.map.get(key) {
       Some(num) => num.parse::<bool>().unwrap(),
       None => default,
-    }
+    };
+    record(key, &value.to_string());
+    value
   }
 
   pub fn get_string(&self, key: &str, default: &str) -> String {
-    match self.map.get(key) {
+    let value = match self.map.get(key) {
       Some(thing) => thing.to_string(),
       None => default.to_string(),
-    }
+    };
+    record(key, &value);
+    value
   }
 }
 
@@ -0,0 +1,630 @@
+// Strange attractors and other iterated maps.
+// Great gallery and parameters: http://paulbourke.net/fractals/
+//
+// Plotting the raw points of an orbit looks like noise or a few dots.
+// The nice smooth renders come from counting how many times the orbit lands in each pixel,
+// then mapping the counts to brightness on a log scale, since a few pixels get hit
+// millions of times more than the rest. That's what `Histogram` is for:
+//
+//   let mut histogram = Histogram::for_map(&Clifford::new(-1.4, 1.6, 1.0, 0.7), 1024, 1024);
+//   histogram.accumulate(&Clifford::new(-1.4, 1.6, 1.0, 0.7), 16, 1_000_000);
+//   histogram.draw(&draw, &win, 2.0, |tone| hsla(0.6, 0.5, tone, 1.0));
+extern crate image;
+extern crate rayon;
+
+use nannou::prelude::*;
+use nannou::rand::rngs::StdRng;
+use nannou::rand::{Rng, SeedableRng};
+
+use self::image::GrayImage;
+use self::rayon::prelude::*;
+
+use super::rng;
+
+/// A 2D iterated map: every point of an orbit is calculated from the one before.
+/// The map holds its own state, so flows like Lorenz that live in 3D can still be projected down to 2D.
+pub trait Map2: Clone + Send + Sync {
+  /// Steps the orbit forward and returns the new point
+  fn next(&mut self) -> Point2;
+
+  /// Starts a new orbit from a random point, drawn from `rng` (see `orbit_rng`)
+  fn reset(&mut self, rng: &mut StdRng);
+
+  /// Points at the start of an orbit that are skipped before it settles onto the attractor
+  fn warmup(&self) -> usize {
+    100
+  }
+}
+
+/// x' = 1 - a x^2 + y, y' = b x
+/// https://en.wikipedia.org/wiki/H%C3%A9non_map
+#[derive(Debug, Clone)]
+pub struct Henon {
+  a: f32,
+  b: f32,
+  point: Point2,
+}
+
+impl Henon {
+  pub fn new(a: f32, b: f32) -> Self {
+    Henon {
+      a,
+      b,
+      point: pt2(0.0, 0.0),
+    }
+  }
+
+  pub fn classic() -> Self {
+    Self::new(1.4, 0.3)
+  }
+}
+
+impl Map2 for Henon {
+  fn next(&mut self) -> Point2 {
+    let Point2 { x, y } = self.point;
+    self.point = pt2(1.0 - self.a * x * x + y, self.b * x);
+    self.point
+  }
+
+  fn reset(&mut self, rng: &mut StdRng) {
+    self.point = near_origin(rng);
+  }
+}
+
+/// The area-preserving quadratic Hénon map, rotating by `angle` every step.
+/// Orbits don't settle onto a single attractor, each starting point traces its own
+/// island or ring, so it needs lots of short orbits instead of one long one.
+/// Port of complexification.net's henonPhaseDeep, see xp_henon_phase
+#[derive(Debug, Clone)]
+pub struct HenonPhase {
+  angle: f32,
+  point: Point2,
+}
+
+impl HenonPhase {
+  pub fn new(angle: f32) -> Self {
+    HenonPhase {
+      angle,
+      point: pt2(0.0, 0.0),
+    }
+  }
+
+  /// Starts the orbit from `point` rather than a random one
+  pub fn start(mut self, point: Point2) -> Self {
+    self.point = point;
+    self
+  }
+}
+
+impl Map2 for HenonPhase {
+  fn next(&mut self) -> Point2 {
+    let Point2 { x, y } = self.point;
+    let (sin, cos) = (self.angle.sin(), self.angle.cos());
+    self.point = pt2(x * cos - (y - x * x) * sin, x * sin + (y - x * x) * cos);
+    self.point
+  }
+
+  fn reset(&mut self, rng: &mut StdRng) {
+    self.point = pt2(rng.gen(), rng.gen());
+  }
+
+  fn warmup(&self) -> usize {
+    0
+  }
+}
+
+/// x' = sin(a y) + c cos(a x), y' = sin(b x) + d cos(b y)
+#[derive(Debug, Clone)]
+pub struct Clifford {
+  a: f32,
+  b: f32,
+  c: f32,
+  d: f32,
+  point: Point2,
+}
+
+impl Clifford {
+  pub fn new(a: f32, b: f32, c: f32, d: f32) -> Self {
+    Clifford {
+      a,
+      b,
+      c,
+      d,
+      point: pt2(0.0, 0.0),
+    }
+  }
+}
+
+impl Map2 for Clifford {
+  fn next(&mut self) -> Point2 {
+    let Point2 { x, y } = self.point;
+    self.point = pt2(
+      (self.a * y).sin() + self.c * (self.a * x).cos(),
+      (self.b * x).sin() + self.d * (self.b * y).cos(),
+    );
+    self.point
+  }
+
+  fn reset(&mut self, rng: &mut StdRng) {
+    self.point = near_origin(rng);
+  }
+}
+
+/// Peter de Jong's map: x' = sin(a y) - cos(b x), y' = sin(c x) - cos(d y)
+#[derive(Debug, Clone)]
+pub struct DeJong {
+  a: f32,
+  b: f32,
+  c: f32,
+  d: f32,
+  point: Point2,
+}
+
+pub type PeterDeJong = DeJong;
+
+impl DeJong {
+  pub fn new(a: f32, b: f32, c: f32, d: f32) -> Self {
+    DeJong {
+      a,
+      b,
+      c,
+      d,
+      point: pt2(0.0, 0.0),
+    }
+  }
+}
+
+impl Map2 for DeJong {
+  fn next(&mut self) -> Point2 {
+    let Point2 { x, y } = self.point;
+    self.point = pt2(
+      (self.a * y).sin() - (self.b * x).cos(),
+      (self.c * x).sin() - (self.d * y).cos(),
+    );
+    self.point
+  }
+
+  fn reset(&mut self, rng: &mut StdRng) {
+    self.point = near_origin(rng);
+  }
+}
+
+/// The Lorenz system, integrated with small Euler steps and viewed from the side.
+/// `rotation` turns it around the vertical (z) axis before it's flattened onto x, z.
+/// https://en.wikipedia.org/wiki/Lorenz_system
+#[derive(Debug, Clone)]
+pub struct Lorenz {
+  sigma: f32,
+  rho: f32,
+  beta: f32,
+  dt: f32,
+  rotation: f32,
+  point: Point3,
+}
+
+impl Lorenz {
+  pub fn new(sigma: f32, rho: f32, beta: f32) -> Self {
+    Lorenz {
+      sigma,
+      rho,
+      beta,
+      dt: 0.005,
+      rotation: 0.0,
+      point: pt3(0.1, 0.0, 0.0),
+    }
+  }
+
+  pub fn classic() -> Self {
+    Self::new(10.0, 28.0, 8.0 / 3.0)
+  }
+
+  pub fn dt(mut self, dt: f32) -> Self {
+    self.dt = dt;
+    self
+  }
+
+  pub fn rotate(mut self, radians: f32) -> Self {
+    self.rotation = radians;
+    self
+  }
+}
+
+impl Map2 for Lorenz {
+  fn next(&mut self) -> Point2 {
+    let Point3 { x, y, z } = self.point;
+    self.point = pt3(
+      x + self.sigma * (y - x) * self.dt,
+      y + (x * (self.rho - z) - y) * self.dt,
+      z + (x * y - self.beta * z) * self.dt,
+    );
+    let (sin, cos) = (self.rotation.sin(), self.rotation.cos());
+    pt2(self.point.x * cos - self.point.y * sin, self.point.z)
+  }
+
+  fn reset(&mut self, rng: &mut StdRng) {
+    self.point = pt3(
+      rng.gen_range(-1.0, 1.0),
+      rng.gen_range(-1.0, 1.0),
+      rng.gen_range(0.0, 1.0),
+    );
+  }
+
+  fn warmup(&self) -> usize {
+    1000
+  }
+}
+
+/// https://en.wikipedia.org/wiki/Ikeda_map
+#[derive(Debug, Clone)]
+pub struct Ikeda {
+  u: f32,
+  point: Point2,
+}
+
+impl Ikeda {
+  pub fn new(u: f32) -> Self {
+    Ikeda {
+      u,
+      point: pt2(0.0, 0.0),
+    }
+  }
+}
+
+impl Map2 for Ikeda {
+  fn next(&mut self) -> Point2 {
+    let Point2 { x, y } = self.point;
+    let t = 0.4 - 6.0 / (1.0 + x * x + y * y);
+    self.point = pt2(
+      1.0 + self.u * (x * t.cos() - y * t.sin()),
+      self.u * (x * t.sin() + y * t.cos()),
+    );
+    self.point
+  }
+
+  fn reset(&mut self, rng: &mut StdRng) {
+    self.point = near_origin(rng);
+  }
+}
+
+/// x' = x^2 - y^2 + a x + b y, y' = 2 x y + c x + d y
+/// https://en.wikipedia.org/wiki/Tinkerbell_map
+#[derive(Debug, Clone)]
+pub struct Tinkerbell {
+  a: f32,
+  b: f32,
+  c: f32,
+  d: f32,
+  point: Point2,
+}
+
+impl Tinkerbell {
+  pub fn new(a: f32, b: f32, c: f32, d: f32) -> Self {
+    Tinkerbell {
+      a,
+      b,
+      c,
+      d,
+      point: pt2(-0.72, -0.64),
+    }
+  }
+
+  pub fn classic() -> Self {
+    Self::new(0.9, -0.6013, 2.0, 0.5)
+  }
+}
+
+impl Map2 for Tinkerbell {
+  fn next(&mut self) -> Point2 {
+    let Point2 { x, y } = self.point;
+    self.point = pt2(
+      x * x - y * y + self.a * x + self.b * y,
+      2.0 * x * y + self.c * x + self.d * y,
+    );
+    self.point
+  }
+
+  // it only has a small basin of attraction, most other starting points fly off to infinity
+  fn reset(&mut self, rng: &mut StdRng) {
+    self.point = pt2(
+      -0.72 + rng.gen_range(-0.01, 0.01),
+      -0.64 + rng.gen_range(-0.01, 0.01),
+    );
+  }
+}
+
+/// Gumowski–Mira map, makes very organic looking shapes that change a lot with `mu`
+/// https://en.wikipedia.org/wiki/Gumowski%E2%80%93Mira_attractor
+#[derive(Debug, Clone)]
+pub struct GumowskiMira {
+  a: f32,
+  b: f32,
+  mu: f32,
+  point: Point2,
+}
+
+impl GumowskiMira {
+  pub fn new(a: f32, b: f32, mu: f32) -> Self {
+    GumowskiMira {
+      a,
+      b,
+      mu,
+      point: pt2(0.0, 0.5),
+    }
+  }
+
+  fn f(&self, x: f32) -> f32 {
+    self.mu * x + 2.0 * (1.0 - self.mu) * x * x / (1.0 + x * x)
+  }
+}
+
+impl Map2 for GumowskiMira {
+  fn next(&mut self) -> Point2 {
+    let Point2 { x, y } = self.point;
+    let next_x = y + self.a * (1.0 - self.b * y * y) * y + self.f(x);
+    self.point = pt2(next_x, -x + self.f(next_x));
+    self.point
+  }
+
+  fn reset(&mut self, rng: &mut StdRng) {
+    self.point = pt2(rng.gen_range(-1.0, 1.0), rng.gen_range(-1.0, 1.0));
+  }
+}
+
+fn near_origin(rng: &mut StdRng) -> Point2 {
+  pt2(rng.gen_range(-0.1, 0.1), rng.gen_range(-0.1, 0.1))
+}
+
+/// A generator for one orbit, from the run's seed (util::rng::seed) and the orbit's index.
+/// Orbits run on every core in whatever order rayon likes, but each one always starts
+/// from the same points, so the histogram comes out the same for the same seed
+pub fn orbit_rng(seed: u64, orbit: usize) -> StdRng {
+  StdRng::seed_from_u64(seed.wrapping_add(orbit as u64))
+}
+
+/// Bounding rect of an orbit. The most extreme 0.1% of points are left out,
+/// so an orbit that wanders off towards infinity before it escapes doesn't blow up the bounds.
+/// Good enough to size a Histogram after a few thousand points.
+pub fn bounds<M: Map2>(map: &M, iterations: usize) -> Rect {
+  let mut rng = orbit_rng(rng::seed(), 0);
+  let mut map = map.clone();
+  map.reset(&mut rng);
+  for _ in 0..map.warmup() {
+    map.next();
+  }
+  let mut xs = Vec::with_capacity(iterations);
+  let mut ys = Vec::with_capacity(iterations);
+  for _ in 0..iterations {
+    let p = map.next();
+    if !p.x.is_finite() || !p.y.is_finite() {
+      map.reset(&mut rng);
+      continue;
+    }
+    xs.push(p.x);
+    ys.push(p.y);
+  }
+  if xs.is_empty() {
+    return Rect::from_w_h(2.0, 2.0);
+  }
+  xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+  ys.sort_by(|a, b| a.partial_cmp(b).unwrap());
+  let trim = xs.len() / 2000;
+  let (left, right) = (xs[trim], xs[xs.len() - 1 - trim]);
+  let (bottom, top) = (ys[trim], ys[ys.len() - 1 - trim]);
+  Rect::from_x_y_w_h(
+    (left + right) / 2.0,
+    (bottom + top) / 2.0,
+    (right - left).max(1e-6),
+    (top - bottom).max(1e-6),
+  )
+}
+
+/// Grows `bounds` to match the aspect ratio of `target` and adds `padding` (a fraction of the size)
+/// on all sides, so a shape mapped from one to the other isn't stretched and doesn't touch the edges
+pub fn fit(bounds: &Rect, target: &Rect, padding: f32) -> Rect {
+  let (w, h) = (
+    bounds.w() * (1.0 + padding * 2.0),
+    bounds.h() * (1.0 + padding * 2.0),
+  );
+  let aspect = target.w() / target.h();
+  let (w, h) = if w / h > aspect {
+    (w, w / aspect)
+  } else {
+    (h * aspect, h)
+  };
+  Rect::from_x_y_w_h(bounds.x(), bounds.y(), w, h)
+}
+
+/// Maps a point from attractor space to the window, see `fit`
+pub fn to_window(point: Point2, bounds: &Rect, win: &Rect) -> Point2 {
+  pt2(
+    map_range(
+      point.x,
+      bounds.left(),
+      bounds.right(),
+      win.left(),
+      win.right(),
+    ),
+    map_range(
+      point.y,
+      bounds.bottom(),
+      bounds.top(),
+      win.bottom(),
+      win.top(),
+    ),
+  )
+}
+
+/// Counts how often an orbit lands in each pixel
+pub struct Histogram {
+  pub width: usize,
+  pub height: usize,
+  // area of attractor space that the histogram covers
+  bounds: Rect,
+  // row-major, j = 0 at the bottom like the window
+  counts: Vec<u32>,
+}
+
+impl Histogram {
+  pub fn new(width: usize, height: usize, bounds: Rect) -> Self {
+    Histogram {
+      width,
+      height,
+      bounds,
+      counts: vec![0; width * height],
+    }
+  }
+
+  /// Sizes the bounds automatically from a sample orbit, with the attractor centered and a little padding
+  pub fn for_map<M: Map2>(map: &M, width: usize, height: usize) -> Self {
+    let target = Rect::from_w_h(width as f32, height as f32);
+    Self::new(width, height, fit(&bounds(map, 100_000), &target, 0.05))
+  }
+
+  pub fn bounds(&self) -> &Rect {
+    &self.bounds
+  }
+
+  pub fn count(&self, i: usize, j: usize) -> u32 {
+    self.counts[j * self.width + i]
+  }
+
+  pub fn max(&self) -> u32 {
+    self.counts.iter().cloned().max().unwrap_or(0)
+  }
+
+  pub fn total(&self) -> u64 {
+    self.counts.iter().map(|c| *c as u64).sum()
+  }
+
+  pub fn add(&mut self, point: Point2) {
+    if let Some(index) = self.index(point) {
+      self.counts[index] = self.counts[index].saturating_add(1);
+    }
+  }
+
+  fn index(&self, point: Point2) -> Option<usize> {
+    let b = &self.bounds;
+    let i = ((point.x - b.left()) / b.w() * self.width as f32).floor();
+    let j = ((point.y - b.bottom()) / b.h() * self.height as f32).floor();
+    // NaN fails these too
+    if i >= 0.0 && j >= 0.0 && i < self.width as f32 && j < self.height as f32 {
+      Some(j as usize * self.width + i as usize)
+    } else {
+      None
+    }
+  }
+
+  /// Runs `orbits` orbits of `iterations` points each, in parallel, and adds them all.
+  /// Orbits that escape to infinity start over.
+  pub fn accumulate<M: Map2>(&mut self, map: &M, orbits: usize, iterations: usize) {
+    let seed = rng::seed();
+    let counts = (0..orbits)
+      .into_par_iter()
+      .fold(
+        || vec![0u32; self.counts.len()],
+        |mut counts, orbit| {
+          let mut rng = orbit_rng(seed, orbit);
+          let mut map = map.clone();
+          map.reset(&mut rng);
+          for _ in 0..map.warmup() {
+            map.next();
+          }
+          for _ in 0..iterations {
+            let p = map.next();
+            if !p.x.is_finite() || !p.y.is_finite() {
+              map.reset(&mut rng);
+              continue;
+            }
+            if let Some(index) = self.index(p) {
+              counts[index] = counts[index].saturating_add(1);
+            }
+          }
+          counts
+        },
+      )
+      .reduce(
+        || vec![0u32; self.counts.len()],
+        |mut a, b| {
+          for (a, b) in a.iter_mut().zip(b.iter()) {
+            *a = a.saturating_add(*b);
+          }
+          a
+        },
+      );
+    for (total, count) in self.counts.iter_mut().zip(counts.iter()) {
+      *total = total.saturating_add(*count);
+    }
+  }
+
+  /// Log tone mapped brightness of a pixel in [0, 1].
+  /// gamma > 1 brings out the faint parts, < 1 the dense parts
+  pub fn tone(&self, i: usize, j: usize, gamma: f32) -> f32 {
+    self.tone_with_max(self.count(i, j), self.max(), gamma)
+  }
+
+  fn tone_with_max(&self, count: u32, max: u32, gamma: f32) -> f32 {
+    if count == 0 || max == 0 {
+      return 0.0;
+    }
+    ((count as f32).ln_1p() / (max as f32).ln_1p()).powf(1.0 / gamma)
+  }
+
+  /// Every tone mapped pixel, row-major with j = 0 at the bottom
+  pub fn tones(&self, gamma: f32) -> Vec<f32> {
+    let max = self.max();
+    self
+      .counts
+      .iter()
+      .map(|c| self.tone_with_max(*c, max, gamma))
+      .collect()
+  }
+
+  /// Draws every pixel that was hit as a rectangle colored by `color(tone)`.
+  /// Empty pixels are skipped so the background shows through
+  pub fn draw(&self, draw: &Draw, win: &Rect, gamma: f32, color: impl Fn(f32) -> Hsla) {
+    let w = win.w() / self.width as f32;
+    let h = win.h() / self.height as f32;
+    let max = self.max();
+    for j in 0..self.height {
+      for i in 0..self.width {
+        let count = self.count(i, j);
+        if count == 0 {
+          continue;
+        }
+        draw
+          .rect()
+          .x_y(
+            win.left() + (i as f32 + 0.5) * w,
+            win.bottom() + (j as f32 + 0.5) * h,
+          )
+          .w_h(w, h)
+          .color(color(self.tone_with_max(count, max, gamma)));
+      }
+    }
+  }
+
+  /// Grayscale image of the tones, white on black, at exactly one pixel per bin.
+  /// Handy for renders bigger than the window
+  pub fn to_image(&self, gamma: f32) -> GrayImage {
+    let tones = self.tones(gamma);
+    GrayImage::from_fn(self.width as u32, self.height as u32, |i, j| {
+      // images have j = 0 at the top
+      let index = (self.height - 1 - j as usize) * self.width + i as usize;
+      image::Luma([(tones[index] * 255.0).round() as u8])
+    })
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every bin
+impl std::fmt::Debug for Histogram {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Histogram")
+      .field("width", &self.width)
+      .field("height", &self.height)
+      .field("bounds", &self.bounds)
+      .field("max", &self.max())
+      .field("total", &self.total())
+      .finish()
+  }
+}
@@ -0,0 +1,335 @@
+// 2D cellular automata
+//
+// LifeLike: any "Life-like" rule, written in B/S notation.
+//   B is the number of live neighbors that make a dead cell come alive,
+//   S is the number of live neighbors that keep a live cell alive.
+//   B3/S23 is Conway's Game of Life, B3678/S34678 is Day & Night, B1357/S1357 is Replicator, etc.
+//   https://conwaylife.com/wiki/Life-like_cellular_automaton
+//
+// Cyclic: every cell has one of n states, and a cell advances to the next state (wrapping around)
+//   when at least `threshold` of its neighbors are already in that next state.
+//   Starting from noise, this turns into spirals that eat each other.
+//   https://en.wikipedia.org/wiki/Cyclic_cellular_automaton
+extern crate rayon;
+
+use self::rayon::prelude::*;
+
+use super::reaction_diffusion::{neighbor_table, offset_index, Boundary};
+use super::rng::{random_f32, random_range};
+use super::simulation::Simulation;
+
+pub struct LifeLike {
+  pub width: usize,
+  pub height: usize,
+  rule: String,
+  // indexed by number of live neighbors
+  birth: [bool; 9],
+  survive: [bool; 9],
+  cells: Vec<bool>,
+  next: Vec<bool>,
+  boundary: Boundary,
+  iterations: usize,
+}
+
+impl LifeLike {
+  /// A grid of dead cells. Panics if the rule can't be parsed.
+  pub fn new(width: usize, height: usize, rule: &str) -> Self {
+    let (birth, survive) =
+      parse_rule(rule).unwrap_or_else(|| panic!("Invalid Life-like rule \"{}\"", rule));
+    LifeLike {
+      width,
+      height,
+      rule: String::from(rule),
+      birth,
+      survive,
+      cells: vec![false; width * height],
+      next: vec![false; width * height],
+      boundary: Boundary::Wrap,
+      iterations: 0,
+    }
+  }
+
+  pub fn boundary(mut self, boundary: Boundary) -> Self {
+    self.boundary = boundary;
+    self
+  }
+
+  /// Each cell is alive with probability `density`
+  pub fn randomize(&mut self, density: f32) {
+    for cell in self.cells.iter_mut() {
+      *cell = random_f32() < density;
+    }
+  }
+
+  pub fn set(&mut self, i: usize, j: usize, alive: bool) {
+    self.cells[j * self.width + i] = alive;
+  }
+
+  pub fn alive(&self, i: usize, j: usize) -> bool {
+    self.cells[j * self.width + i]
+  }
+
+  pub fn iterations(&self) -> usize {
+    self.iterations
+  }
+}
+
+impl Simulation for LifeLike {
+  fn width(&self) -> usize {
+    self.width
+  }
+
+  fn height(&self) -> usize {
+    self.height
+  }
+
+  fn step(&mut self) {
+    let width = self.width;
+    let columns = neighbor_table(width, self.boundary);
+    let rows = neighbor_table(self.height, self.boundary);
+    let LifeLike {
+      ref cells,
+      ref mut next,
+      birth,
+      survive,
+      ..
+    } = *self;
+
+    next.par_chunks_mut(width).enumerate().for_each(|(j, row)| {
+      for i in 0..width {
+        let mut count = 0;
+        for (dj, y) in rows[j].iter().enumerate() {
+          for (di, x) in columns[i].iter().enumerate() {
+            if (di != 1 || dj != 1) && cells[y * width + x] {
+              count += 1;
+            }
+          }
+        }
+        row[i] = if cells[j * width + i] {
+          survive[count]
+        } else {
+          birth[count]
+        };
+      }
+    });
+
+    std::mem::swap(&mut self.cells, &mut self.next);
+    self.iterations += 1;
+  }
+
+  fn sample(&self, i: usize, j: usize) -> f32 {
+    if self.alive(i, j) {
+      1.0
+    } else {
+      0.0
+    }
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every cell
+impl std::fmt::Debug for LifeLike {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("LifeLike")
+      .field("width", &self.width)
+      .field("height", &self.height)
+      .field("rule", &self.rule)
+      .field("boundary", &self.boundary)
+      .field("iterations", &self.iterations)
+      .finish()
+  }
+}
+
+/// Parses a rule like "B3/S23" into (birth, survive) lookup tables.
+/// The parts can be in either order and are case-insensitive.
+/// The old "23/3" notation (survive/birth, no letters) works too.
+pub fn parse_rule(rule: &str) -> Option<([bool; 9], [bool; 9])> {
+  let rule = rule.trim().to_uppercase();
+  let parts: Vec<&str> = rule.split('/').collect();
+  if parts.len() != 2 {
+    return None;
+  }
+  let (birth, survive) = match (parts[0].chars().next(), parts[1].chars().next()) {
+    (Some('B'), Some('S')) => (&parts[0][1..], &parts[1][1..]),
+    (Some('S'), Some('B')) => (&parts[1][1..], &parts[0][1..]),
+    _ => (parts[1], parts[0]),
+  };
+  Some((counts(birth)?, counts(survive)?))
+}
+
+// "23" -> lookup table with 2 and 3 set
+fn counts(digits: &str) -> Option<[bool; 9]> {
+  let mut table = [false; 9];
+  for c in digits.chars() {
+    match c.to_digit(10) {
+      Some(n) if n <= 8 => table[n as usize] = true,
+      _ => return None,
+    }
+  }
+  Some(table)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Neighborhood {
+  /// the square around the cell
+  Moore,
+  /// the diamond around the cell
+  VonNeumann,
+}
+
+pub struct Cyclic {
+  pub width: usize,
+  pub height: usize,
+  n_states: u8,
+  threshold: usize,
+  range: usize,
+  neighborhood: Neighborhood,
+  states: Vec<u8>,
+  next: Vec<u8>,
+  boundary: Boundary,
+  iterations: usize,
+}
+
+impl Cyclic {
+  /// Random states, with Griffeath's classic rule: 14 states, range 1, threshold 1, von Neumann neighborhood
+  pub fn new(width: usize, height: usize) -> Self {
+    let mut cyclic = Cyclic {
+      width,
+      height,
+      n_states: 14,
+      threshold: 1,
+      range: 1,
+      neighborhood: Neighborhood::VonNeumann,
+      states: vec![0; width * height],
+      next: vec![0; width * height],
+      boundary: Boundary::Wrap,
+      iterations: 0,
+    };
+    cyclic.randomize();
+    cyclic
+  }
+
+  // also re-randomizes the grid, since old states might be out of range
+  pub fn states(mut self, n_states: u8) -> Self {
+    self.n_states = n_states.max(2);
+    self.randomize();
+    self
+  }
+
+  pub fn threshold(mut self, threshold: usize) -> Self {
+    self.threshold = threshold;
+    self
+  }
+
+  pub fn range(mut self, range: usize) -> Self {
+    self.range = range;
+    self
+  }
+
+  pub fn neighborhood(mut self, neighborhood: Neighborhood) -> Self {
+    self.neighborhood = neighborhood;
+    self
+  }
+
+  pub fn boundary(mut self, boundary: Boundary) -> Self {
+    self.boundary = boundary;
+    self
+  }
+
+  pub fn randomize(&mut self) {
+    let n_states = self.n_states;
+    for state in self.states.iter_mut() {
+      *state = random_range(0, n_states);
+    }
+  }
+
+  pub fn state(&self, i: usize, j: usize) -> u8 {
+    self.states[j * self.width + i]
+  }
+
+  pub fn iterations(&self) -> usize {
+    self.iterations
+  }
+
+  // (dx, dy) of every neighbor
+  fn offsets(&self) -> Vec<(isize, isize)> {
+    let r = self.range as isize;
+    let mut offsets = vec![];
+    for dy in -r..=r {
+      for dx in -r..=r {
+        let inside = match self.neighborhood {
+          Neighborhood::Moore => true,
+          Neighborhood::VonNeumann => dx.abs() + dy.abs() <= r,
+        };
+        if inside && (dx, dy) != (0, 0) {
+          offsets.push((dx, dy));
+        }
+      }
+    }
+    offsets
+  }
+}
+
+impl Simulation for Cyclic {
+  fn width(&self) -> usize {
+    self.width
+  }
+
+  fn height(&self) -> usize {
+    self.height
+  }
+
+  fn step(&mut self) {
+    let offsets = self.offsets();
+    let Cyclic {
+      width,
+      height,
+      n_states,
+      threshold,
+      boundary,
+      ref states,
+      ref mut next,
+      ..
+    } = *self;
+
+    next.par_chunks_mut(width).enumerate().for_each(|(j, row)| {
+      for i in 0..width {
+        let state = states[j * width + i];
+        let successor = (state + 1) % n_states;
+        let count = offsets
+          .iter()
+          .filter(|(dx, dy)| {
+            let x = offset_index(i, *dx, width, boundary);
+            let y = offset_index(j, *dy, height, boundary);
+            states[y * width + x] == successor
+          })
+          .count();
+        row[i] = if count >= threshold { successor } else { state };
+      }
+    });
+
+    std::mem::swap(&mut self.states, &mut self.next);
+    self.iterations += 1;
+  }
+
+  fn sample(&self, i: usize, j: usize) -> f32 {
+    self.state(i, j) as f32 / (self.n_states - 1) as f32
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every cell
+impl std::fmt::Debug for Cyclic {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Cyclic")
+      .field("width", &self.width)
+      .field("height", &self.height)
+      .field("n_states", &self.n_states)
+      .field("threshold", &self.threshold)
+      .field("range", &self.range)
+      .field("neighborhood", &self.neighborhood)
+      .field("boundary", &self.boundary)
+      .field("iterations", &self.iterations)
+      .finish()
+  }
+}
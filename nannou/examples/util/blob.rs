@@ -3,6 +3,8 @@ use nannou::noise::{NoiseFn, Perlin};
 use nannou::prelude::*;
 use std::fmt::{self, Display, Formatter};
 
+use super::rng::{random_f32, random_range};
+
 pub use super::meander;
 pub use super::Line2;
 
@@ -25,14 +27,23 @@ pub struct Blob {
 
 impl Blob {
   pub fn new() -> Blob {
+    let noise_scale = random_range(0.5, 1.0);
+    let seed = random_range(1.0, 10.0.powi(11));
+    let color = [random_f32(), random_f32(), random_f32(), random_f32()];
+    Blob::seeded(seed).noise_scale(noise_scale).color(color)
+  }
+
+  /// Same defaults as `new`, minus the randomness: the shape only depends on `seed`,
+  /// so it's safe to build from several threads
+  pub fn seeded(seed: f32) -> Blob {
     Blob {
       x: 0.0,
       y: 0.0,
-      noise_scale: random_range(0.5, 1.0),
+      noise_scale: 0.75,
       width: 1.0,
       height: 1.0,
-      seed: random_range(1.0, 10.0.powi(11)),
-      color: [random_f32(), random_f32(), random_f32(), random_f32()],
+      seed,
+      color: [0.0, 0.0, 0.0, 1.0],
       fuzziness: 0.0,
       rotation: 0.,
       resolution: 360,
@@ -1,4 +1,10 @@
+use super::color::{rgb_from_hex, Palette5};
+use super::density::DensityMap;
 use super::interp::{lerp, nextf};
+use super::raster::Raster;
+use super::rng::{random_f32, random_range};
+use super::Line2;
+use nannou::noise::{NoiseFn, Perlin, Seedable};
 use nannou::prelude::*;
 use nannou::Draw;
 use std::fmt::{self, Display, Formatter};
@@ -93,35 +99,6 @@ impl Brush {
     }
   }
 
-  // give the stroke a subtle arc by giving it half a sin curve.
-  // Turns out this isn't super easy!
-  fn slight_curve(&self, start: Point2, end: Point2, draw: &Draw) {
-    let (lum, alpha) = self.adjust_lum_alpha();
-    let x_range = end.x - start.x;
-    let y_range = end.y - start.y;
-    let angle = y_range.atan2(x_range);
-    let segments = 20;
-    let dx = x_range / segments as f32;
-    let dy = y_range / segments as f32;
-    let segment_length = start.distance(end) / segments as f32;
-    let points: Vec<Point2> = (0..segments)
-      .map(|n| {
-        let pos = n as f32 / segments as f32;
-        let x = angle.cos() * segment_length + dx * n as f32 + start.x;
-        let y = angle.sin() * segment_length + dy * n as f32 + start.y;
-        // let x = x + (pos * (PI + angle)).cos() * 20.;// + (angle + PI).cos() * 20.;
-        let y = y + (pos * (PI + angle)).sin() * 10.; // + (angle + PI).sin() * 20.;
-        pt2(x, y)
-      })
-      .collect();
-
-    draw
-      .polyline()
-      .color(hsla(self.hsla[0], self.hsla[1], lum, alpha))
-      .weight(0.5)
-      .points(points);
-  }
-
   fn spread(&self, start: Point2, end: Point2, spread: f32) -> (Point2, Point2) {
     // spread points at a right angle from the original line
     let orientation = (end.y - start.y).atan2(end.x - start.x);
@@ -181,3 +158,279 @@ impl Display for Brush {
     write!(f, "Brush<width: {}>", self.width)
   }
 }
+
+// A brush made of individual bristles, loosely after "Hairy Brushes" (Strassmann, 1986).
+// Each bristle has
+// - an offset across the brush, so the bristles fan out when the brush is pressed harder
+// - a stiffness: soft bristles lag behind on curves, which smears the edge of the stroke
+// - its own paint load, which runs out along the stroke and leaves dry-brush gaps
+// Paint only sticks where the paper is high enough. A heavy, wet, slow brush reaches into
+// the valleys of the paper, a dry or fast brush only catches the peaks.
+// The paper is a height map in [0, 1], without one the brush uses Perlin noise as the grain.
+//
+// Paths are sampled at a fixed time step, so points that are further apart are a faster stroke.
+// Strokes paint into a Raster, not a Draw, because paint builds up pixel by pixel.
+#[derive(Debug, Clone)]
+pub struct Bristle {
+  // position across the brush, in [-1, 1]
+  pub offset: f32,
+  // in (0, 1], how quickly the tip catches up with where the brush is
+  pub stiffness: f32,
+  pub paint: f32,
+  pub capacity: f32,
+  pub color: [f32; 3],
+  // where the tip is in the window, None between strokes
+  tip: Option<Point2>,
+}
+
+pub struct BristleBrush {
+  bristles: Vec<Bristle>,
+  width: f32,
+  // paint used per pixel travelled at full pressure, as a fraction of a full load
+  depletion: f32,
+  // alpha of one dab of a fully loaded bristle
+  flow: f32,
+  // how much the paper decides where paint sticks, 0 ignores the paper
+  grain: f32,
+  // how much faster strokes skip over the paper
+  speed_sensitivity: f32,
+  paper: Option<DensityMap>,
+  noise: Perlin,
+  noise_scale: f64,
+}
+
+impl BristleBrush {
+  pub fn new(width: f32, n_bristles: usize) -> Self {
+    let n = n_bristles.max(1);
+    let bristles = (0..n)
+      .map(|i| {
+        // evenly spread with a little jitter, so there are no obvious rows
+        let offset = if n == 1 {
+          0.0
+        } else {
+          map_range(i, 0, n - 1, -1.0, 1.0) + random_range(-0.5, 0.5) / n as f32
+        };
+        let capacity = random_range(0.7, 1.0);
+        Bristle {
+          offset,
+          stiffness: random_range(0.3, 0.9),
+          paint: capacity,
+          capacity,
+          color: [0.0, 0.0, 0.0],
+          tip: None,
+        }
+      })
+      .collect();
+    BristleBrush {
+      bristles,
+      width,
+      depletion: 0.0007,
+      flow: 0.3,
+      grain: 0.8,
+      speed_sensitivity: 0.1,
+      paper: None,
+      noise: Perlin::new(),
+      noise_scale: 0.4,
+    }
+  }
+
+  /// Same color on every bristle
+  pub fn color(mut self, rgb: [f32; 3]) -> Self {
+    for bristle in self.bristles.iter_mut() {
+      bristle.color = rgb;
+    }
+    self
+  }
+
+  /// Each bristle picks up a random color from the palette, so strokes streak
+  pub fn palette(mut self, palette: Palette5) -> Self {
+    for bristle in self.bristles.iter_mut() {
+      let rgb = rgb_from_hex(palette[random_range(0, palette.len())]);
+      bristle.color = [rgb.red, rgb.green, rgb.blue];
+    }
+    self
+  }
+
+  pub fn depletion(mut self, depletion: f32) -> Self {
+    self.depletion = depletion.max(0.0);
+    self
+  }
+
+  pub fn flow(mut self, flow: f32) -> Self {
+    self.flow = flow.max(0.0).min(1.0);
+    self
+  }
+
+  pub fn grain(mut self, grain: f32) -> Self {
+    self.grain = grain.max(0.0).min(1.0);
+    self
+  }
+
+  pub fn speed_sensitivity(mut self, sensitivity: f32) -> Self {
+    self.speed_sensitivity = sensitivity.max(0.0);
+    self
+  }
+
+  /// Height map of the paper, stretched over the raster like DensityMap::sample stretches over the window
+  pub fn paper(mut self, heights: DensityMap) -> Self {
+    self.paper = Some(heights);
+    self
+  }
+
+  /// Seed for the grain noise used when there's no paper
+  pub fn seed(mut self, seed: u32) -> Self {
+    self.noise = self.noise.set_seed(seed);
+    self
+  }
+
+  pub fn bristles(&self) -> &[Bristle] {
+    &self.bristles
+  }
+
+  /// Dips the brush again, filling every bristle back up
+  pub fn reload(&mut self) {
+    for bristle in self.bristles.iter_mut() {
+      bristle.paint = bristle.capacity;
+    }
+  }
+
+  /// Average paint left in the bristles, in [0, 1]
+  pub fn load(&self) -> f32 {
+    self
+      .bristles
+      .iter()
+      .map(|b| b.paint / b.capacity)
+      .sum::<f32>()
+      / self.bristles.len() as f32
+  }
+
+  /// Drags the brush along `path` (in window coordinates), painting into `raster`.
+  /// `pressure` is called with t in [0, 1] along the path and should return [0, 1].
+  /// Paint isn't refilled between strokes, call reload() for that.
+  pub fn stroke(
+    &mut self,
+    raster: &mut Raster,
+    win: &Rect,
+    path: &Line2,
+    pressure: impl Fn(f32) -> f32,
+  ) {
+    if path.len() < 2 {
+      return;
+    }
+    let scale = raster.width as f32 / win.w();
+    let n = path.len();
+    for bristle in self.bristles.iter_mut() {
+      bristle.tip = None;
+    }
+    let mut direction = vec2(1.0, 0.0);
+    for k in 0..n {
+      let point = path[k];
+      if k + 1 < n && path[k].distance(path[k + 1]) > 0.0 {
+        direction = (path[k + 1] - path[k]).normalize();
+      }
+      let speed = if k > 0 {
+        path[k - 1].distance(point) * scale
+      } else {
+        0.0
+      };
+      let pressure = pressure(k as f32 / (n - 1) as f32).max(0.0).min(1.0);
+      let normal = vec2(-direction.y, direction.x);
+      // pressing harder flattens the brush, so it gets wider
+      let spread = self.width / 2.0 * (0.4 + 0.6 * pressure);
+      for index in 0..self.bristles.len() {
+        let mut bristle = self.bristles[index].clone();
+        let target = point + normal * bristle.offset * spread;
+        let tip = match bristle.tip {
+          Some(tip) => tip + (target - tip) * bristle.stiffness,
+          None => target,
+        };
+        if let Some(previous) = bristle.tip {
+          self.drag(raster, win, &mut bristle, (previous, tip), pressure, speed);
+        }
+        bristle.tip = Some(tip);
+        self.bristles[index] = bristle;
+      }
+    }
+    for bristle in self.bristles.iter_mut() {
+      bristle.tip = None;
+    }
+  }
+
+  // dabs paint along one bristle's movement
+  fn drag(
+    &self,
+    raster: &mut Raster,
+    win: &Rect,
+    bristle: &mut Bristle,
+    (from, to): (Point2, Point2),
+    pressure: f32,
+    speed: f32,
+  ) {
+    let a = raster.window_to_pixel(from, win);
+    let b = raster.window_to_pixel(to, win);
+    let scale = raster.width as f32 / win.w();
+    let radius = (self.width * scale / self.bristles.len() as f32 * (1.0 + pressure)).max(0.7);
+    let distance = a.distance(b);
+    let steps = (distance / (radius * 0.5)).ceil().max(1.0) as usize;
+    let [r, g, bl] = bristle.color;
+    for s in 0..steps {
+      let load = bristle.paint / bristle.capacity;
+      if load <= 0.0 {
+        return;
+      }
+      // paint sticks above this height: wet, heavy, slow brushes reach lower
+      let threshold = 1.0 - load * pressure / (1.0 + speed * self.speed_sensitivity);
+      let center = a + (b - a) * (s as f32 / steps as f32);
+      let (min_i, max_i) = ((center.x - radius).floor(), (center.x + radius).ceil());
+      let (min_j, max_j) = ((center.y - radius).floor(), (center.y + radius).ceil());
+      for j in (min_j.max(0.0) as usize)..(max_j.max(0.0) as usize).min(raster.height) {
+        for i in (min_i.max(0.0) as usize)..(max_i.max(0.0) as usize).min(raster.width) {
+          let d = center.distance(pt2(i as f32 + 0.5, j as f32 + 0.5));
+          if d > radius {
+            continue;
+          }
+          let height = 1.0 - self.grain * (1.0 - self.height(raster, i, j));
+          let sticks = ((height - threshold) / 0.15 + 0.5).max(0.0).min(1.0);
+          let alpha = self.flow * (1.0 - d / radius) * sticks * (0.3 + 0.7 * load);
+          if alpha > 0.0 {
+            raster.blend(i, j, [r, g, bl, alpha]);
+          }
+        }
+      }
+      bristle.paint =
+        (bristle.paint - self.depletion * distance / steps as f32 * pressure).max(0.0);
+    }
+  }
+
+  // paper height under a raster pixel
+  fn height(&self, raster: &Raster, i: usize, j: usize) -> f32 {
+    match &self.paper {
+      Some(paper) => paper.at(
+        (i * paper.width / raster.width).min(paper.width - 1),
+        (j * paper.height / raster.height).min(paper.height - 1),
+      ),
+      None => {
+        let n = self
+          .noise
+          .get([i as f64 * self.noise_scale, j as f64 * self.noise_scale]);
+        (n as f32 * 0.5 + 0.5).max(0.0).min(1.0)
+      }
+    }
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every bristle and the whole paper
+impl fmt::Debug for BristleBrush {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    f.debug_struct("BristleBrush")
+      .field("width", &self.width)
+      .field("n_bristles", &self.bristles.len())
+      .field("depletion", &self.depletion)
+      .field("flow", &self.flow)
+      .field("grain", &self.grain)
+      .field("speed_sensitivity", &self.speed_sensitivity)
+      .field("paper", &self.paper)
+      .finish()
+  }
+}
@@ -1,5 +1,8 @@
 use nannou::prelude::*;
 
+use super::args;
+use super::rng::random_range;
+
 // this is such a nice place to get simple color palettes
 // https://observablehq.com/@makio135/give-me-colors
 
@@ -189,14 +192,15 @@ pub const PALETTES: [(&str, Palette5); 44] = [
 pub fn get_palette(name: &str) -> Palette5 {
   match name {
     name if name == "random" => {
-      let palette = select_random(
-        &PALETTES
-          .to_vec()
-          .iter()
-          .map(|(_title, palette)| *palette)
-          .collect(),
-      );
+      let (title, palette) = select_random(&PALETTES.to_vec());
       println!("palette: {}", palette.to_vec().join(", "));
+      // so the capture records the palette that was actually used, not "random"
+      if args::resolved()
+        .get("palette")
+        .map_or(false, |p| p == "random")
+      {
+        args::record("palette", title);
+      }
       palette
     }
     _ => *PALETTES
@@ -4,11 +4,12 @@ extern crate delaunator;
 use nannou::noise::{Billow, Fbm, MultiFractal, NoiseFn, RidgedMulti};
 use nannou::prelude::*;
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
 use self::delaunator::{triangulate, Point};
 
-use super::PointCloud;
+use super::rng::random_range;
+use super::{Line2, PointCloud};
 
 pub type Deque2 = VecDeque<Point2>;
 
@@ -210,6 +211,125 @@ pub fn connect_contour_segments(mut segments: Vec<Deque2>) -> Vec<Deque2> {
   contour_lines
 }
 
+/// Triangulates a regular grid of values, e.g. a simulation field, without the Delauney step.
+/// Each grid square is split into two triangles.
+/// `value(i, j)` is the "elevation" at each grid point and `position(i, j)` is where it sits on the canvas.
+pub fn grid_triangles(
+  width: usize,
+  height: usize,
+  value: impl Fn(usize, usize) -> f32,
+  position: impl Fn(usize, usize) -> Point2,
+) -> Vec<Triangle3D> {
+  let vertex = |i: usize, j: usize| {
+    let p = position(i, j);
+    pt3(p.x, p.y, value(i, j))
+  };
+  let mut triangles = Vec::with_capacity(width.saturating_sub(1) * height.saturating_sub(1) * 2);
+  for j in 0..height.saturating_sub(1) {
+    for i in 0..width.saturating_sub(1) {
+      let (a, b, c, d) = (
+        vertex(i, j),
+        vertex(i + 1, j),
+        vertex(i + 1, j + 1),
+        vertex(i, j + 1),
+      );
+      triangles.push(Triangle3D {
+        vertices: vec![a, b, c],
+      });
+      triangles.push(Triangle3D {
+        vertices: vec![a, c, d],
+      });
+    }
+  }
+  triangles
+}
+
+/// Does the same job as `connect_contour_segments`, but looks up matching endpoints in a hash
+/// instead of searching every segment, so it stays fast with hundreds of thousands of segments
+/// (e.g. a finely triangulated grid).
+/// Closed contours come back closed, i.e. the last point is the same as the first.
+pub fn stitch_contour_segments(segments: Vec<Deque2>) -> Vec<Deque2> {
+  // matching endpoints come from the same triangle edge, so they're only off by float error
+  let tolerance = 1e-3;
+  let key = |p: &Point2| {
+    (
+      (p.x / tolerance).floor() as i64,
+      (p.y / tolerance).floor() as i64,
+    )
+  };
+  // contours that pass exactly through a grid point leave zero-length segments behind,
+  // which would stop the lines from joining up
+  let segments: Vec<Deque2> = segments
+    .into_iter()
+    .filter(|segment| segment[0].distance(segment[1]) > tolerance)
+    .collect();
+  // bucket -> (segment index, which end)
+  let mut endpoints: HashMap<(i64, i64), Vec<(usize, usize)>> = HashMap::new();
+  for (index, segment) in segments.iter().enumerate() {
+    for end in 0..2 {
+      endpoints
+        .entry(key(&segment[end]))
+        .or_insert_with(Vec::new)
+        .push((index, end));
+    }
+  }
+  let mut used = vec![false; segments.len()];
+
+  // an unused segment with an endpoint at `point`, and the point at its other end
+  let find = |point: &Point2, used: &Vec<bool>| -> Option<(usize, Point2)> {
+    let (x, y) = key(point);
+    for dx in -1..=1 {
+      for dy in -1..=1 {
+        if let Some(candidates) = endpoints.get(&(x + dx, y + dy)) {
+          for (index, end) in candidates {
+            if !used[*index] && segments[*index][*end].distance(*point) < tolerance {
+              return Some((*index, segments[*index][1 - *end]));
+            }
+          }
+        }
+      }
+    }
+    None
+  };
+
+  let mut lines = vec![];
+  for start in 0..segments.len() {
+    if used[start] {
+      continue;
+    }
+    used[start] = true;
+    let mut line: Deque2 = segments[start].clone();
+    while let Some((index, next)) = find(line.back().unwrap(), &used) {
+      used[index] = true;
+      line.push_back(next);
+    }
+    while let Some((index, previous)) = find(line.front().unwrap(), &used) {
+      used[index] = true;
+      line.push_front(previous);
+    }
+    lines.push(line);
+  }
+  lines
+}
+
+/// Isolines of a grid of values at `threshold`, stitched into continuous lines.
+/// `value(i, j)` and `position(i, j)` work the same as in `grid_triangles`.
+/// The lines are interpolated between grid points, so they're smooth and can be drawn
+/// at any size regardless of the resolution of the grid.
+pub fn field_contours(
+  width: usize,
+  height: usize,
+  threshold: f32,
+  value: impl Fn(usize, usize) -> f32,
+  position: impl Fn(usize, usize) -> Point2,
+) -> Vec<Line2> {
+  let triangles = grid_triangles(width, height, value, position);
+  stitch_contour_segments(calc_contour(threshold, &triangles))
+    .into_iter()
+    .map(|line| line.into_iter().collect())
+    .collect()
+}
+
 #[derive(Debug)]
 pub struct MultiFractalOptions {
   // influences "frequency" of noise "waves"
@@ -0,0 +1,143 @@
+// A grayscale "density map" built from an input image (or any function).
+// Density is darkness, so black pixels are 1.0 and white pixels are 0.0,
+// which is usually what you want when deciding where to put ink.
+extern crate image;
+
+use nannou::prelude::*;
+
+use self::image::imageops::FilterType;
+use self::image::GrayImage;
+
+use super::rng::{random_f32, random_range};
+
+#[derive(Clone)]
+pub struct DensityMap {
+  pub width: usize,
+  pub height: usize,
+  // row-major, with j = 0 at the *top* of the image
+  values: Vec<f32>,
+}
+
+impl DensityMap {
+  /// Loads an image from disk and scales it so the longest side is at most `max_size` pixels.
+  /// Large photos are downsampled because most consumers iterate every pixel.
+  pub fn open(path: &str, max_size: u32) -> Self {
+    let img = image::open(path)
+      .unwrap_or_else(|err| panic!("failed to open image {}: {}", path, err))
+      .to_luma8();
+    let (w, h) = img.dimensions();
+    let scale = (max_size as f32 / w.max(h) as f32).min(1.0);
+    let img = if scale < 1.0 {
+      image::imageops::resize(
+        &img,
+        (w as f32 * scale).round() as u32,
+        (h as f32 * scale).round() as u32,
+        FilterType::Triangle,
+      )
+    } else {
+      img
+    };
+    Self::from_luma(&img)
+  }
+
+  pub fn from_luma(img: &GrayImage) -> Self {
+    let (w, h) = img.dimensions();
+    Self::from_fn(w as usize, h as usize, |i, j| {
+      1.0 - img.get_pixel(i as u32, j as u32)[0] as f32 / 255.0
+    })
+  }
+
+  /// Builds a density map from a function of (i, j) pixel coordinates.
+  /// The function should return values in [0, 1].
+  pub fn from_fn(width: usize, height: usize, f: impl Fn(usize, usize) -> f32) -> Self {
+    let mut values = Vec::with_capacity(width * height);
+    for j in 0..height {
+      for i in 0..width {
+        values.push(f(i, j));
+      }
+    }
+    DensityMap {
+      width,
+      height,
+      values,
+    }
+  }
+
+  /// Raise every value to `gamma`. Values > 1.0 push mid-tones towards white,
+  /// values < 1.0 push them towards black.
+  pub fn gamma(mut self, gamma: f32) -> Self {
+    for v in self.values.iter_mut() {
+      *v = v.powf(gamma);
+    }
+    self
+  }
+
+  pub fn invert(mut self) -> Self {
+    for v in self.values.iter_mut() {
+      *v = 1.0 - *v;
+    }
+    self
+  }
+
+  pub fn at(&self, i: usize, j: usize) -> f32 {
+    self.values[j * self.width + i]
+  }
+
+  /// Maps a point in pixel space to the window.
+  /// The image is stretched to fill `win`, so pick a window with the same aspect ratio.
+  pub fn pixel_to_window(&self, point: Point2, win: &Rect) -> Point2 {
+    pt2(
+      map_range(point.x, 0.0, self.width as f32, win.left(), win.right()),
+      map_range(point.y, 0.0, self.height as f32, win.top(), win.bottom()),
+    )
+  }
+
+  /// Maps a point in the window to pixel space
+  pub fn window_to_pixel(&self, point: Point2, win: &Rect) -> Point2 {
+    pt2(
+      map_range(point.x, win.left(), win.right(), 0.0, self.width as f32),
+      map_range(point.y, win.top(), win.bottom(), 0.0, self.height as f32),
+    )
+  }
+
+  /// The density underneath a point in the window.
+  /// Points outside the image have zero density.
+  pub fn sample(&self, point: Point2, win: &Rect) -> f32 {
+    let p = self.window_to_pixel(point, win);
+    if p.x < 0.0 || p.y < 0.0 || p.x >= self.width as f32 || p.y >= self.height as f32 {
+      return 0.0;
+    }
+    self.at(p.x as usize, p.y as usize)
+  }
+
+  /// Rejection sampling: returns `n` points in pixel space,
+  /// distributed with probability proportional to the density.
+  pub fn sample_points(&self, n: usize) -> Vec<Point2> {
+    let mut points = Vec::with_capacity(n);
+    // relative to the densest pixel, so a faint image doesn't take forever to fill.
+    // An all-white image would loop forever
+    let max = self.values.iter().cloned().fold(0.0, f32::max);
+    if max <= 0.0 {
+      return points;
+    }
+    while points.len() < n {
+      let x = random_range(0.0, self.width as f32);
+      let y = random_range(0.0, self.height as f32);
+      if random_f32() * max < self.at(x as usize, y as usize) {
+        points.push(pt2(x, y));
+      }
+    }
+    points
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every pixel
+impl std::fmt::Debug for DensityMap {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("DensityMap")
+      .field("width", &self.width)
+      .field("height", &self.height)
+      .finish()
+  }
+}
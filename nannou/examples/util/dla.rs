@@ -0,0 +1,361 @@
+// Diffusion-limited aggregation
+// https://en.wikipedia.org/wiki/Diffusion-limited_aggregation
+//
+// This replaces the recursive Tree + "check_tree_collision" approach from diffusion_1..4.
+// The cluster is a flat Vec of nodes where each node knows the index of its parent,
+// and collisions are checked with a SpatialHash, so adding a particle costs the same
+// whether the cluster has 10 nodes or 100,000.
+//
+// Algorithm in a nutshell:
+// 1. spawn a walker on a circle just outside the cluster
+// 2. let it wander randomly (plus any drift) until it comes within `particle_size` of a node
+// 3. it sticks with probability `sticking`, as long as the growth constraints allow it.
+//    Otherwise it keeps wandering
+// 4. the spawn circle grows with the cluster
+use nannou::prelude::*;
+
+use super::rng::{random_f32, random_range};
+use super::spatial::SpatialHash;
+use super::Line2;
+
+#[derive(Debug, Copy, Clone)]
+pub struct Node {
+  pub position: Point2,
+  // None for the seeds
+  pub parent: Option<usize>,
+  // number of steps from the seed this node grew from
+  pub depth: usize,
+  // number of nodes in the cluster when this node stuck, so older nodes have smaller ages
+  pub age: usize,
+}
+
+pub struct Dla {
+  nodes: Vec<Node>,
+  hash: SpatialHash,
+  center: Point2,
+  // distance between connected nodes, and how far a walker moves per step
+  particle_size: f32,
+  // probability a walker sticks when it touches the cluster
+  sticking: f32,
+  // walkers spawn this far outside the furthest node
+  spawn_padding: f32,
+  // walkers that wander further than spawn radius * kill_factor are respawned
+  kill_factor: f32,
+  // a walker that hasn't stuck after this many steps is respawned
+  max_steps: usize,
+  // maximum angle between a node's branch and its parent's branch, in radians
+  max_angle: f32,
+  max_children: usize,
+  children: Vec<usize>,
+  // furthest distance of any node from the center
+  radius: f32,
+  // extra movement added to every step, e.g. wind or gravity
+  drift: Option<Box<dyn Fn(Point2) -> Vector2>>,
+}
+
+impl Dla {
+  pub fn new(particle_size: f32) -> Self {
+    Dla {
+      nodes: vec![],
+      hash: SpatialHash::new(particle_size),
+      center: pt2(0.0, 0.0),
+      particle_size,
+      sticking: 1.0,
+      spawn_padding: particle_size * 5.0,
+      kill_factor: 2.0,
+      max_steps: 10000,
+      max_angle: PI,
+      max_children: usize::max_value(),
+      children: vec![],
+      radius: 0.0,
+      drift: None,
+    }
+  }
+
+  /// Adds a seed (a node with no parent) that the cluster can grow from.
+  /// The spawn circle is centered on the average of the seeds.
+  pub fn seed(mut self, point: Point2) -> Self {
+    self.add_node(point, None);
+    let seeds: Vec<Point2> = self
+      .nodes
+      .iter()
+      .filter(|node| node.parent.is_none())
+      .map(|node| node.position)
+      .collect();
+    let n = seeds.len() as f32;
+    self.center = seeds.iter().fold(pt2(0.0, 0.0), |sum, p| sum + *p / n);
+    self.radius = self
+      .nodes
+      .iter()
+      .map(|n| n.position.distance(self.center))
+      .fold(0.0, f32::max);
+    self
+  }
+
+  pub fn sticking(mut self, probability: f32) -> Self {
+    self.sticking = probability.max(0.001).min(1.0);
+    self
+  }
+
+  pub fn spawn_padding(mut self, padding: f32) -> Self {
+    self.spawn_padding = padding;
+    self
+  }
+
+  pub fn kill_factor(mut self, kill_factor: f32) -> Self {
+    self.kill_factor = kill_factor.max(1.1);
+    self
+  }
+
+  pub fn max_steps(mut self, max_steps: usize) -> Self {
+    self.max_steps = max_steps;
+    self
+  }
+
+  // Line-of-growth constraints.
+  // A small max_angle makes long straight-ish branches, max_children limits how bushy it gets
+  pub fn max_angle(mut self, radians: f32) -> Self {
+    self.max_angle = radians;
+    self
+  }
+
+  pub fn max_children(mut self, max_children: usize) -> Self {
+    self.max_children = max_children;
+    self
+  }
+
+  /// A field that pushes walkers around. It's called with the walker position,
+  /// and the result is added to every step (scaled by the step length)
+  pub fn drift(mut self, drift: impl Fn(Point2) -> Vector2 + 'static) -> Self {
+    self.drift = Some(Box::new(drift));
+    self
+  }
+
+  /// Constant drift in one direction
+  pub fn bias(self, x: f32, y: f32) -> Self {
+    self.drift(move |_| vec2(x, y))
+  }
+
+  pub fn nodes(&self) -> &[Node] {
+    &self.nodes
+  }
+
+  pub fn len(&self) -> usize {
+    self.nodes.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.nodes.is_empty()
+  }
+
+  pub fn max_depth(&self) -> usize {
+    self.nodes.iter().map(|n| n.depth).max().unwrap_or(0)
+  }
+
+  /// Adds `n` particles to the cluster
+  pub fn grow(&mut self, n: usize) {
+    for i in 0..n {
+      if i % 1000 == 0 && i > 0 {
+        println!("{} of {} particles ...", i, n);
+      }
+      self.add_particle();
+    }
+  }
+
+  /// Releases walkers until one sticks, and returns the index of the new node.
+  /// Returns None if the cluster has no seeds, or if nothing stuck after a lot of walkers
+  /// (e.g. the constraints are too strict to allow any more growth).
+  pub fn add_particle(&mut self) -> Option<usize> {
+    if self.nodes.is_empty() {
+      return None;
+    }
+    for _walker in 0..1000 {
+      if let Some(index) = self.walk() {
+        return Some(index);
+      }
+    }
+    None
+  }
+
+  // a single walker, from spawn until it sticks or gives up
+  fn walk(&mut self) -> Option<usize> {
+    let spawn_radius = self.radius + self.spawn_padding;
+    let angle = random_range(0.0, 2.0 * PI);
+    let mut position = self.center + vec2(angle.cos(), angle.sin()) * spawn_radius;
+
+    for _step in 0..self.max_steps {
+      let distance = position.distance(self.center);
+      if distance > spawn_radius * self.kill_factor {
+        return None;
+      }
+      // far away from the cluster the walker can take big steps without jumping over anything
+      let step = (distance - self.radius - self.particle_size * 2.0).max(self.particle_size);
+      let angle = random_range(0.0, 2.0 * PI);
+      position += vec2(angle.cos(), angle.sin()) * step;
+      if let Some(drift) = &self.drift {
+        position += drift(position) * step;
+      }
+
+      if let Some(parent) = self.touching(position) {
+        if random_f32() < self.sticking {
+          if let Some(point) = self.attachment(parent, position) {
+            return Some(self.add_node(point, Some(parent)));
+          }
+        }
+      }
+    }
+    None
+  }
+
+  // closest node within reach of the walker
+  fn touching(&self, position: Point2) -> Option<usize> {
+    self
+      .hash
+      .within(position, self.particle_size)
+      .into_iter()
+      .min_by(|a, b| {
+        let da = self.nodes[*a].position.distance(position);
+        let db = self.nodes[*b].position.distance(position);
+        da.partial_cmp(&db).unwrap()
+      })
+  }
+
+  // where the new node would go, or None if the growth constraints rule it out
+  fn attachment(&self, parent: usize, position: Point2) -> Option<Point2> {
+    if self.children[parent] >= self.max_children {
+      return None;
+    }
+    let node = self.nodes[parent];
+    let direction = position - node.position;
+    let length = direction.magnitude();
+    if length < std::f32::EPSILON {
+      return None;
+    }
+    // snap the new node to exactly `particle_size` from its parent so branches are evenly spaced
+    let point = node.position + direction / length * self.particle_size;
+    if let Some(grandparent) = node.parent {
+      let incoming = node.position - self.nodes[grandparent].position;
+      let angle = (direction.y.atan2(direction.x) - incoming.y.atan2(incoming.x)).abs();
+      // wrap to [0, PI]
+      let angle = if angle > PI { 2.0 * PI - angle } else { angle };
+      if angle > self.max_angle {
+        return None;
+      }
+    }
+    // don't let it squeeze in on top of some other node
+    let crowded = self
+      .hash
+      .within(point, self.particle_size * 0.99)
+      .into_iter()
+      .any(|i| i != parent);
+    if crowded {
+      return None;
+    }
+    Some(point)
+  }
+
+  fn add_node(&mut self, position: Point2, parent: Option<usize>) -> usize {
+    let index = self.nodes.len();
+    let depth = parent.map_or(0, |p| self.nodes[p].depth + 1);
+    self.nodes.push(Node {
+      position,
+      parent,
+      depth,
+      age: index,
+    });
+    self.children.push(0);
+    if let Some(p) = parent {
+      self.children[p] += 1;
+    }
+    self.hash.insert(index, position);
+    self.radius = self.radius.max(position.distance(self.center));
+    index
+  }
+
+  /// How many nodes hang off each node (including itself), indexed like `nodes()`.
+  /// Big near the seeds and 1 at the tips, which makes a good stroke weight for tapering.
+  pub fn descendants(&self) -> Vec<usize> {
+    tree_descendants(&self.nodes)
+  }
+
+  /// The cluster as branches of node indices, each running from a tip towards its seed.
+  /// See `tree_branches`
+  pub fn branches(&self) -> Vec<Vec<usize>> {
+    tree_branches(&self.nodes)
+  }
+
+  /// `branches` as polylines
+  pub fn paths(&self) -> Vec<Line2> {
+    self
+      .branches()
+      .iter()
+      .map(|branch| branch.iter().map(|i| self.nodes[*i].position).collect())
+      .collect()
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every node
+impl std::fmt::Debug for Dla {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Dla")
+      .field("n_nodes", &self.nodes.len())
+      .field("center", &self.center)
+      .field("radius", &self.radius)
+      .field("particle_size", &self.particle_size)
+      .field("sticking", &self.sticking)
+      .field("max_angle", &self.max_angle)
+      .field("max_children", &self.max_children)
+      .field("drift", &self.drift.is_some())
+      .finish()
+  }
+}
+
+/// Descendant counts (including the node itself) for any tree stored as a flat list of nodes,
+/// as long as every parent comes before its children.
+pub fn tree_descendants(nodes: &[Node]) -> Vec<usize> {
+  let mut counts = vec![1; nodes.len()];
+  // children are always added after their parents, so one backwards pass is enough
+  for index in (0..nodes.len()).rev() {
+    if let Some(parent) = nodes[index].parent {
+      counts[parent] += counts[index];
+    }
+  }
+  counts
+}
+
+/// Splits a tree into branches of node indices, each running from a tip towards its root.
+/// Every edge appears in exactly one branch: a branch stops at the first node that an earlier
+/// (deeper) branch already passed through, and includes that node so the lines connect.
+/// Linear time apart from sorting the tips.
+pub fn tree_branches(nodes: &[Node]) -> Vec<Vec<usize>> {
+  let n = nodes.len();
+  let mut is_tip = vec![true; n];
+  for node in nodes {
+    if let Some(parent) = node.parent {
+      is_tip[parent] = false;
+    }
+  }
+  let mut tips: Vec<usize> = (0..n).filter(|i| is_tip[*i]).collect();
+  // deepest first, so the longest branches come out as single lines
+  tips.sort_by(|a, b| nodes[*b].depth.cmp(&nodes[*a].depth));
+  let mut visited = vec![false; n];
+  let mut branches = vec![];
+  for tip in tips {
+    let mut branch = vec![tip];
+    visited[tip] = true;
+    let mut current = tip;
+    while let Some(parent) = nodes[current].parent {
+      branch.push(parent);
+      if visited[parent] {
+        break;
+      }
+      visited[parent] = true;
+      current = parent;
+    }
+    if branch.len() > 1 {
+      branches.push(branch);
+    }
+  }
+  branches
+}
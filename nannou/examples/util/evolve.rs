@@ -0,0 +1,436 @@
+// Genetic algorithms, pulled out of noc_genetic3/4/5 so every evolutionary sketch doesn't need its own
+// DNA, Population, mating pool, crossover and mutate.
+// https://natureofcode.com/book/chapter-9-the-evolution-of-code/
+//
+// A sketch implements Genome for whatever it wants to evolve (usually just a Vec of genes),
+// then hands a Population a fitness function and picks the selection/crossover/mutation strategies.
+//
+//   let mut population = Population::from_fn(200, |_| MyGenome::random())
+//     .fitness(|genome| score(genome))
+//     .selection(Selection::Tournament(3))
+//     .crossover(Crossover::Uniform)
+//     .mutation(Mutation::Creep(0.1), 0.02)
+//     .elitism(2);
+//   population.evolve(100);
+//   let best = population.best();
+//
+// Fitness is "higher is better". It's cached on each individual so it's only ever computed once per genome,
+// and it doesn't need a fitness function at all: `set_fitness` lets a person do the judging.
+
+use super::rng::{random_f32, random_range};
+
+/// A single gene. The defaults work for anything that can only be copied around (chars, enums, etc.),
+/// numbers override them so blend crossover and creep mutation can make in-between values.
+pub trait Gene: Clone {
+  /// A mix of self and other, where t = 0 is self and t = 1 is other
+  fn blend(&self, other: &Self, t: f32) -> Self {
+    if t < 0.5 {
+      self.clone()
+    } else {
+      other.clone()
+    }
+  }
+
+  /// A value close to this one. `amount` is the largest change.
+  fn perturb(&self, _amount: f32) -> Self {
+    self.clone()
+  }
+}
+
+impl Gene for f32 {
+  fn blend(&self, other: &Self, t: f32) -> Self {
+    self + (other - self) * t
+  }
+
+  fn perturb(&self, amount: f32) -> Self {
+    self + random_range(-amount, amount)
+  }
+}
+
+impl Gene for usize {
+  fn blend(&self, other: &Self, t: f32) -> Self {
+    (*self as f32 + (*other as f32 - *self as f32) * t)
+      .round()
+      .max(0.0) as usize
+  }
+}
+
+impl Gene for bool {}
+impl Gene for char {}
+
+pub trait Genome: Clone {
+  type Gene: Gene;
+
+  fn genes(&self) -> &[Self::Gene];
+
+  fn genes_mut(&mut self) -> &mut [Self::Gene];
+
+  /// A brand new random value for the gene at `index`, used by `Mutation::Reset`.
+  /// Genes can have different ranges depending on where they are, that's why it gets the index.
+  fn random_gene(&self, index: usize) -> Self::Gene;
+
+  /// Called on every child after crossover and mutation,
+  /// e.g. to clamp genes back into range after a creep mutation
+  fn repair(&mut self) {}
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Selection {
+  /// chance of being picked is proportional to fitness (the Nature of Code "mating pool")
+  Roulette,
+  /// pick the best out of `n` random individuals. Bigger n = more pressure to be fit
+  Tournament(usize),
+  /// chance of being picked is proportional to position in the sorted population,
+  /// so one really fit individual can't take over everything
+  Rank,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Crossover {
+  /// genes before a random point come from one parent, the rest from the other
+  SinglePoint,
+  /// every gene comes from a random parent (the "coin flip" from Nature of Code exercise 9.5)
+  Uniform,
+  /// every gene is a random mix of both parents, allowed to go `alpha` past either one (BLX-alpha).
+  /// Only makes in-between values for genes that implement `Gene::blend`
+  Blend(f32),
+  /// child is a copy of the first parent
+  None,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Mutation {
+  /// replace the gene with `Genome::random_gene`
+  Reset,
+  /// nudge the gene by up to this much with `Gene::perturb`
+  Creep(f32),
+  /// swap the gene with another random gene, which keeps permutations valid (e.g. for tours)
+  Swap,
+}
+
+#[derive(Debug, Clone)]
+pub struct Individual<G: Genome> {
+  pub genome: G,
+  // None until it's been evaluated
+  pub fitness: Option<f32>,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct GenerationStats {
+  pub generation: usize,
+  pub best: f32,
+  pub worst: f32,
+  pub mean: f32,
+  pub std_dev: f32,
+}
+
+pub struct Population<G: Genome> {
+  individuals: Vec<Individual<G>>,
+  fitness: Option<Box<dyn Fn(&G) -> f32>>,
+  selection: Selection,
+  crossover: Crossover,
+  // chance a child is made with crossover instead of just copying a parent
+  crossover_rate: f32,
+  mutation: Mutation,
+  // chance of mutating each gene
+  mutation_rate: f32,
+  // how many of the best individuals are copied unchanged to the next generation
+  elitism: usize,
+  generation: usize,
+  history: Vec<GenerationStats>,
+}
+
+impl<G: Genome> Population<G> {
+  pub fn new(genomes: Vec<G>) -> Self {
+    Population {
+      individuals: genomes
+        .into_iter()
+        .map(|genome| Individual {
+          genome,
+          fitness: None,
+        })
+        .collect(),
+      fitness: None,
+      selection: Selection::Tournament(3),
+      crossover: Crossover::Uniform,
+      crossover_rate: 1.0,
+      mutation: Mutation::Reset,
+      mutation_rate: 0.01,
+      elitism: 0,
+      generation: 0,
+      history: vec![],
+    }
+  }
+
+  pub fn from_fn(size: usize, f: impl FnMut(usize) -> G) -> Self {
+    Self::new((0..size).map(f).collect())
+  }
+
+  pub fn fitness(mut self, fitness: impl Fn(&G) -> f32 + 'static) -> Self {
+    self.fitness = Some(Box::new(fitness));
+    self
+  }
+
+  pub fn selection(mut self, selection: Selection) -> Self {
+    self.selection = selection;
+    self
+  }
+
+  pub fn crossover(mut self, crossover: Crossover) -> Self {
+    self.crossover = crossover;
+    self
+  }
+
+  pub fn crossover_rate(mut self, rate: f32) -> Self {
+    self.crossover_rate = rate;
+    self
+  }
+
+  pub fn mutation(mut self, mutation: Mutation, rate: f32) -> Self {
+    self.mutation = mutation;
+    self.mutation_rate = rate;
+    self
+  }
+
+  pub fn elitism(mut self, elitism: usize) -> Self {
+    self.elitism = elitism;
+    self
+  }
+
+  pub fn individuals(&self) -> &[Individual<G>] {
+    &self.individuals
+  }
+
+  pub fn len(&self) -> usize {
+    self.individuals.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.individuals.is_empty()
+  }
+
+  pub fn generation(&self) -> usize {
+    self.generation
+  }
+
+  /// Stats for every generation so far, oldest first
+  pub fn history(&self) -> &[GenerationStats] {
+    &self.history
+  }
+
+  /// Overrides the fitness of one individual, e.g. when a person is picking favorites
+  pub fn set_fitness(&mut self, index: usize, fitness: f32) {
+    self.individuals[index].fitness = Some(fitness);
+  }
+
+  /// Replaces one individual, which will be evaluated again when needed
+  pub fn set_genome(&mut self, index: usize, genome: G) {
+    self.individuals[index] = Individual {
+      genome,
+      fitness: None,
+    };
+  }
+
+  /// Computes fitness for every individual that doesn't have one yet.
+  /// Without a fitness function, anything that wasn't given a fitness with `set_fitness` gets 0.
+  pub fn evaluate(&mut self) {
+    for individual in self.individuals.iter_mut() {
+      if individual.fitness.is_none() {
+        individual.fitness = Some(match &self.fitness {
+          Some(fitness) => fitness(&individual.genome),
+          None => 0.0,
+        });
+      }
+    }
+  }
+
+  /// The fittest individual. Evaluates the population first if needed.
+  pub fn best(&mut self) -> &Individual<G> {
+    self.evaluate();
+    self
+      .individuals
+      .iter()
+      .max_by(|a, b| a.fitness.partial_cmp(&b.fitness).unwrap())
+      .expect("population is empty")
+  }
+
+  /// Stats for the current population. Evaluates the population first if needed.
+  pub fn stats(&mut self) -> GenerationStats {
+    self.evaluate();
+    let values: Vec<f32> = self
+      .individuals
+      .iter()
+      .map(|individual| individual.fitness.unwrap())
+      .collect();
+    let n = values.len().max(1) as f32;
+    let mean = values.iter().sum::<f32>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / n;
+    GenerationStats {
+      generation: self.generation,
+      best: values.iter().cloned().fold(std::f32::MIN, f32::max),
+      worst: values.iter().cloned().fold(std::f32::MAX, f32::min),
+      mean,
+      std_dev: variance.sqrt(),
+    }
+  }
+
+  pub fn evolve(&mut self, generations: usize) {
+    for _ in 0..generations {
+      self.next_generation();
+    }
+  }
+
+  /// Replaces the population with the children of the current one
+  pub fn next_generation(&mut self) {
+    if self.individuals.is_empty() {
+      return;
+    }
+    let stats = self.stats();
+    self.history.push(stats);
+
+    let mut sorted = self.individuals.clone();
+    sorted.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
+    // elites keep their cached fitness
+    let mut next: Vec<Individual<G>> = sorted
+      .iter()
+      .take(self.elitism.min(sorted.len()))
+      .cloned()
+      .collect();
+
+    let weights = self.selection_weights(&sorted);
+    while next.len() < self.individuals.len() {
+      let a = &sorted[self.select(&sorted, &weights)].genome;
+      let b = &sorted[self.select(&sorted, &weights)].genome;
+      let mut child = if random_f32() < self.crossover_rate {
+        crossover(a, b, self.crossover)
+      } else {
+        a.clone()
+      };
+      mutate(&mut child, self.mutation, self.mutation_rate);
+      child.repair();
+      next.push(Individual {
+        genome: child,
+        fitness: None,
+      });
+    }
+
+    self.individuals = next;
+    self.generation += 1;
+  }
+
+  // cumulative weights for roulette and rank selection, `sorted` is best first
+  fn selection_weights(&self, sorted: &[Individual<G>]) -> Vec<f32> {
+    let n = sorted.len();
+    let weights: Vec<f32> = match self.selection {
+      Selection::Roulette => {
+        // shift so the worst has a tiny chance instead of a negative one
+        let worst = sorted[n - 1].fitness.unwrap();
+        let offset = if worst < 0.0 { -worst } else { 0.0 };
+        sorted
+          .iter()
+          .map(|i| i.fitness.unwrap() + offset + std::f32::EPSILON)
+          .collect()
+      }
+      Selection::Rank => (0..n).map(|rank| (n - rank) as f32).collect(),
+      Selection::Tournament(_) => return vec![],
+    };
+    weights
+      .iter()
+      .scan(0.0, |sum, w| {
+        *sum += w;
+        Some(*sum)
+      })
+      .collect()
+  }
+
+  // index of a parent in `sorted`
+  fn select(&self, sorted: &[Individual<G>], cumulative: &[f32]) -> usize {
+    match self.selection {
+      Selection::Tournament(size) => {
+        // sorted is best first, so the smallest index wins
+        (0..size.max(1))
+          .map(|_| random_range(0, sorted.len()))
+          .min()
+          .unwrap()
+      }
+      _ => {
+        let target = random_f32() * cumulative[cumulative.len() - 1];
+        cumulative
+          .iter()
+          .position(|sum| *sum >= target)
+          .unwrap_or(cumulative.len() - 1)
+      }
+    }
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every genome
+impl<G: Genome> std::fmt::Debug for Population<G> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Population")
+      .field("size", &self.individuals.len())
+      .field("selection", &self.selection)
+      .field("crossover", &self.crossover)
+      .field("crossover_rate", &self.crossover_rate)
+      .field("mutation", &self.mutation)
+      .field("mutation_rate", &self.mutation_rate)
+      .field("elitism", &self.elitism)
+      .field("generation", &self.generation)
+      .field("last_stats", &self.history.last())
+      .finish()
+  }
+}
+
+/// A child of `a` and `b`. Parents with different numbers of genes only mix the genes they share.
+pub fn crossover<G: Genome>(a: &G, b: &G, method: Crossover) -> G {
+  let mut child = a.clone();
+  let n = a.genes().len().min(b.genes().len());
+  let other = b.genes();
+  let genes = child.genes_mut();
+  match method {
+    Crossover::SinglePoint => {
+      let point = random_range(0, n + 1);
+      genes[point..n].clone_from_slice(&other[point..n]);
+    }
+    Crossover::Uniform => {
+      for i in 0..n {
+        if random_f32() < 0.5 {
+          genes[i] = other[i].clone();
+        }
+      }
+    }
+    Crossover::Blend(alpha) => {
+      for i in 0..n {
+        let t = random_range(-alpha, 1.0 + alpha);
+        genes[i] = genes[i].blend(&other[i], t);
+      }
+    }
+    Crossover::None => {}
+  }
+  child
+}
+
+/// Mutates each gene with probability `rate`
+pub fn mutate<G: Genome>(genome: &mut G, method: Mutation, rate: f32) {
+  let n = genome.genes().len();
+  for i in 0..n {
+    if random_f32() >= rate {
+      continue;
+    }
+    match method {
+      Mutation::Reset => {
+        let gene = genome.random_gene(i);
+        genome.genes_mut()[i] = gene;
+      }
+      Mutation::Creep(amount) => {
+        let gene = genome.genes()[i].perturb(amount);
+        genome.genes_mut()[i] = gene;
+      }
+      Mutation::Swap => {
+        let j = random_range(0, n);
+        genome.genes_mut().swap(i, j);
+      }
+    }
+  }
+}
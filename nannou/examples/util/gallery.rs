@@ -0,0 +1,292 @@
+// Interactive evolution, a.k.a. "aesthetic selection".
+// Instead of a fitness function, the fitness is "the ones I like":
+// a Gallery shows every genome in the population as a thumbnail in a grid,
+// you pick favorites, and the favorites breed the next generation.
+//
+// Params is a ready-made genome for the usual case where a sketch is driven by a handful of numbers.
+// Every gene is stored in [0, 1] and mapped into its own range when you `get` it,
+// so crossover and mutation don't need to know anything about the ranges.
+//
+// Every generation's favorites are appended to a lineage file, one genome per line after a "# generation" comment,
+// in the same "name=value name=value" format that `Params::parse` reads, so any of them can be re-rendered later.
+use nannou::prelude::*;
+
+use std::fs::OpenOptions;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use super::evolve::{Genome, Population};
+use super::rng::random_f32;
+
+#[derive(Debug, Clone)]
+pub struct ParamSpec {
+  pub name: String,
+  pub min: f32,
+  pub max: f32,
+}
+
+pub fn param(name: &str, min: f32, max: f32) -> ParamSpec {
+  ParamSpec {
+    name: String::from(name),
+    min,
+    max,
+  }
+}
+
+#[derive(Clone)]
+pub struct Params {
+  specs: Rc<Vec<ParamSpec>>,
+  genes: Vec<f32>,
+}
+
+impl Params {
+  pub fn random(specs: &Rc<Vec<ParamSpec>>) -> Self {
+    Params {
+      specs: specs.clone(),
+      genes: specs.iter().map(|_| random_f32()).collect(),
+    }
+  }
+
+  /// Reads the "name=value name=value" format written by `Display`.
+  /// Values are in the real ranges, not [0, 1]. Anything missing is random. Panics on unknown names.
+  pub fn parse(specs: &Rc<Vec<ParamSpec>>, line: &str) -> Self {
+    let mut params = Self::random(specs);
+    for pair in line.split_whitespace() {
+      let mut parts = pair.splitn(2, '=');
+      let name = parts.next().unwrap();
+      let value: f32 = parts
+        .next()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| panic!("Invalid param \"{}\", expected name=value", pair));
+      let index = params.index(name);
+      let spec = &specs[index];
+      params.genes[index] = map_range(value, spec.min, spec.max, 0.0, 1.0);
+    }
+    params
+  }
+
+  fn index(&self, name: &str) -> usize {
+    self
+      .specs
+      .iter()
+      .position(|spec| spec.name == name)
+      .unwrap_or_else(|| panic!("Unknown param \"{}\"", name))
+  }
+
+  /// The value of a param, in its range
+  pub fn get(&self, name: &str) -> f32 {
+    let index = self.index(name);
+    let spec = &self.specs[index];
+    map_range(self.genes[index], 0.0, 1.0, spec.min, spec.max)
+  }
+
+  /// For params that are counts
+  pub fn get_usize(&self, name: &str) -> usize {
+    self.get(name).round().max(0.0) as usize
+  }
+}
+
+impl std::fmt::Display for Params {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let pairs: Vec<String> = self
+      .specs
+      .iter()
+      .map(|spec| format!("{}={}", spec.name, self.get(&spec.name)))
+      .collect();
+    write!(f, "{}", pairs.join(" "))
+  }
+}
+
+// implement Debug custom because otherwise
+// every Params would log all of the specs too
+impl std::fmt::Debug for Params {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "Params({})", self)
+  }
+}
+
+impl Genome for Params {
+  type Gene = f32;
+
+  fn genes(&self) -> &[f32] {
+    &self.genes
+  }
+
+  fn genes_mut(&mut self) -> &mut [f32] {
+    &mut self.genes
+  }
+
+  fn random_gene(&self, _index: usize) -> f32 {
+    random_f32()
+  }
+
+  fn repair(&mut self) {
+    for gene in self.genes.iter_mut() {
+      *gene = gene.max(0.0).min(1.0);
+    }
+  }
+}
+
+pub struct Gallery<G: Genome + std::fmt::Display> {
+  population: Population<G>,
+  columns: usize,
+  favorites: Vec<bool>,
+  // (generation, genome) for every favorite so far
+  lineage: Vec<(usize, G)>,
+  lineage_path: Option<PathBuf>,
+  // gap between thumbnails, as a fraction of the thumbnail size
+  padding: f32,
+}
+
+impl<G: Genome + std::fmt::Display> Gallery<G> {
+  /// The population decides how many thumbnails there are.
+  /// Its selection strategy decides how the favorites are picked as parents
+  /// (everything that isn't a favorite has a fitness of 0).
+  pub fn new(population: Population<G>, columns: usize) -> Self {
+    let n = population.len();
+    Gallery {
+      population,
+      columns: columns.max(1),
+      favorites: vec![false; n],
+      lineage: vec![],
+      lineage_path: None,
+      padding: 0.05,
+    }
+  }
+
+  /// Appends every generation's favorites to this file
+  pub fn lineage_path(mut self, path: PathBuf) -> Self {
+    self.lineage_path = Some(path);
+    self
+  }
+
+  pub fn padding(mut self, padding: f32) -> Self {
+    self.padding = padding;
+    self
+  }
+
+  pub fn population(&self) -> &Population<G> {
+    &self.population
+  }
+
+  pub fn genome(&self, index: usize) -> &G {
+    &self.population.individuals()[index].genome
+  }
+
+  pub fn len(&self) -> usize {
+    self.population.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.population.is_empty()
+  }
+
+  pub fn generation(&self) -> usize {
+    self.population.generation()
+  }
+
+  pub fn lineage(&self) -> &[(usize, G)] {
+    &self.lineage
+  }
+
+  pub fn rows(&self) -> usize {
+    (self.len() + self.columns - 1) / self.columns
+  }
+
+  /// One rect per genome, left to right and top to bottom
+  pub fn cells(&self, win: &Rect) -> Vec<Rect> {
+    let w = win.w() / self.columns as f32;
+    let h = win.h() / self.rows().max(1) as f32;
+    (0..self.len())
+      .map(|index| {
+        let (i, j) = (index % self.columns, index / self.columns);
+        Rect::from_x_y_w_h(
+          win.left() + (i as f32 + 0.5) * w,
+          win.top() - (j as f32 + 0.5) * h,
+          w * (1.0 - self.padding),
+          h * (1.0 - self.padding),
+        )
+      })
+      .collect()
+  }
+
+  /// The genome under a point, e.g. the mouse
+  pub fn index_at(&self, point: Point2, win: &Rect) -> Option<usize> {
+    self.cells(win).iter().position(|cell| cell.contains(point))
+  }
+
+  pub fn toggle(&mut self, index: usize) {
+    if index < self.favorites.len() {
+      self.favorites[index] = !self.favorites[index];
+    }
+  }
+
+  pub fn is_favorite(&self, index: usize) -> bool {
+    self.favorites[index]
+  }
+
+  pub fn n_favorites(&self) -> usize {
+    self.favorites.iter().filter(|f| **f).count()
+  }
+
+  /// Breeds the favorites into a new generation.
+  /// The favorites themselves are kept as the first genomes of the new generation
+  /// so a good one never gets lost. Does nothing if there aren't any favorites.
+  /// With only one or two favorites most children are near-copies, so galleries work best with a high mutation rate.
+  pub fn breed(&mut self) {
+    let favorites: Vec<usize> = (0..self.len()).filter(|i| self.favorites[*i]).collect();
+    if favorites.is_empty() {
+      return;
+    }
+    let generation = self.generation();
+    for index in 0..self.len() {
+      self
+        .population
+        .set_fitness(index, if self.favorites[index] { 1.0 } else { 0.0 });
+    }
+    let kept: Vec<G> = favorites.iter().map(|i| self.genome(*i).clone()).collect();
+    for genome in &kept {
+      self.lineage.push((generation, genome.clone()));
+    }
+    self.save_lineage(generation, &kept);
+
+    self.population.next_generation();
+    for (index, genome) in kept.into_iter().enumerate().take(self.len()) {
+      self.population.set_genome(index, genome);
+    }
+    self.favorites = vec![false; self.len()];
+  }
+
+  fn save_lineage(&self, generation: usize, genomes: &[G]) {
+    let path = match &self.lineage_path {
+      Some(path) => path,
+      None => return,
+    };
+    if let Some(dir) = path.parent() {
+      std::fs::create_dir_all(dir).ok();
+    }
+    match OpenOptions::new().create(true).append(true).open(path) {
+      Ok(mut file) => {
+        for genome in genomes {
+          writeln!(file, "# generation {}\n{}", generation, genome).unwrap();
+        }
+      }
+      Err(err) => println!("{}", err),
+    }
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every genome
+impl<G: Genome + std::fmt::Display> std::fmt::Debug for Gallery<G> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Gallery")
+      .field("population", &self.population)
+      .field("columns", &self.columns)
+      .field("n_favorites", &self.n_favorites())
+      .field("lineage_length", &self.lineage.len())
+      .field("lineage_path", &self.lineage_path)
+      .finish()
+  }
+}
@@ -1,4 +1,5 @@
 use super::chance;
+use super::rng::random_range;
 use nannou::prelude::*;
 
 #[derive(Debug, Clone)]
@@ -1,3 +1,5 @@
+use super::rng::random_range;
+use super::spatial::SpatialHash;
 use super::PointCloud;
 use nannou::prelude::*;
 
@@ -70,3 +72,41 @@ pub fn point_cloud(
     })
     .collect()
 }
+
+/// Poisson disk sampling (Bridson's algorithm): random points in `rect`
+/// that are never closer than `radius` to each other, but without big gaps either.
+/// `k` is how many candidates to try around each point before giving up on it, 30 is typical.
+/// https://www.cs.ubc.ca/~rbridson/docs/bridson-siggraph07-poissondisk.pdf
+pub fn poisson_disk(rect: &Rect, radius: f32, k: usize) -> PointCloud {
+  let mut hash = SpatialHash::new(radius);
+  let mut points = vec![];
+  let mut active = vec![];
+  let first = pt2(
+    random_range(rect.left(), rect.right()),
+    random_range(rect.bottom(), rect.top()),
+  );
+  hash.insert(0, first);
+  points.push(first);
+  active.push(0);
+  while !active.is_empty() {
+    let a = random_range(0, active.len());
+    let center = points[active[a]];
+    let mut found = false;
+    for _ in 0..k {
+      let angle = random_range(0.0, 2.0 * PI);
+      let distance = random_range(radius, radius * 2.0);
+      let candidate = center + vec2(angle.cos(), angle.sin()) * distance;
+      if rect.contains(candidate) && !hash.any_within(candidate, radius) {
+        hash.insert(points.len(), candidate);
+        active.push(points.len());
+        points.push(candidate);
+        found = true;
+        break;
+      }
+    }
+    if !found {
+      active.swap_remove(a);
+    }
+  }
+  points
+}
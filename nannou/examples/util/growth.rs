@@ -0,0 +1,448 @@
+// Two classic organic growth algorithms
+//
+// DifferentialGrowth: a line made of nodes that repel each other but stay attached to their neighbors.
+//   Long edges get split, so the line keeps getting longer and has to fold up to fit, like coral or brains.
+//   https://inconvergent.net/generative/differential-line/
+//   https://medium.com/@jason.webb/2d-differential-growth-in-js-1843fd51b0ce
+//
+// SpaceColonization: a tree grows towards a cloud of "attractor" points and eats them as it reaches them.
+//   Works for trees, roots, leaf veins, river networks etc. depending on where the attractors are.
+//   http://algorithmicbotany.org/papers/colonization.egwnp2007.large.pdf
+use nannou::prelude::*;
+
+use super::dla::{tree_branches, tree_descendants, Node};
+use super::polygon::{close_ring, contains};
+use super::rng::random_f32;
+use super::spatial::SpatialHash;
+use super::Line2;
+
+pub struct DifferentialGrowth {
+  nodes: Vec<Point2>,
+  closed: bool,
+  // neighbors closer than this push each other away
+  repulsion_radius: f32,
+  repulsion: f32,
+  // pull towards the connected neighbors
+  attraction: f32,
+  // pull towards the midpoint of the connected neighbors, which straightens things out
+  alignment: f32,
+  // edges longer than this are split in two
+  max_edge: f32,
+  // chance per step of splitting each edge even if it isn't too long, which makes the growth less uniform
+  injection: f32,
+  max_nodes: usize,
+  // nodes can't move outside of this polygon
+  bounds: Option<Line2>,
+  iterations: usize,
+}
+
+impl DifferentialGrowth {
+  /// `closed` lines wrap around, open lines keep their end points fixed
+  pub fn new(line: Line2, closed: bool) -> Self {
+    DifferentialGrowth {
+      nodes: line,
+      closed,
+      repulsion_radius: 10.0,
+      repulsion: 1.0,
+      attraction: 0.1,
+      alignment: 0.1,
+      max_edge: 5.0,
+      injection: 0.005,
+      max_nodes: 10000,
+      bounds: None,
+      iterations: 0,
+    }
+  }
+
+  pub fn repulsion(mut self, radius: f32, force: f32) -> Self {
+    self.repulsion_radius = radius;
+    self.repulsion = force;
+    self
+  }
+
+  pub fn attraction(mut self, attraction: f32) -> Self {
+    self.attraction = attraction;
+    self
+  }
+
+  pub fn alignment(mut self, alignment: f32) -> Self {
+    self.alignment = alignment;
+    self
+  }
+
+  pub fn max_edge(mut self, max_edge: f32) -> Self {
+    self.max_edge = max_edge;
+    self
+  }
+
+  pub fn injection(mut self, probability: f32) -> Self {
+    self.injection = probability;
+    self
+  }
+
+  pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+    self.max_nodes = max_nodes;
+    self
+  }
+
+  pub fn bounds(mut self, polygon: Line2) -> Self {
+    self.bounds = Some(polygon);
+    self
+  }
+
+  pub fn nodes(&self) -> &[Point2] {
+    &self.nodes
+  }
+
+  pub fn iterations(&self) -> usize {
+    self.iterations
+  }
+
+  /// The current line, closed with the first point repeated at the end if it's a closed line
+  pub fn line(&self) -> Line2 {
+    if self.closed {
+      close_ring(&self.nodes)
+    } else {
+      self.nodes.clone()
+    }
+  }
+
+  pub fn steps(&mut self, n: usize) {
+    for _ in 0..n {
+      self.step();
+    }
+  }
+
+  pub fn step(&mut self) {
+    let n = self.nodes.len();
+    if n < 2 {
+      return;
+    }
+    let hash = SpatialHash::from_points(&self.nodes, self.repulsion_radius);
+    let nodes = &self.nodes;
+    let moved: Vec<Point2> = (0..n)
+      .map(|i| {
+        let point = nodes[i];
+        let (prev, next) = match self.neighbors(i) {
+          (Some(prev), Some(next)) => (nodes[prev], nodes[next]),
+          // end points of open lines don't move
+          _ => return point,
+        };
+        let mut force = vec2(0.0, 0.0);
+
+        // only pull on edges that are getting long, otherwise attraction and repulsion
+        // cancel out and the line stops growing
+        for neighbor in &[prev, next] {
+          if neighbor.distance(point) > self.max_edge * 0.5 {
+            force += (*neighbor - point) * self.attraction;
+          }
+        }
+        force += ((prev + next) / 2.0 - point) * self.alignment;
+
+        for j in hash.within(point, self.repulsion_radius) {
+          let away = point - nodes[j];
+          let distance = away.magnitude();
+          if j == i || distance < std::f32::EPSILON {
+            continue;
+          }
+          // stronger the closer they are
+          force += away / distance * (self.repulsion_radius - distance) * self.repulsion
+            / self.repulsion_radius;
+        }
+
+        // limit the speed so the line can't tangle itself up in one step
+        let limit = self.max_edge * 0.2;
+        if force.magnitude() > limit {
+          force = force.normalize() * limit;
+        }
+        let point = point + force;
+        match &self.bounds {
+          Some(bounds) if !contains(bounds, point) => nodes[i],
+          _ => point,
+        }
+      })
+      .collect();
+    self.nodes = moved;
+    self.subdivide();
+    self.iterations += 1;
+  }
+
+  // indices of the connected nodes
+  fn neighbors(&self, i: usize) -> (Option<usize>, Option<usize>) {
+    let n = self.nodes.len();
+    if self.closed {
+      (Some((i + n - 1) % n), Some((i + 1) % n))
+    } else {
+      (
+        if i > 0 { Some(i - 1) } else { None },
+        if i + 1 < n { Some(i + 1) } else { None },
+      )
+    }
+  }
+
+  fn subdivide(&mut self) {
+    let n = self.nodes.len();
+    let n_edges = if self.closed { n } else { n - 1 };
+    let mut nodes = Vec::with_capacity(n * 2);
+    for i in 0..n {
+      let point = self.nodes[i];
+      nodes.push(point);
+      if i >= n_edges || nodes.len() + n - i > self.max_nodes {
+        continue;
+      }
+      let next = self.nodes[(i + 1) % n];
+      if point.distance(next) > self.max_edge || random_f32() < self.injection {
+        nodes.push((point + next) / 2.0);
+      }
+    }
+    self.nodes = nodes;
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every node
+impl std::fmt::Debug for DifferentialGrowth {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("DifferentialGrowth")
+      .field("n_nodes", &self.nodes.len())
+      .field("closed", &self.closed)
+      .field("repulsion_radius", &self.repulsion_radius)
+      .field("repulsion", &self.repulsion)
+      .field("attraction", &self.attraction)
+      .field("alignment", &self.alignment)
+      .field("max_edge", &self.max_edge)
+      .field("injection", &self.injection)
+      .field("bounds", &self.bounds.is_some())
+      .field("iterations", &self.iterations)
+      .finish()
+  }
+}
+
+/// A branch polyline running from a tip towards the root,
+/// with a stroke width for every point
+#[derive(Debug, Clone)]
+pub struct Branch {
+  pub points: Line2,
+  pub widths: Vec<f32>,
+}
+
+pub struct SpaceColonization {
+  attractors: Vec<Point2>,
+  // same kind of nodes as util::dla so the trees can be exported the same way
+  nodes: Vec<Node>,
+  hash: SpatialHash,
+  // attractors only pull on nodes within this distance
+  attraction_distance: f32,
+  // attractors are removed once a node gets this close
+  kill_distance: f32,
+  // length of each new segment
+  segment_length: f32,
+  iterations: usize,
+}
+
+impl SpaceColonization {
+  /// Typically the attractors come from `util::poisson_disk`, `util::point_cloud`,
+  /// or random points inside some shape
+  pub fn new(attractors: Vec<Point2>) -> Self {
+    SpaceColonization {
+      attractors,
+      nodes: vec![],
+      hash: SpatialHash::new(40.0),
+      attraction_distance: 80.0,
+      kill_distance: 10.0,
+      segment_length: 5.0,
+      iterations: 0,
+    }
+  }
+
+  /// Adds a node the tree can grow from. Add several for a forest, or along a line for veins.
+  pub fn root(mut self, point: Point2) -> Self {
+    self.add_node(point, None);
+    self
+  }
+
+  pub fn attraction_distance(mut self, distance: f32) -> Self {
+    self.attraction_distance = distance;
+    self.rehash();
+    self
+  }
+
+  pub fn kill_distance(mut self, distance: f32) -> Self {
+    self.kill_distance = distance;
+    self
+  }
+
+  pub fn segment_length(mut self, length: f32) -> Self {
+    self.segment_length = length;
+    self
+  }
+
+  pub fn nodes(&self) -> &[Node] {
+    &self.nodes
+  }
+
+  pub fn attractors(&self) -> &[Point2] {
+    &self.attractors
+  }
+
+  pub fn iterations(&self) -> usize {
+    self.iterations
+  }
+
+  /// Steps until the tree stops growing, or `max_steps` is reached
+  pub fn grow(&mut self, max_steps: usize) {
+    for _ in 0..max_steps {
+      if !self.step() {
+        break;
+      }
+    }
+  }
+
+  /// Returns false if nothing grew, i.e. the tree is done
+  pub fn step(&mut self) -> bool {
+    if self.nodes.is_empty() || self.attractors.is_empty() {
+      return false;
+    }
+    // sum of directions to the attractors pulling on each node
+    let mut pulls: Vec<Option<Vector2>> = vec![None; self.nodes.len()];
+    for attractor in &self.attractors {
+      if let Some(closest) = self.closest_node(*attractor, self.attraction_distance) {
+        let direction = (*attractor - self.nodes[closest].position).normalize();
+        pulls[closest] = Some(pulls[closest].unwrap_or(vec2(0.0, 0.0)) + direction);
+      }
+    }
+
+    let in_range = pulls.iter().any(|pull| pull.is_some());
+    let mut grew = false;
+    for (parent, pull) in pulls.into_iter().enumerate() {
+      if let Some(pull) = pull {
+        // attractors on opposite sides can cancel each other out
+        if pull.magnitude() < std::f32::EPSILON {
+          continue;
+        }
+        let position = self.nodes[parent].position + pull.normalize() * self.segment_length;
+        grew |= self.try_add(position, parent);
+      }
+    }
+
+    // Nothing is in range yet, so grow the node that is closest to any attractor straight towards it.
+    // This is how a lone root "finds" a cloud of attractors that is far away.
+    if !in_range {
+      // the attractors are usually far away from the few nodes there are at this point,
+      // so search from the nodes with a coarse hash of the attractors
+      let attractors = SpatialHash::from_points(&self.attractors, self.attraction_distance);
+      let closest = self
+        .nodes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, node)| attractors.nearest(node.position).map(|(a, d)| (i, a, d)))
+        .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap());
+      if let Some((parent, attractor, _)) = closest {
+        let direction = (self.attractors[attractor] - self.nodes[parent].position).normalize();
+        let position = self.nodes[parent].position + direction * self.segment_length;
+        grew = self.try_add(position, parent);
+      }
+    }
+
+    let hash = &self.hash;
+    let kill_distance = self.kill_distance;
+    self
+      .attractors
+      .retain(|a| !hash.any_within(*a, kill_distance));
+    self.iterations += 1;
+    grew
+  }
+
+  // Most queries are "nearest node within attraction_distance", so size the cells for that.
+  // Much smaller cells mean checking hundreds of empty cells per attractor.
+  fn rehash(&mut self) {
+    self.hash = SpatialHash::new(self.attraction_distance / 2.0);
+    for (i, node) in self.nodes.iter().enumerate() {
+      self.hash.insert(i, node.position);
+    }
+  }
+
+  // nearest node within `distance`
+  fn closest_node(&self, point: Point2, distance: f32) -> Option<usize> {
+    self
+      .hash
+      .within(point, distance)
+      .into_iter()
+      .min_by(|a, b| {
+        let da = self.nodes[*a].position.distance(point);
+        let db = self.nodes[*b].position.distance(point);
+        da.partial_cmp(&db).unwrap()
+      })
+  }
+
+  // A node that is pulled the same way every step would keep adding the same child forever,
+  // so skip anything that lands right on top of an existing node
+  fn try_add(&mut self, position: Point2, parent: usize) -> bool {
+    if self.hash.any_within(position, self.segment_length * 0.5) {
+      return false;
+    }
+    self.add_node(position, Some(parent));
+    true
+  }
+
+  fn add_node(&mut self, position: Point2, parent: Option<usize>) {
+    let index = self.nodes.len();
+    let depth = parent.map_or(0, |p| self.nodes[p].depth + 1);
+    self.nodes.push(Node {
+      position,
+      parent,
+      depth,
+      age: index,
+    });
+    self.hash.insert(index, position);
+  }
+
+  /// Stroke width of every node, using the "pipe model": the tips are `tip_width` and
+  /// where branches join, width^exponent of the parent is the sum of width^exponent of the children.
+  /// An exponent of 2 is da Vinci's rule (the cross-section area is preserved), higher values taper less.
+  pub fn widths(&self, tip_width: f32, exponent: f32) -> Vec<f32> {
+    let mut sums: Vec<f32> = vec![0.0; self.nodes.len()];
+    let mut widths = vec![tip_width; self.nodes.len()];
+    for index in (0..self.nodes.len()).rev() {
+      if sums[index] > 0.0 {
+        widths[index] = sums[index].powf(1.0 / exponent);
+      }
+      if let Some(parent) = self.nodes[index].parent {
+        sums[parent] += widths[index].powf(exponent);
+      }
+    }
+    widths
+  }
+
+  /// How many nodes hang off each node, including itself
+  pub fn descendants(&self) -> Vec<usize> {
+    tree_descendants(&self.nodes)
+  }
+
+  /// The tree as polylines running from each tip towards the root, with widths from `widths`
+  pub fn branches(&self, tip_width: f32, exponent: f32) -> Vec<Branch> {
+    let widths = self.widths(tip_width, exponent);
+    tree_branches(&self.nodes)
+      .iter()
+      .map(|branch| Branch {
+        points: branch.iter().map(|i| self.nodes[*i].position).collect(),
+        widths: branch.iter().map(|i| widths[*i]).collect(),
+      })
+      .collect()
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every node
+impl std::fmt::Debug for SpaceColonization {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("SpaceColonization")
+      .field("n_nodes", &self.nodes.len())
+      .field("n_attractors", &self.attractors.len())
+      .field("attraction_distance", &self.attraction_distance)
+      .field("kill_distance", &self.kill_distance)
+      .field("segment_length", &self.segment_length)
+      .field("iterations", &self.iterations)
+      .finish()
+  }
+}
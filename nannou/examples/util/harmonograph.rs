@@ -0,0 +1,559 @@
+// Pendulums, spirographs and other curves that go round and round.
+// https://en.wikipedia.org/wiki/Harmonograph
+// and this: http://www.jonathan.lansey.net/pastimes/pendulum/index.html
+//
+// Everything here implements `Curve`, which samples the curve over its domain
+// and returns the points along with `t`, how far along the curve each point is in [0, 1].
+// `t` is handy for color ramps:
+//
+//   let points = Spirograph::epicycloid(300.0, 100.0).points_t();
+//   draw.polyline().points_colored(points.into_iter().map(|(p, t)| (p, hsl(t, 0.5, 0.5))));
+use nannou::prelude::*;
+
+use super::Line2;
+
+pub trait Curve {
+  /// The point at `theta`
+  fn eval(&self, theta: f32) -> Point2;
+
+  /// (start, end) of theta that draws the whole curve
+  fn domain(&self) -> (f32, f32);
+
+  /// Points per unit of theta
+  fn resolution(&self) -> f32;
+
+  /// Every point along with how far along the curve it is, in [0, 1]
+  fn points_t(&self) -> Vec<(Point2, f32)> {
+    let (start, end) = self.domain();
+    let resolution = self.resolution();
+    let n_points = ((end - start) * resolution).floor().max(0.0) as usize;
+    (0..n_points)
+      .map(|n| {
+        let theta = start + n as f32 / resolution;
+        let t = n as f32 / (n_points.max(2) - 1) as f32;
+        (self.eval(theta), t)
+      })
+      .collect()
+  }
+
+  fn points(&self) -> Line2 {
+    self
+      .points_t()
+      .into_iter()
+      .map(|(point, _)| point)
+      .collect()
+  }
+}
+
+pub type Oscillator = fn(f32) -> f32;
+
+pub fn osc_sin(t: f32) -> f32 {
+  t.sin()
+}
+
+pub fn osc_cos(t: f32) -> f32 {
+  t.cos()
+}
+
+pub fn osc_half_sin(t: f32) -> f32 {
+  (t.sin() + 1.) / 2.
+}
+
+pub fn osc_collapse(t: f32) -> f32 {
+  t.sin() * t.powf(-0.35)
+}
+
+pub const OSCILLATORS: [&str; 4] = ["sin", "cos", "half-sin", "collapse"];
+
+/// Looks up an oscillator by name, see OSCILLATORS
+pub fn oscillator(name: &str) -> Option<Oscillator> {
+  match name {
+    "sin" => Some(osc_sin),
+    "cos" => Some(osc_cos),
+    "half-sin" => Some(osc_half_sin),
+    "collapse" => Some(osc_collapse),
+    _ => None,
+  }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Pendulum {
+  amplitude: f32,
+  frequency: f32,
+  phase: f32,
+  damping: f32,
+}
+
+impl Pendulum {
+  pub fn new(amplitude: f32, frequency: f32, phase: f32, damping: f32) -> Self {
+    Pendulum {
+      amplitude,
+      frequency,
+      phase,
+      damping,
+    }
+  }
+
+  pub fn eval(&self, t: f32) -> f32 {
+    self.amplitude * (t * self.frequency + self.phase).sin() * decay(self.damping, t)
+  }
+
+  /// Swinging in a circle instead of back and forth, like the rotary pendulum under the table
+  /// of a three-pendulum harmonograph. Returns the (x, y) offset.
+  pub fn eval_rotary(&self, t: f32) -> Vector2 {
+    let angle = t * self.frequency + self.phase;
+    vec2(angle.sin(), angle.cos()) * self.amplitude * decay(self.damping, t)
+  }
+}
+
+fn decay(damping: f32, t: f32) -> f32 {
+  (-damping * t).exp()
+}
+
+/// Two identical pendulums joined by a spring.
+/// The swing moves back and forth between them, so one slows down while the other speeds up.
+/// https://en.wikipedia.org/wiki/Normal_mode
+#[derive(Debug, Copy, Clone)]
+pub struct CoupledPendulums {
+  amplitude: f32,
+  frequency: f32,
+  // how stiff the spring is compared to gravity. Small values swap the energy slowly
+  coupling: f32,
+  phase: f32,
+  damping: f32,
+}
+
+impl CoupledPendulums {
+  pub fn new(amplitude: f32, frequency: f32, coupling: f32, phase: f32, damping: f32) -> Self {
+    CoupledPendulums {
+      amplitude,
+      frequency,
+      coupling,
+      phase,
+      damping,
+    }
+  }
+
+  /// Positions of both pendulums at `t`, starting with only the first one swinging
+  pub fn eval(&self, t: f32) -> (f32, f32) {
+    // the pair swings together at the plain frequency, and against each other a little faster
+    let together = (t * self.frequency + self.phase).cos();
+    let against = (t * self.frequency * (1.0 + 2.0 * self.coupling).sqrt() + self.phase).cos();
+    let scale = self.amplitude / 2.0 * decay(self.damping, t);
+    ((together + against) * scale, (together - against) * scale)
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Harmonograph {
+  // origin of harmonograph
+  x: f32,
+  y: f32,
+  x_pendulums: Vec<Pendulum>,
+  y_pendulums: Vec<Pendulum>,
+  rotary_pendulums: Vec<Pendulum>,
+  // first pendulum of each pair moves x, second moves y
+  coupled: Vec<CoupledPendulums>,
+  // radians per unit of t that the paper turns under the pen
+  table_rotation: f32,
+  resolution: f32,
+  length: f32,
+  start: f32,
+}
+
+impl Harmonograph {
+  pub fn new() -> Self {
+    Harmonograph {
+      x: 0.0,
+      y: 0.0,
+      x_pendulums: vec![],
+      y_pendulums: vec![],
+      rotary_pendulums: vec![],
+      coupled: vec![],
+      table_rotation: 0.0,
+      resolution: 100.,
+      length: 100.,
+      start: PI / 12.,
+    }
+  }
+
+  pub fn x_y(mut self, x: f32, y: f32) -> Self {
+    self.x = x;
+    self.y = y;
+    self
+  }
+
+  pub fn x_pendulums(mut self, pendulums: Vec<Pendulum>) -> Self {
+    self.x_pendulums = pendulums;
+    self
+  }
+
+  pub fn y_pendulums(mut self, pendulums: Vec<Pendulum>) -> Self {
+    self.y_pendulums = pendulums;
+    self
+  }
+
+  pub fn rotary_pendulums(mut self, pendulums: Vec<Pendulum>) -> Self {
+    self.rotary_pendulums = pendulums;
+    self
+  }
+
+  pub fn coupled(mut self, pairs: Vec<CoupledPendulums>) -> Self {
+    self.coupled = pairs;
+    self
+  }
+
+  pub fn table_rotation(mut self, speed: f32) -> Self {
+    self.table_rotation = speed;
+    self
+  }
+
+  pub fn resolution(mut self, resolution: f32) -> Self {
+    self.resolution = resolution;
+    self
+  }
+
+  pub fn length(mut self, length: f32) -> Self {
+    self.length = length;
+    self
+  }
+
+  pub fn start(mut self, start: f32) -> Self {
+    self.start = start;
+    self
+  }
+}
+
+impl Curve for Harmonograph {
+  fn eval(&self, t: f32) -> Point2 {
+    let x = self
+      .x_pendulums
+      .iter()
+      .fold(0.0, |sum, pendulum| sum + pendulum.eval(t));
+    let y = self
+      .y_pendulums
+      .iter()
+      .fold(0.0, |sum, pendulum| sum + pendulum.eval(t));
+    let rotary = self
+      .rotary_pendulums
+      .iter()
+      .fold(vec2(0.0, 0.0), |sum, pendulum| {
+        sum + pendulum.eval_rotary(t)
+      });
+    let coupled = self.coupled.iter().fold(vec2(0.0, 0.0), |sum, pair| {
+      let (a, b) = pair.eval(t);
+      sum + vec2(a, b)
+    });
+    let point = pt2(x, y) + rotary + coupled;
+    let angle = t * self.table_rotation;
+    let (sin, cos) = (angle.sin(), angle.cos());
+    pt2(
+      point.x * cos - point.y * sin + self.x,
+      point.x * sin + point.y * cos + self.y,
+    )
+  }
+
+  fn domain(&self) -> (f32, f32) {
+    (self.start, self.start + self.length)
+  }
+
+  fn resolution(&self) -> f32 {
+    self.resolution
+  }
+}
+
+/// A pen in a wheel rolling around the inside (hypotrochoid) or outside (epitrochoid) of a ring.
+/// https://en.wikipedia.org/wiki/Hypotrochoid
+/// https://en.wikipedia.org/wiki/Epitrochoid
+#[derive(Debug, Clone)]
+pub struct Spirograph {
+  x: f32,
+  y: f32,
+  ring: f32,
+  wheel: f32,
+  // distance of the pen from the center of the wheel
+  pen: f32,
+  inside: bool,
+  rotation: f32,
+  // times around the ring. Calculated from the radii if not set
+  turns: Option<usize>,
+  resolution: f32,
+}
+
+impl Spirograph {
+  pub fn hypotrochoid(ring: f32, wheel: f32, pen: f32) -> Self {
+    Spirograph {
+      x: 0.0,
+      y: 0.0,
+      ring,
+      wheel,
+      pen,
+      inside: true,
+      rotation: 0.0,
+      turns: None,
+      resolution: 100.0,
+    }
+  }
+
+  pub fn epitrochoid(ring: f32, wheel: f32, pen: f32) -> Self {
+    Spirograph {
+      inside: false,
+      ..Self::hypotrochoid(ring, wheel, pen)
+    }
+  }
+
+  /// Pen on the rim of the wheel, rolling inside the ring. Makes pointy stars
+  /// https://en.wikipedia.org/wiki/Hypocycloid
+  pub fn hypocycloid(ring: f32, wheel: f32) -> Self {
+    Self::hypotrochoid(ring, wheel, wheel)
+  }
+
+  /// Pen on the rim of the wheel, rolling outside the ring. Makes flowers
+  /// https://en.wikipedia.org/wiki/Epicycloid
+  pub fn epicycloid(ring: f32, wheel: f32) -> Self {
+    Self::epitrochoid(ring, wheel, wheel)
+  }
+
+  pub fn x_y(mut self, x: f32, y: f32) -> Self {
+    self.x = x;
+    self.y = y;
+    self
+  }
+
+  pub fn rotate(mut self, radians: f32) -> Self {
+    self.rotation = radians;
+    self
+  }
+
+  pub fn turns(mut self, turns: usize) -> Self {
+    self.turns = Some(turns);
+    self
+  }
+
+  pub fn resolution(mut self, resolution: f32) -> Self {
+    self.resolution = resolution;
+    self
+  }
+
+  /// Times around the ring before the pen is back where it started.
+  /// Irrational-ish ratios never close, so this gives up after 500.
+  pub fn closing_turns(&self) -> usize {
+    self.turns.unwrap_or_else(|| {
+      let ratio = self.ring / self.wheel;
+      (1..500)
+        .find(|n| {
+          let x = *n as f32 * ratio;
+          (x - x.round()).abs() < 1e-3 * *n as f32
+        })
+        .unwrap_or(500)
+    })
+  }
+}
+
+impl Curve for Spirograph {
+  fn eval(&self, theta: f32) -> Point2 {
+    let (r, w, d) = (self.ring, self.wheel, self.pen);
+    let theta = theta + self.rotation;
+    let point = if self.inside {
+      let k = (r - w) / w;
+      pt2(
+        (r - w) * theta.cos() + d * (k * theta).cos(),
+        (r - w) * theta.sin() - d * (k * theta).sin(),
+      )
+    } else {
+      let k = (r + w) / w;
+      pt2(
+        (r + w) * theta.cos() - d * (k * theta).cos(),
+        (r + w) * theta.sin() - d * (k * theta).sin(),
+      )
+    };
+    point + vec2(self.x, self.y)
+  }
+
+  fn domain(&self) -> (f32, f32) {
+    // one extra point so the curve closes
+    (
+      0.0,
+      self.closing_turns() as f32 * 2.0 * PI + 1.0 / self.resolution,
+    )
+  }
+
+  fn resolution(&self) -> f32 {
+    self.resolution
+  }
+}
+
+/// r = cos(n / d * theta)
+/// https://en.wikipedia.org/wiki/Rose_(mathematics)
+#[derive(Debug, Clone)]
+pub struct Rose {
+  x: f32,
+  y: f32,
+  radius: f32,
+  n: usize,
+  d: usize,
+  // 0 draws petals that meet in the middle, higher values open up the center
+  offset: f32,
+  rotation: f32,
+  resolution: f32,
+}
+
+impl Rose {
+  pub fn new(radius: f32, n: usize, d: usize) -> Self {
+    Rose {
+      x: 0.0,
+      y: 0.0,
+      radius,
+      n: n.max(1),
+      d: d.max(1),
+      offset: 0.0,
+      rotation: 0.0,
+      resolution: 100.0,
+    }
+  }
+
+  pub fn x_y(mut self, x: f32, y: f32) -> Self {
+    self.x = x;
+    self.y = y;
+    self
+  }
+
+  pub fn offset(mut self, offset: f32) -> Self {
+    self.offset = offset;
+    self
+  }
+
+  pub fn rotate(mut self, radians: f32) -> Self {
+    self.rotation = radians;
+    self
+  }
+
+  pub fn resolution(mut self, resolution: f32) -> Self {
+    self.resolution = resolution;
+    self
+  }
+}
+
+impl Curve for Rose {
+  fn eval(&self, theta: f32) -> Point2 {
+    let k = self.n as f32 / self.d as f32;
+    let r = self.radius * ((k * theta).cos() + self.offset) / (1.0 + self.offset);
+    let angle = theta + self.rotation;
+    pt2(angle.cos() * r + self.x, angle.sin() * r + self.y)
+  }
+
+  fn domain(&self) -> (f32, f32) {
+    let divisor = gcd(self.n, self.d);
+    let (n, d) = (self.n / divisor, self.d / divisor);
+    // odd petal counts trace over themselves after half a turn
+    let turns = if n % 2 == 1 && d % 2 == 1 && self.offset == 0.0 {
+      d as f32 * 0.5
+    } else {
+      d as f32
+    };
+    (0.0, turns * 2.0 * PI + 1.0 / self.resolution)
+  }
+
+  fn resolution(&self) -> f32 {
+    self.resolution
+  }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+  if b == 0 {
+    a
+  } else {
+    gcd(b, a % b)
+  }
+}
+
+/// Two pendulums at right angles without any damping.
+/// https://en.wikipedia.org/wiki/Lissajous_curve
+#[derive(Debug, Clone)]
+pub struct Lissajous {
+  x: f32,
+  y: f32,
+  w: f32,
+  h: f32,
+  a: usize,
+  b: usize,
+  phase: f32,
+  resolution: f32,
+}
+
+pub const LISSAJOUS_PRESETS: [&str; 7] = [
+  "circle",
+  "figure-eight",
+  "pretzel",
+  "knot",
+  "bow",
+  "weave",
+  "lattice",
+];
+
+impl Lissajous {
+  pub fn new(a: usize, b: usize, phase: f32) -> Self {
+    Lissajous {
+      x: 0.0,
+      y: 0.0,
+      w: 500.0,
+      h: 500.0,
+      a: a.max(1),
+      b: b.max(1),
+      phase,
+      resolution: 100.0,
+    }
+  }
+
+  /// A few classic ratios, see LISSAJOUS_PRESETS. Panics on unknown names
+  pub fn preset(name: &str) -> Self {
+    match name {
+      "circle" => Self::new(1, 1, PI / 2.0),
+      "figure-eight" => Self::new(1, 2, 0.0),
+      "pretzel" => Self::new(3, 2, PI / 2.0),
+      "knot" => Self::new(3, 4, PI / 4.0),
+      "bow" => Self::new(5, 4, PI / 2.0),
+      "weave" => Self::new(5, 6, PI / 4.0),
+      "lattice" => Self::new(9, 8, PI / 2.0),
+      _ => panic!(
+        "Unknown Lissajous preset \"{}\", expected one of {:?}",
+        name, LISSAJOUS_PRESETS
+      ),
+    }
+  }
+
+  pub fn x_y(mut self, x: f32, y: f32) -> Self {
+    self.x = x;
+    self.y = y;
+    self
+  }
+
+  pub fn w_h(mut self, w: f32, h: f32) -> Self {
+    self.w = w;
+    self.h = h;
+    self
+  }
+
+  pub fn resolution(mut self, resolution: f32) -> Self {
+    self.resolution = resolution;
+    self
+  }
+}
+
+impl Curve for Lissajous {
+  fn eval(&self, theta: f32) -> Point2 {
+    pt2(
+      (self.a as f32 * theta + self.phase).sin() * self.w / 2.0 + self.x,
+      (self.b as f32 * theta).sin() * self.h / 2.0 + self.y,
+    )
+  }
+
+  fn domain(&self) -> (f32, f32) {
+    // whole number ratios always close after one turn
+    (0.0, 2.0 * PI + 1.0 / self.resolution)
+  }
+
+  fn resolution(&self) -> f32 {
+    self.resolution
+  }
+}
@@ -0,0 +1,327 @@
+// Hatch and crosshatch fills for arbitrary closed shapes.
+// Any closed Line2 works: blobs, hexagons, prism faces, contour lines, the output of util::polygon, etc.
+//
+// Styles:
+//   Parallel: evenly spaced straight lines at `angle`
+//   Cross:    two layers of parallel lines, the second rotated by `cross_angle`
+//   Contour:  concentric inward offsets that follow the outline of the shape
+//   Noise:    parallel lines that wobble along a Perlin noise field
+//
+// `tone` controls how dark the fill looks by changing the line density:
+// tone 1.0 uses `spacing` exactly, tone 0.5 doubles it, and tone 0.0 draws nothing at all.
+use nannou::noise::{NoiseFn, Perlin};
+use nannou::prelude::*;
+
+use super::polygon;
+use super::rng::random_range;
+use super::{rotate, Line2};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum HatchStyle {
+  Parallel,
+  Cross,
+  Contour,
+  Noise,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Hatch {
+  style: HatchStyle,
+  // radians
+  angle: f32,
+  // angle of the second layer relative to the first, for HatchStyle::Cross
+  cross_angle: f32,
+  // distance between lines at full tone
+  spacing: f32,
+  // in [0, 1], where 1 is darkest
+  tone: f32,
+  // HatchStyle::Noise options
+  noise_scale: f32,
+  noise_amplitude: f32,
+  seed: f32,
+  // maximum length of the segments in noise-warped lines
+  resolution: f32,
+}
+
+impl Hatch {
+  pub fn new() -> Self {
+    Hatch {
+      style: HatchStyle::Parallel,
+      angle: PI / 4.0,
+      cross_angle: PI / 2.0,
+      spacing: 5.0,
+      tone: 1.0,
+      noise_scale: 0.005,
+      noise_amplitude: 10.0,
+      seed: random_range(1.0, 10000.0),
+      resolution: 4.0,
+    }
+  }
+
+  pub fn style(mut self, style: HatchStyle) -> Self {
+    self.style = style;
+    self
+  }
+
+  pub fn parallel(self) -> Self {
+    self.style(HatchStyle::Parallel)
+  }
+
+  pub fn cross(self) -> Self {
+    self.style(HatchStyle::Cross)
+  }
+
+  pub fn contour(self) -> Self {
+    self.style(HatchStyle::Contour)
+  }
+
+  pub fn noise(self) -> Self {
+    self.style(HatchStyle::Noise)
+  }
+
+  pub fn angle(mut self, radians: f32) -> Self {
+    self.angle = radians;
+    self
+  }
+
+  pub fn angle_deg(mut self, degrees: f32) -> Self {
+    self.angle = deg_to_rad(degrees);
+    self
+  }
+
+  pub fn cross_angle(mut self, radians: f32) -> Self {
+    self.cross_angle = radians;
+    self
+  }
+
+  pub fn spacing(mut self, spacing: f32) -> Self {
+    self.spacing = spacing;
+    self
+  }
+
+  pub fn tone(mut self, tone: f32) -> Self {
+    self.tone = tone.max(0.0).min(1.0);
+    self
+  }
+
+  // frequency and strength of the wobble for HatchStyle::Noise
+  pub fn noise_scale(mut self, noise_scale: f32) -> Self {
+    self.noise_scale = noise_scale;
+    self
+  }
+
+  pub fn noise_amplitude(mut self, noise_amplitude: f32) -> Self {
+    self.noise_amplitude = noise_amplitude;
+    self
+  }
+
+  pub fn seed(mut self, seed: f32) -> Self {
+    self.seed = seed;
+    self
+  }
+
+  pub fn resolution(mut self, resolution: f32) -> Self {
+    self.resolution = resolution;
+    self
+  }
+
+  /// The distance between lines once tone is taken into account,
+  /// or None if the tone is too light to draw anything.
+  pub fn effective_spacing(&self) -> Option<f32> {
+    if self.tone <= 0.0 || self.spacing <= 0.0 {
+      None
+    } else {
+      Some(self.spacing / self.tone)
+    }
+  }
+
+  /// Hatch lines filling the polygon
+  pub fn lines(&self, polygon: &Line2) -> Vec<Line2> {
+    self.lines_multi(&[polygon.clone()])
+  }
+
+  /// Hatch lines filling a shape made of several rings.
+  /// Rings inside other rings are treated as holes (even-odd rule),
+  /// so this works directly with the output of `polygon::difference`.
+  pub fn lines_multi(&self, rings: &[Line2]) -> Vec<Line2> {
+    let spacing = match self.effective_spacing() {
+      Some(spacing) => spacing,
+      None => return vec![],
+    };
+    match self.style {
+      HatchStyle::Parallel => parallel_lines(rings, self.angle, spacing),
+      HatchStyle::Cross => {
+        let mut lines = parallel_lines(rings, self.angle, spacing);
+        lines.extend(parallel_lines(
+          rings,
+          self.angle + self.cross_angle,
+          spacing,
+        ));
+        lines
+      }
+      HatchStyle::Contour => self.contour_lines(rings, spacing),
+      HatchStyle::Noise => self.noise_lines(rings, spacing),
+    }
+  }
+
+  pub fn draw(&self, polygon: &Line2, draw: &Draw, color: Hsla, weight: f32) {
+    for line in self.lines(polygon) {
+      draw
+        .polyline()
+        .caps_round()
+        .join_round()
+        .weight(weight)
+        .color(color)
+        .points(line);
+    }
+  }
+
+  // Contours follow the outer rings only. Holes just cut the contours off, like the other styles
+  fn contour_lines(&self, rings: &[Line2], spacing: f32) -> Vec<Line2> {
+    if rings.len() == 1 {
+      return contour_lines(&rings[0], spacing);
+    }
+    rings
+      .iter()
+      .enumerate()
+      .filter(|(i, ring)| {
+        !rings
+          .iter()
+          .enumerate()
+          .any(|(j, other)| *i != j && polygon::contains(other, ring[0]))
+      })
+      .flat_map(|(_, ring)| contour_lines(ring, spacing))
+      .flat_map(|line| polygon::clip_inside_rings(&line, rings))
+      .collect()
+  }
+
+  // Lay straight lines across the whole bounding box, resample them, push each point along the
+  // line's normal by some noise, then clip the wobbly lines back to the shape.
+  fn noise_lines(&self, rings: &[Line2], spacing: f32) -> Vec<Line2> {
+    let perlin = Perlin::new();
+    let all_points: Line2 = rings.iter().flat_map(|r| r.iter().cloned()).collect();
+    let bounds = polygon::bounds(&all_points);
+    // pad the box so wobbling lines still reach the edges of the shape
+    let pad = self.noise_amplitude.abs();
+    let padded = vec![
+      pt2(bounds.left() - pad, bounds.bottom() - pad),
+      pt2(bounds.right() + pad, bounds.bottom() - pad),
+      pt2(bounds.right() + pad, bounds.top() + pad),
+      pt2(bounds.left() - pad, bounds.top() + pad),
+    ];
+    let normal = self.angle + PI / 2.0;
+    parallel_lines(&[padded], self.angle, spacing)
+      .iter()
+      .map(|line| {
+        resample(line, self.resolution)
+          .iter()
+          .map(|p| {
+            let noise = perlin.get([
+              (p.x * self.noise_scale) as f64,
+              (p.y * self.noise_scale) as f64,
+              self.seed as f64,
+            ]) as f32;
+            pt2(
+              p.x + normal.cos() * noise * self.noise_amplitude,
+              p.y + normal.sin() * noise * self.noise_amplitude,
+            )
+          })
+          .collect()
+      })
+      .flat_map(|line: Line2| polygon::clip_inside_rings(&line, rings))
+      .collect()
+  }
+}
+
+/// Straight, evenly spaced lines at `angle` covering the rings (even-odd fill rule).
+/// This is a classic scanline fill: rotate the shape so the hatch lines are horizontal,
+/// find where each horizontal line crosses the edges, fill between pairs of crossings, and rotate back.
+pub fn parallel_lines(rings: &[Line2], angle: f32, spacing: f32) -> Vec<Line2> {
+  let origin = pt2(0.0, 0.0);
+  let rotated: Vec<Line2> = rings
+    .iter()
+    .map(|ring| {
+      polygon::open_ring(ring)
+        .iter()
+        .map(|p| rotate(*p, origin, -angle))
+        .collect()
+    })
+    .collect();
+  let all_points: Line2 = rotated.iter().flat_map(|r| r.iter().cloned()).collect();
+  if all_points.len() < 3 || spacing <= 0.0 {
+    return vec![];
+  }
+  let bounds = polygon::bounds(&all_points);
+
+  let mut lines = vec![];
+  // start half a space in, so the lines are centered in the shape
+  let n_lines = (bounds.h() / spacing).floor() as usize;
+  let start_y = bounds.bottom() + (bounds.h() - n_lines as f32 * spacing) / 2.0;
+  for n in 0..=n_lines {
+    let y = start_y + n as f32 * spacing;
+    let mut crossings: Vec<f32> = vec![];
+    for ring in rotated.iter() {
+      let len = ring.len();
+      for i in 0..len {
+        let a = ring[i];
+        let b = ring[(i + 1) % len];
+        // half-open rule so vertices that sit exactly on the scanline are only counted once
+        if (a.y > y) != (b.y > y) {
+          crossings.push(a.x + (y - a.y) / (b.y - a.y) * (b.x - a.x));
+        }
+      }
+    }
+    crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    for pair in crossings.chunks(2) {
+      if pair.len() == 2 && pair[1] - pair[0] > std::f32::EPSILON {
+        lines.push(vec![
+          rotate(pt2(pair[0], y), origin, angle),
+          rotate(pt2(pair[1], y), origin, angle),
+        ]);
+      }
+    }
+  }
+  lines
+}
+
+/// Concentric rings following the outline of the shape, `spacing` apart, until the shape shrinks away.
+pub fn contour_lines(ring: &Line2, spacing: f32) -> Vec<Line2> {
+  let mut lines = vec![];
+  if spacing <= 0.0 {
+    return lines;
+  }
+  // nothing can be further inside than half the smaller side of the bounding box,
+  // which also guarantees this stops even if an offset misbehaves
+  let bounds = polygon::bounds(ring);
+  let max_depth = bounds.w().min(bounds.h()) / 2.0;
+  // the outline itself isn't part of the hatch, so start one space in
+  let mut n = 1;
+  while n as f32 * spacing <= max_depth {
+    let offsets = polygon::offset(ring, -(n as f32) * spacing);
+    if offsets.is_empty() {
+      break;
+    }
+    lines.extend(offsets.iter().map(polygon::close_ring));
+    n += 1;
+  }
+  lines
+}
+
+// subdivide a line so no segment is longer than `max_length`
+fn resample(line: &Line2, max_length: f32) -> Line2 {
+  let mut points = vec![];
+  for w in line.windows(2) {
+    let n = (w[0].distance(w[1]) / max_length.max(0.1)).ceil().max(1.0) as usize;
+    for i in 0..n {
+      let t = i as f32 / n as f32;
+      points.push(pt2(
+        w[0].x + (w[1].x - w[0].x) * t,
+        w[0].y + (w[1].y - w[0].y) * t,
+      ));
+    }
+  }
+  if let Some(last) = line.last() {
+    points.push(*last);
+  }
+  points
+}
@@ -2,6 +2,8 @@ use nannou::geom::point::Point2;
 use nannou::prelude::*;
 use std::fmt::{self, Display, Formatter};
 
+use super::polygon;
+
 #[derive(Debug, Copy, Clone)]
 pub struct Hexagon {
   pub x: f32,
@@ -14,24 +16,9 @@ impl Hexagon {
     Hexagon { x, y, radius }
   }
 
+  // hexagons that share an edge (e.g. in a honeycomb) are not considered intersecting
   fn has_intersection(&self, other: Hexagon) -> bool {
-    // should be able to use this method but I'll be damned if Rust makes any sense
-    // use nannou::draw::mesh::vertex::IntoVertex;
-    // use nannou::geom::polygon::Polygon;
-    // use nannou::geom::vertex::Vertex2d;
-    // use nannou::mesh::vertex::WithTexCoords;
-    // let points = self.points();
-    // let polygon = Polygon::new(points.iter());
-    // other.points().iter().any(|p| {
-    //   // let vertex = p.into_vertex();
-    //   match polygon.contains(p) {
-    //     // match polygon.contains(&vertex) {
-    //   Some(_) => true,
-    //   None => false
-    // }})
-    let vec1 = Point2::new(self.x, self.y);
-    let vec2 = Point2::new(other.x, other.y);
-    vec1.distance(vec2) < (self.radius + other.radius) * (PI / 6.).cos()
+    polygon::intersects(&self.points(), &other.points())
   }
 
   pub fn points(&self) -> Vec<Point2> {
@@ -0,0 +1,212 @@
+// Hidden-line removal for stacked line drawings like ridge plots (joy division style) and cloth2.rs.
+//
+// The old trick was to draw a background-colored polygon under each line to paint over
+// whatever was behind it. That looks fine as a PNG but the hidden lines are still in the drawing,
+// so it's useless for SVG or a plotter. This does the real thing with a "floating horizon":
+//
+// 1. lines are added front-to-back
+// 2. the window is split into narrow vertical buckets along the x axis.
+//    Each bucket remembers which y ranges are already covered by lines in front
+// 3. each new line is cut wherever it passes through a covered range, and only the visible parts are returned
+// 4. the new line then covers everything from itself down to `depth` below it, in every bucket it crosses
+//
+// With the default infinite depth, a line hides everything below it (classic joy division).
+// A finite depth gives each line a "skirt", like the background polygons in cloth2.rs.
+use nannou::prelude::*;
+
+use super::Line2;
+
+#[derive(Debug, Clone)]
+pub struct Horizon {
+  left: f32,
+  right: f32,
+  resolution: f32,
+  depth: f32,
+  // covered (bottom, top) ranges in each bucket, sorted and non-overlapping
+  buckets: Vec<Vec<(f32, f32)>>,
+}
+
+impl Horizon {
+  pub fn new(left: f32, right: f32) -> Self {
+    Horizon {
+      left,
+      right,
+      resolution: 1.0,
+      depth: std::f32::INFINITY,
+      buckets: vec![],
+    }
+    .resolution(1.0)
+  }
+
+  /// A horizon that covers the horizontal extent of all the lines
+  pub fn from_lines(lines: &[Line2]) -> Self {
+    let (left, right) = lines
+      .iter()
+      .flat_map(|line| line.iter())
+      .fold((std::f32::MAX, std::f32::MIN), |(left, right), p| {
+        (left.min(p.x), right.max(p.x))
+      });
+    if left > right {
+      Horizon::new(0.0, 0.0)
+    } else {
+      Horizon::new(left, right)
+    }
+  }
+
+  // width of the buckets in pixels. Smaller is more accurate and slower.
+  // Clears anything that was already added.
+  pub fn resolution(mut self, resolution: f32) -> Self {
+    self.resolution = resolution.max(0.01);
+    let n_buckets = ((self.right - self.left) / self.resolution).ceil() as usize + 1;
+    self.buckets = vec![vec![]; n_buckets];
+    self
+  }
+
+  // how far below each line the hidden region extends
+  pub fn depth(mut self, depth: f32) -> Self {
+    self.depth = depth;
+    self
+  }
+
+  pub fn clear(&mut self) {
+    for bucket in self.buckets.iter_mut() {
+      bucket.clear();
+    }
+  }
+
+  /// The parts of the line that aren't hidden by anything added so far.
+  /// Doesn't change the horizon.
+  pub fn visible(&self, line: &Line2) -> Vec<Line2> {
+    let mut lines = vec![];
+    let mut current: Line2 = vec![];
+    if let Some(first) = line.first() {
+      if self.covering(*first).is_none() {
+        current.push(*first);
+      }
+    }
+    for segment in line.windows(2) {
+      let (a, b) = (segment[0], segment[1]);
+      // step along the segment at least twice per bucket so we don't jump over any covered ranges
+      let n = (a.distance(b) / (self.resolution / 2.0)).ceil().max(1.0) as usize;
+      let mut previous = a;
+      for step in 1..=n {
+        let t = step as f32 / n as f32;
+        let p = pt2(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t);
+        match (self.covering(p), current.is_empty()) {
+          // just came out from behind something
+          (None, true) => current.push(self.edge(p, previous)),
+          // just went behind something, so finish the line at the edge of the covered range
+          (Some(range), false) => {
+            current.push(cut(previous, p, range));
+            lines.push(current);
+            current = vec![];
+          }
+          _ => (),
+        }
+        previous = p;
+      }
+      if !current.is_empty() {
+        current.push(b);
+      }
+    }
+    if !current.is_empty() {
+      lines.push(current);
+    }
+    lines.retain(|line| line.len() > 1);
+    lines
+  }
+
+  /// Mark everything from the line down to `depth` below it as hidden.
+  pub fn occlude(&mut self, line: &Line2) {
+    for segment in line.windows(2) {
+      let (a, b) = (segment[0], segment[1]);
+      let (i_a, i_b) = (self.bucket_index(a.x), self.bucket_index(b.x));
+      if i_a == i_b {
+        // (almost) vertical segments cover their whole height in a single bucket
+        let top = a.y.max(b.y);
+        self.cover(i_a, a.y.min(b.y) - self.depth, top);
+        continue;
+      }
+      for i in i_a.min(i_b)..=i_a.max(i_b) {
+        let x = self.bucket_x(i).max(a.x.min(b.x)).min(a.x.max(b.x));
+        let y = a.y + (x - a.x) / (b.x - a.x) * (b.y - a.y);
+        self.cover(i, y - self.depth, y);
+      }
+    }
+  }
+
+  /// Returns the visible parts of the line, then adds it to the horizon.
+  /// This is the one to use for lines ordered front-to-back.
+  pub fn add(&mut self, line: &Line2) -> Vec<Line2> {
+    let visible = self.visible(line);
+    self.occlude(line);
+    visible
+  }
+
+  fn bucket_index(&self, x: f32) -> usize {
+    let i = ((x - self.left) / self.resolution).round().max(0.0) as usize;
+    i.min(self.buckets.len() - 1)
+  }
+
+  fn bucket_x(&self, i: usize) -> f32 {
+    self.left + i as f32 * self.resolution
+  }
+
+  // the covered range that hides the point, if any
+  fn covering(&self, point: Point2) -> Option<(f32, f32)> {
+    if point.x < self.left - self.resolution || point.x > self.right + self.resolution {
+      return None;
+    }
+    self.buckets[self.bucket_index(point.x)]
+      .iter()
+      .find(|(bottom, top)| point.y > *bottom && point.y < *top)
+      .cloned()
+  }
+
+  // where the segment from the hidden point to the visible point leaves the covered range
+  fn edge(&self, visible: Point2, hidden: Point2) -> Point2 {
+    match self.covering(hidden) {
+      Some(range) => cut(visible, hidden, range),
+      None => hidden,
+    }
+  }
+
+  // add a range to a bucket, merging it with any ranges it overlaps
+  fn cover(&mut self, i: usize, bottom: f32, top: f32) {
+    let bucket = &mut self.buckets[i];
+    let (mut bottom, mut top) = (bottom, top);
+    bucket.retain(|(b, t)| {
+      if *b <= top && *t >= bottom {
+        bottom = bottom.min(*b);
+        top = top.max(*t);
+        false
+      } else {
+        true
+      }
+    });
+    let index = bucket
+      .iter()
+      .position(|(b, _)| *b > bottom)
+      .unwrap_or_else(|| bucket.len());
+    bucket.insert(index, (bottom, top));
+  }
+}
+
+/// Convenience for the common case: lines ordered front-to-back, where each line hides
+/// everything up to `depth` below it. Use std::f32::INFINITY for a classic ridge plot.
+pub fn remove_hidden_lines(lines: &[Line2], depth: f32) -> Vec<Line2> {
+  let mut horizon = Horizon::from_lines(lines).depth(depth);
+  lines.iter().flat_map(|line| horizon.add(line)).collect()
+}
+
+// the point between `visible` and `hidden` where the segment crosses into the (bottom, top) range
+fn cut(visible: Point2, hidden: Point2, (bottom, top): (f32, f32)) -> Point2 {
+  let edge = if visible.y >= top { top } else { bottom };
+  let dy = hidden.y - visible.y;
+  let t = if dy.abs() > std::f32::EPSILON {
+    ((edge - visible.y) / dy).max(0.0).min(1.0)
+  } else {
+    0.5
+  };
+  pt2(visible.x + (hidden.x - visible.x) * t, visible.y + dy * t)
+}
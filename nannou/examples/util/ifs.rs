@@ -0,0 +1,692 @@
+// Iterated function systems and fractal flames.
+//
+// An IFS is a handful of transforms that each shrink the plane a little.
+// The "chaos game" starts at a random point, then over and over picks one of the transforms at random
+// (weighted by its probability) and moves the point with it. The points land on the fractal.
+// https://en.wikipedia.org/wiki/Iterated_function_system
+//
+// Fractal flames are the same thing with non-linear "variations" after each affine transform,
+// a color coordinate that follows the point around, and a log-density tone map at the end.
+// Everything here follows Scott Draves' paper: https://flam3.com/flame_draves.pdf
+//
+// A Flame keeps its own orbit state and implements attractors::Map2,
+// so util::attractors::{bounds, fit} work for sizing the histogram.
+extern crate image;
+extern crate rayon;
+
+use nannou::prelude::*;
+use nannou::rand::rngs::StdRng;
+use nannou::rand::Rng;
+
+use self::image::RgbImage;
+use self::rayon::prelude::*;
+use std::fs::File;
+use std::io::prelude::*;
+
+use super::attractors::{bounds, fit, orbit_rng, Map2};
+use super::color::{rgb_from_hex, Palette5};
+// random flames and the orbits' starting points come from the seeded util::rng so they can be reproduced,
+// picking transforms in the chaos game runs on every core and uses nannou's per-thread random_*
+use super::rng;
+
+/// x' = a x + b y + c, y' = d x + e y + f
+#[derive(Debug, Copy, Clone)]
+pub struct Affine {
+  pub a: f32,
+  pub b: f32,
+  pub c: f32,
+  pub d: f32,
+  pub e: f32,
+  pub f: f32,
+}
+
+impl Affine {
+  pub fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+    Affine { a, b, c, d, e, f }
+  }
+
+  pub fn identity() -> Self {
+    Self::new(1.0, 0.0, 0.0, 0.0, 1.0, 0.0)
+  }
+
+  /// Scales, then rotates, then moves
+  pub fn scale_rotate_translate(scale: f32, radians: f32, x: f32, y: f32) -> Self {
+    let (sin, cos) = (radians.sin(), radians.cos());
+    Self::new(scale * cos, -scale * sin, x, scale * sin, scale * cos, y)
+  }
+
+  /// Random coefficients in [-1, 1]. Not always contractive, but the variations usually reel it back in
+  pub fn random() -> Self {
+    let r = || rng::random_range(-1.0, 1.0);
+    Self::new(r(), r(), r(), r(), r(), r())
+  }
+
+  pub fn apply(&self, p: Point2) -> Point2 {
+    pt2(
+      self.a * p.x + self.b * p.y + self.c,
+      self.d * p.x + self.e * p.y + self.f,
+    )
+  }
+}
+
+/// The standard flame variations, numbered as in the paper.
+/// Anything that needs parameters beyond the affine transform is left out.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Variation {
+  Linear,
+  Sinusoidal,
+  Spherical,
+  Swirl,
+  Horseshoe,
+  Polar,
+  Handkerchief,
+  Heart,
+  Disc,
+  Spiral,
+  Hyperbolic,
+  Diamond,
+  Ex,
+  Julia,
+  Bent,
+  Fisheye,
+  Exponential,
+  Cosine,
+  Bubble,
+  Cylinder,
+  Eyefish,
+}
+
+pub const VARIATIONS: [&str; 21] = [
+  "linear",
+  "sinusoidal",
+  "spherical",
+  "swirl",
+  "horseshoe",
+  "polar",
+  "handkerchief",
+  "heart",
+  "disc",
+  "spiral",
+  "hyperbolic",
+  "diamond",
+  "ex",
+  "julia",
+  "bent",
+  "fisheye",
+  "exponential",
+  "cosine",
+  "bubble",
+  "cylinder",
+  "eyefish",
+];
+
+/// Looks up a variation by name, see VARIATIONS
+pub fn variation(name: &str) -> Option<Variation> {
+  use self::Variation::*;
+  match name {
+    "linear" => Some(Linear),
+    "sinusoidal" => Some(Sinusoidal),
+    "spherical" => Some(Spherical),
+    "swirl" => Some(Swirl),
+    "horseshoe" => Some(Horseshoe),
+    "polar" => Some(Polar),
+    "handkerchief" => Some(Handkerchief),
+    "heart" => Some(Heart),
+    "disc" => Some(Disc),
+    "spiral" => Some(Spiral),
+    "hyperbolic" => Some(Hyperbolic),
+    "diamond" => Some(Diamond),
+    "ex" => Some(Ex),
+    "julia" => Some(Julia),
+    "bent" => Some(Bent),
+    "fisheye" => Some(Fisheye),
+    "exponential" => Some(Exponential),
+    "cosine" => Some(Cosine),
+    "bubble" => Some(Bubble),
+    "cylinder" => Some(Cylinder),
+    "eyefish" => Some(Eyefish),
+    _ => None,
+  }
+}
+
+impl Variation {
+  pub fn apply(&self, p: Point2) -> Point2 {
+    use self::Variation::*;
+    let Point2 { x, y } = p;
+    // nudged away from 0 so the ones that divide by r don't blow up
+    let r2 = x * x + y * y + 1e-10;
+    let r = r2.sqrt();
+    // the paper measures theta from the y axis
+    let theta = x.atan2(y);
+    match self {
+      Linear => p,
+      Sinusoidal => pt2(x.sin(), y.sin()),
+      Spherical => p / r2,
+      Swirl => pt2(x * r2.sin() - y * r2.cos(), x * r2.cos() + y * r2.sin()),
+      Horseshoe => pt2((x - y) * (x + y), 2.0 * x * y) / r,
+      Polar => pt2(theta / PI, r - 1.0),
+      Handkerchief => pt2((theta + r).sin(), (theta - r).cos()) * r,
+      Heart => pt2((theta * r).sin(), -(theta * r).cos()) * r,
+      Disc => pt2((PI * r).sin(), (PI * r).cos()) * theta / PI,
+      Spiral => pt2(theta.cos() + r.sin(), theta.sin() - r.cos()) / r,
+      Hyperbolic => pt2(theta.sin() / r, r * theta.cos()),
+      Diamond => pt2(theta.sin() * r.cos(), theta.cos() * r.sin()),
+      Ex => {
+        let p0 = (theta + r).sin().powi(3);
+        let p1 = (theta - r).cos().powi(3);
+        pt2(p0 + p1, p0 - p1) * r
+      }
+      Julia => {
+        // randomly picks one of the two square roots
+        let omega = if random_f32() < 0.5 { 0.0 } else { PI };
+        pt2((theta / 2.0 + omega).cos(), (theta / 2.0 + omega).sin()) * r.sqrt()
+      }
+      Bent => match (x >= 0.0, y >= 0.0) {
+        (true, true) => p,
+        (false, true) => pt2(2.0 * x, y),
+        (true, false) => pt2(x, y / 2.0),
+        (false, false) => pt2(2.0 * x, y / 2.0),
+      },
+      Fisheye => pt2(y, x) * 2.0 / (r + 1.0),
+      Exponential => pt2((PI * y).cos(), (PI * y).sin()) * (x - 1.0).exp(),
+      Cosine => pt2((PI * x).cos() * y.cosh(), -(PI * x).sin() * y.sinh()),
+      Bubble => p * 4.0 / (r2 + 4.0),
+      Cylinder => pt2(x.sin(), y),
+      Eyefish => p * 2.0 / (r + 1.0),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Transform {
+  affine: Affine,
+  // blended together by weight
+  variations: Vec<(Variation, f32)>,
+  // applied after the variations
+  post: Option<Affine>,
+  // relative probability of being picked by the chaos game
+  weight: f32,
+  // position in the palette, [0, 1]
+  color: f32,
+  // how quickly points take on this transform's color. 0 leaves the color alone
+  color_speed: f32,
+}
+
+impl Transform {
+  /// Only linear, which makes a plain IFS transform
+  pub fn new(affine: Affine) -> Self {
+    Transform {
+      affine,
+      variations: vec![],
+      post: None,
+      weight: 1.0,
+      color: 0.0,
+      color_speed: 0.5,
+    }
+  }
+
+  pub fn random(variations: &[Variation]) -> Self {
+    let mut transform = Self::new(Affine::random())
+      .weight(rng::random_range(0.2, 1.0))
+      .color(rng::random_f32());
+    for _ in 0..rng::random_range(1, 3) {
+      if !variations.is_empty() {
+        let v = variations[rng::random_range(0, variations.len())];
+        transform = transform.variation(v, rng::random_range(0.2, 1.0));
+      }
+    }
+    transform
+  }
+
+  /// Adds a variation. Without any, the transform is linear
+  pub fn variation(mut self, variation: Variation, weight: f32) -> Self {
+    self.variations.push((variation, weight));
+    self
+  }
+
+  pub fn post(mut self, affine: Affine) -> Self {
+    self.post = Some(affine);
+    self
+  }
+
+  pub fn weight(mut self, weight: f32) -> Self {
+    self.weight = weight.max(0.0);
+    self
+  }
+
+  pub fn color(mut self, color: f32) -> Self {
+    self.color = color;
+    self
+  }
+
+  pub fn color_speed(mut self, speed: f32) -> Self {
+    self.color_speed = speed;
+    self
+  }
+
+  pub fn apply(&self, p: Point2) -> Point2 {
+    let p = self.affine.apply(p);
+    let p = if self.variations.is_empty() {
+      p
+    } else {
+      self
+        .variations
+        .iter()
+        .fold(pt2(0.0, 0.0), |sum, (v, w)| sum + v.apply(p) * *w)
+    };
+    match &self.post {
+      Some(post) => post.apply(p),
+      None => p,
+    }
+  }
+
+  fn mix_color(&self, color: f32) -> f32 {
+    color * (1.0 - self.color_speed) + self.color * self.color_speed
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Flame {
+  transforms: Vec<Transform>,
+  // applied to every point before it's plotted, but never fed back into the orbit
+  final_transform: Option<Transform>,
+  // rgb, the color coordinate is interpolated between these
+  palette: Vec<[f32; 3]>,
+  // orbit state
+  point: Point2,
+  color: f32,
+}
+
+impl Flame {
+  pub fn new(transforms: Vec<Transform>) -> Self {
+    Flame {
+      transforms,
+      final_transform: None,
+      palette: vec![[1.0, 1.0, 1.0]],
+      point: pt2(0.0, 0.0),
+      color: 0.0,
+    }
+  }
+
+  /// `n` random transforms using any of `variations`, colored evenly across the palette
+  pub fn random(n: usize, variations: &[Variation]) -> Self {
+    Self::new(
+      (0..n)
+        .map(|i| Transform::random(variations).color(i as f32 / (n.max(2) - 1) as f32))
+        .collect(),
+    )
+  }
+
+  /// https://en.wikipedia.org/wiki/Sierpi%C5%84ski_triangle
+  pub fn sierpinski() -> Self {
+    let corner = |x: f32, y: f32, color: f32| {
+      Transform::new(Affine::new(0.5, 0.0, x, 0.0, 0.5, y)).color(color)
+    };
+    Self::new(vec![
+      corner(0.0, 0.0, 0.0),
+      corner(0.5, 0.0, 0.5),
+      corner(0.25, 0.5, 1.0),
+    ])
+  }
+
+  /// https://en.wikipedia.org/wiki/Barnsley_fern
+  pub fn barnsley_fern() -> Self {
+    let t = |a, b, c, d, e, f, weight, color| {
+      Transform::new(Affine::new(a, b, c, d, e, f))
+        .weight(weight)
+        .color(color)
+    };
+    Self::new(vec![
+      t(0.0, 0.0, 0.0, 0.0, 0.16, 0.0, 0.01, 0.0),
+      t(0.85, 0.04, 0.0, -0.04, 0.85, 1.6, 0.85, 0.3),
+      t(0.2, -0.26, 0.0, 0.23, 0.22, 1.6, 0.07, 0.7),
+      t(-0.15, 0.28, 0.0, 0.26, 0.24, 0.44, 0.07, 1.0),
+    ])
+  }
+
+  pub fn final_transform(mut self, transform: Transform) -> Self {
+    self.final_transform = Some(transform);
+    self
+  }
+
+  pub fn palette(mut self, palette: Palette5) -> Self {
+    self.palette = palette
+      .iter()
+      .map(|hex| {
+        let rgb = rgb_from_hex(hex);
+        [rgb.red, rgb.green, rgb.blue]
+      })
+      .collect();
+    self
+  }
+
+  pub fn transforms(&self) -> &[Transform] {
+    &self.transforms
+  }
+
+  /// rgb of a color coordinate in [0, 1], blended between palette colors
+  pub fn palette_color(&self, color: f32) -> [f32; 3] {
+    let n = self.palette.len();
+    if n == 1 {
+      return self.palette[0];
+    }
+    let position = color.max(0.0).min(1.0) * (n - 1) as f32;
+    let i = (position.floor() as usize).min(n - 2);
+    let t = position - i as f32;
+    let (a, b) = (self.palette[i], self.palette[i + 1]);
+    [
+      a[0] + (b[0] - a[0]) * t,
+      a[1] + (b[1] - a[1]) * t,
+      a[2] + (b[2] - a[2]) * t,
+    ]
+  }
+
+  fn pick(&self) -> &Transform {
+    let total: f32 = self.transforms.iter().map(|t| t.weight).sum();
+    let mut choice = random_range(0.0, total);
+    for transform in &self.transforms {
+      if choice < transform.weight {
+        return transform;
+      }
+      choice -= transform.weight;
+    }
+    &self.transforms[self.transforms.len() - 1]
+  }
+
+  /// One round of the chaos game. Returns the point to plot and its color coordinate
+  pub fn step(&mut self) -> (Point2, f32) {
+    let (point, color) = {
+      let transform = self.pick();
+      (transform.apply(self.point), transform.mix_color(self.color))
+    };
+    self.point = point;
+    self.color = color;
+    match &self.final_transform {
+      Some(t) => (t.apply(point), t.mix_color(color)),
+      None => (point, color),
+    }
+  }
+}
+
+impl Map2 for Flame {
+  fn next(&mut self) -> Point2 {
+    self.step().0
+  }
+
+  fn reset(&mut self, rng: &mut StdRng) {
+    self.point = pt2(rng.gen_range(-1.0, 1.0), rng.gen_range(-1.0, 1.0));
+    self.color = rng.gen();
+  }
+
+  // the paper skips the first 20
+  fn warmup(&self) -> usize {
+    20
+  }
+}
+
+/// Like attractors::Histogram, but every bin also adds up the color of the points that land in it
+pub struct FlameHistogram {
+  pub width: usize,
+  pub height: usize,
+  bounds: Rect,
+  // row-major, j = 0 at the bottom like the window
+  counts: Vec<f32>,
+  colors: Vec<[f32; 3]>,
+}
+
+impl FlameHistogram {
+  pub fn new(width: usize, height: usize, bounds: Rect) -> Self {
+    FlameHistogram {
+      width,
+      height,
+      bounds,
+      counts: vec![0.0; width * height],
+      colors: vec![[0.0; 3]; width * height],
+    }
+  }
+
+  /// Sizes the bounds automatically from a sample orbit, with a little padding
+  pub fn for_flame(flame: &Flame, width: usize, height: usize) -> Self {
+    let target = Rect::from_w_h(width as f32, height as f32);
+    Self::new(width, height, fit(&bounds(flame, 100_000), &target, 0.05))
+  }
+
+  pub fn bounds(&self) -> &Rect {
+    &self.bounds
+  }
+
+  pub fn max(&self) -> f32 {
+    self.counts.iter().cloned().fold(0.0, f32::max)
+  }
+
+  fn index(&self, point: Point2) -> Option<usize> {
+    let b = &self.bounds;
+    let i = ((point.x - b.left()) / b.w() * self.width as f32).floor();
+    let j = ((point.y - b.bottom()) / b.h() * self.height as f32).floor();
+    // NaN fails these too
+    if i >= 0.0 && j >= 0.0 && i < self.width as f32 && j < self.height as f32 {
+      Some(j as usize * self.width + i as usize)
+    } else {
+      None
+    }
+  }
+
+  /// Plays the chaos game for `orbits` orbits of `iterations` points, spread over all the cores
+  pub fn accumulate(&mut self, flame: &Flame, orbits: usize, iterations: usize) {
+    let n = self.counts.len();
+    // one pair of buffers per thread rather than per orbit, these get big
+    let threads = rayon::current_num_threads().max(1);
+    let seed = rng::seed();
+    let (counts, colors) = (0..threads)
+      .into_par_iter()
+      .map(|thread| {
+        let mut counts = vec![0.0; n];
+        let mut colors = vec![[0.0; 3]; n];
+        let mut flame = flame.clone();
+        for orbit in (thread..orbits).step_by(threads) {
+          let mut rng = orbit_rng(seed, orbit);
+          flame.reset(&mut rng);
+          for _ in 0..flame.warmup() {
+            flame.step();
+          }
+          for _ in 0..iterations {
+            let (p, c) = flame.step();
+            if !p.x.is_finite() || !p.y.is_finite() {
+              flame.reset(&mut rng);
+              continue;
+            }
+            if let Some(index) = self.index(p) {
+              let rgb = flame.palette_color(c);
+              counts[index] += 1.0;
+              colors[index][0] += rgb[0];
+              colors[index][1] += rgb[1];
+              colors[index][2] += rgb[2];
+            }
+          }
+        }
+        (counts, colors)
+      })
+      .reduce(
+        || (vec![0.0; n], vec![[0.0; 3]; n]),
+        |(mut counts, mut colors), (other_counts, other_colors)| {
+          for i in 0..n {
+            counts[i] += other_counts[i];
+            for k in 0..3 {
+              colors[i][k] += other_colors[i][k];
+            }
+          }
+          (counts, colors)
+        },
+      );
+    for i in 0..n {
+      self.counts[i] += counts[i];
+      for k in 0..3 {
+        self.colors[i][k] += colors[i][k];
+      }
+    }
+  }
+
+  /// Tone mapped rgb for every pixel, row-major with j = 0 at the top (like an image).
+  /// Density is log scaled, then:
+  /// - gamma brightens the faint parts, 2.2 is a good start
+  /// - vibrancy 1 applies gamma to the density only, which keeps colors saturated.
+  ///   0 applies it to each channel separately, which washes colors out towards white
+  /// Not clamped, so values can go over 1 (handy for EXR)
+  pub fn render(&self, gamma: f32, vibrancy: f32, background: [f32; 3]) -> Vec<[f32; 3]> {
+    let max = self.max();
+    let log_max = max.ln_1p().max(std::f32::EPSILON);
+    let mut pixels = Vec::with_capacity(self.counts.len());
+    for j in (0..self.height).rev() {
+      for i in 0..self.width {
+        let index = j * self.width + i;
+        let count = self.counts[index];
+        if count == 0.0 {
+          pixels.push(background);
+          continue;
+        }
+        let alpha = count.ln_1p() / log_max;
+        let alpha_gamma = alpha.powf(1.0 / gamma);
+        let mut rgb = [0.0; 3];
+        for k in 0..3 {
+          let color = self.colors[index][k] / count;
+          let vibrant = color * alpha_gamma;
+          let washed = (color * alpha).powf(1.0 / gamma);
+          rgb[k] =
+            vibrancy * vibrant + (1.0 - vibrancy) * washed + background[k] * (1.0 - alpha_gamma);
+        }
+        pixels.push(rgb);
+      }
+    }
+    pixels
+  }
+
+  /// Draws every pixel that was hit as a rectangle. Empty pixels are skipped so the background shows through
+  pub fn draw(&self, draw: &Draw, win: &Rect, gamma: f32, vibrancy: f32, background: [f32; 3]) {
+    let w = win.w() / self.width as f32;
+    let h = win.h() / self.height as f32;
+    let pixels = self.render(gamma, vibrancy, background);
+    for j in 0..self.height {
+      for i in 0..self.width {
+        if self.counts[j * self.width + i] == 0.0 {
+          continue;
+        }
+        // render is top-down, the histogram is bottom-up
+        let [r, g, b] = pixels[(self.height - 1 - j) * self.width + i];
+        draw
+          .rect()
+          .x_y(
+            win.left() + (i as f32 + 0.5) * w,
+            win.bottom() + (j as f32 + 0.5) * h,
+          )
+          .w_h(w, h)
+          .rgb(r.min(1.0), g.min(1.0), b.min(1.0));
+      }
+    }
+  }
+
+  pub fn to_image(&self, gamma: f32, vibrancy: f32, background: [f32; 3]) -> RgbImage {
+    let pixels = self.render(gamma, vibrancy, background);
+    let byte = |v: f32| (v.max(0.0).min(1.0) * 255.0).round() as u8;
+    RgbImage::from_fn(self.width as u32, self.height as u32, |i, j| {
+      let [r, g, b] = pixels[j as usize * self.width + i as usize];
+      image::Rgb([byte(r), byte(g), byte(b)])
+    })
+  }
+
+  pub fn save_png(&self, path: &std::path::Path, gamma: f32, vibrancy: f32, background: [f32; 3]) {
+    if let Err(err) = self.to_image(gamma, vibrancy, background).save(path) {
+      println!("{}", err);
+    }
+  }
+
+  /// Unclamped 32 bit float OpenEXR, for grading the render somewhere else.
+  /// Pass gamma 1 to keep the values linear.
+  pub fn save_exr(&self, path: &std::path::Path, gamma: f32, vibrancy: f32, background: [f32; 3]) {
+    let pixels = self.render(gamma, vibrancy, background);
+    let result = File::create(path)
+      .and_then(|mut file| file.write_all(&exr_bytes(self.width, self.height, &pixels)));
+    match result {
+      Ok(_) => (),
+      Err(err) => println!("{}", err),
+    }
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every bin
+impl std::fmt::Debug for FlameHistogram {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("FlameHistogram")
+      .field("width", &self.width)
+      .field("height", &self.height)
+      .field("bounds", &self.bounds)
+      .field("max", &self.max())
+      .finish()
+  }
+}
+
+// The image crate can't write EXR, but an uncompressed single part scanline file is simple enough.
+// https://www.openexr.com/documentation/openexrfilelayout.pdf
+// `pixels` are row-major rgb with j = 0 at the top.
+fn exr_bytes(width: usize, height: usize, pixels: &[[f32; 3]]) -> Vec<u8> {
+  let mut bytes: Vec<u8> = vec![0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0];
+  let attribute = |bytes: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]| {
+    bytes.extend_from_slice(name.as_bytes());
+    bytes.push(0);
+    bytes.extend_from_slice(kind.as_bytes());
+    bytes.push(0);
+    bytes.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    bytes.extend_from_slice(value);
+  };
+
+  // channels have to be in alphabetical order
+  let mut channels = vec![];
+  for name in &["B", "G", "R"] {
+    channels.extend_from_slice(name.as_bytes());
+    channels.push(0);
+    // 2 = FLOAT, then pLinear and 3 reserved bytes, then x and y sampling
+    channels.extend_from_slice(&2i32.to_le_bytes());
+    channels.extend_from_slice(&[0, 0, 0, 0]);
+    channels.extend_from_slice(&1i32.to_le_bytes());
+    channels.extend_from_slice(&1i32.to_le_bytes());
+  }
+  channels.push(0);
+  let mut window = vec![];
+  for v in &[0, 0, width as i32 - 1, height as i32 - 1] {
+    window.extend_from_slice(&(*v as i32).to_le_bytes());
+  }
+  attribute(&mut bytes, "channels", "chlist", &channels);
+  attribute(&mut bytes, "compression", "compression", &[0]);
+  attribute(&mut bytes, "dataWindow", "box2i", &window);
+  attribute(&mut bytes, "displayWindow", "box2i", &window);
+  attribute(&mut bytes, "lineOrder", "lineOrder", &[0]);
+  attribute(&mut bytes, "pixelAspectRatio", "float", &1f32.to_le_bytes());
+  attribute(&mut bytes, "screenWindowCenter", "v2f", &[0; 8]);
+  attribute(
+    &mut bytes,
+    "screenWindowWidth",
+    "float",
+    &1f32.to_le_bytes(),
+  );
+  bytes.push(0);
+
+  // offset table, one entry per scanline, then the scanlines themselves
+  let line_size = width * 3 * 4;
+  let table_start = bytes.len();
+  let first_line = table_start + height * 8;
+  for j in 0..height {
+    let offset = (first_line + j * (line_size + 8)) as u64;
+    bytes.extend_from_slice(&offset.to_le_bytes());
+  }
+  for j in 0..height {
+    bytes.extend_from_slice(&(j as i32).to_le_bytes());
+    bytes.extend_from_slice(&(line_size as i32).to_le_bytes());
+    // each channel's row in turn: all of B, then G, then R
+    for k in &[2, 1, 0] {
+      for i in 0..width {
+        bytes.extend_from_slice(&pixels[j * width + i][*k].to_le_bytes());
+      }
+    }
+  }
+  bytes
+}
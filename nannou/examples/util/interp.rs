@@ -1,6 +1,8 @@
 use nannou::prelude::*;
 use std::f64::consts::E;
 
+use super::rng::random_f32;
+
 pub struct Interp {}
 
 pub trait Interpolate<T> {
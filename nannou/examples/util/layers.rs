@@ -0,0 +1,149 @@
+// A stack of named raster layers, composited bottom to top into the final frame.
+// Each layer has a blend mode, opacity and optional mask, like in an image editor,
+// so e.g. paper, paint and shading can be built separately and only flattened right before capture.
+//
+//   let mut layers = Layers::new(1024, 1024).background([1.0, 1.0, 1.0, 1.0]);
+//   layers.push(Layer::new("paper", paper.colors(1024, 1024)));
+//   layers.push(Layer::new("paint", paint).mode(BlendMode::Multiply).opacity(0.9));
+//   layers.raster_mut("paint").blur(1.5);
+//   layers.draw(app, &draw, &win);
+use nannou::prelude::*;
+
+use super::raster::BlendMode;
+use super::{DensityMap, Raster};
+
+#[derive(Debug, Clone)]
+pub struct Layer {
+  pub name: String,
+  pub raster: Raster,
+  pub mode: BlendMode,
+  pub opacity: f32,
+  // 1 where the layer shows, stretched to fit like the raster
+  pub mask: Option<DensityMap>,
+  pub visible: bool,
+}
+
+impl Layer {
+  pub fn new(name: &str, raster: Raster) -> Self {
+    Layer {
+      name: String::from(name),
+      raster,
+      mode: BlendMode::Normal,
+      opacity: 1.0,
+      mask: None,
+      visible: true,
+    }
+  }
+
+  pub fn mode(mut self, mode: BlendMode) -> Self {
+    self.mode = mode;
+    self
+  }
+
+  pub fn opacity(mut self, opacity: f32) -> Self {
+    self.opacity = opacity.max(0.0).min(1.0);
+    self
+  }
+
+  pub fn mask(mut self, mask: DensityMap) -> Self {
+    self.mask = Some(mask);
+    self
+  }
+
+  pub fn visible(mut self, visible: bool) -> Self {
+    self.visible = visible;
+    self
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Layers {
+  pub width: usize,
+  pub height: usize,
+  background: [f32; 4],
+  // bottom first
+  layers: Vec<Layer>,
+}
+
+impl Layers {
+  pub fn new(width: usize, height: usize) -> Self {
+    Layers {
+      width,
+      height,
+      background: [0.0, 0.0, 0.0, 0.0],
+      layers: vec![],
+    }
+  }
+
+  /// Color under all the layers, transparent by default
+  pub fn background(mut self, color: [f32; 4]) -> Self {
+    self.background = color;
+    self
+  }
+
+  /// Adds a layer on top. Names have to be unique
+  pub fn push(&mut self, layer: Layer) {
+    if self.get(&layer.name).is_some() {
+      panic!("There is already a layer named \"{}\"", layer.name);
+    }
+    self.layers.push(layer);
+  }
+
+  /// Adds an empty, transparent layer on top and returns its raster to paint into
+  pub fn add(&mut self, name: &str) -> &mut Raster {
+    self.push(Layer::new(name, Raster::new(self.width, self.height)));
+    &mut self.layers.last_mut().unwrap().raster
+  }
+
+  pub fn get(&self, name: &str) -> Option<&Layer> {
+    self.layers.iter().find(|layer| layer.name == name)
+  }
+
+  pub fn get_mut(&mut self, name: &str) -> Option<&mut Layer> {
+    self.layers.iter_mut().find(|layer| layer.name == name)
+  }
+
+  /// The raster of a layer, panics if there is no layer with that name
+  pub fn raster_mut(&mut self, name: &str) -> &mut Raster {
+    let names = self.names();
+    match self.get_mut(name) {
+      Some(layer) => &mut layer.raster,
+      None => panic!("No layer named \"{}\", expected one of {:?}", name, names),
+    }
+  }
+
+  /// Layer names, bottom first
+  pub fn names(&self) -> Vec<String> {
+    self.layers.iter().map(|layer| layer.name.clone()).collect()
+  }
+
+  /// Composites every visible layer onto the background
+  pub fn flatten(&self) -> Raster {
+    let mut frame = Raster::filled(self.width, self.height, self.background);
+    for layer in self.layers.iter().filter(|layer| layer.visible) {
+      frame.composite_masked(
+        &layer.raster,
+        layer.mode,
+        layer.opacity,
+        layer.mask.as_ref(),
+      );
+    }
+    frame
+  }
+
+  /// Flattens the layers and draws the result stretched over `win`, as a single texture
+  pub fn draw(&self, app: &App, draw: &Draw, win: &Rect) {
+    self.flatten().draw(app, draw, win);
+  }
+
+  /// Saves every layer as its own PNG next to `path`, e.g. `{stem}_layer_paint.png`,
+  /// which is handy for checking what each one contributes
+  pub fn save_layers(&self, path: &std::path::Path) {
+    let stem = path.file_stem().unwrap().to_string_lossy();
+    for layer in &self.layers {
+      layer
+        .raster
+        .save(&path.with_file_name(format!("{}_layer_{}.png", stem, layer.name)));
+    }
+  }
+}
@@ -0,0 +1,616 @@
+// L-systems and turtle graphics.
+// The Algorithmic Beauty of Plants is the reference for all of this: http://algorithmicbotany.org/papers/#abop
+//
+// An L-system is an axiom (starting string) and rules that replace every symbol at once, over and over.
+// Symbols can carry parameters, e.g. "F(10.5)" or "A(1,2)".
+// Rules can be
+// - stochastic: several rules for the same symbol, picked at random by weight
+// - context-sensitive: only apply when the symbol has a given neighbor on the left and/or right
+// - parametric: the replacement is built from the symbol's parameters,
+//   optionally only when a condition on the parameters holds
+//
+// The turtle walks the final string and draws:
+//   F, G  move forward and draw. A parameter sets the length
+//   f     move forward without drawing
+//   + -   turn left / right. A parameter sets the angle in degrees
+//   |     turn around
+//   [ ]   save / restore the turtle's state, which starts / ends a branch
+//   > <   multiply / divide the length by length_factor
+//   * /   multiply / divide the angle by angle_factor
+//   !     multiply the stroke width by width_factor
+// Everything else is ignored by the turtle, so it can be used for bookkeeping in rules.
+use nannou::noise::{NoiseFn, Perlin, Seedable};
+use nannou::prelude::*;
+
+use super::rng::{random, random_range};
+use super::Line2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module {
+  pub symbol: char,
+  pub params: Vec<f32>,
+}
+
+impl Module {
+  pub fn param(&self, index: usize) -> Option<f32> {
+    self.params.get(index).cloned()
+  }
+}
+
+/// Reads "F(1,2)+[X]" into modules. Panics on malformed parameters
+pub fn parse(string: &str) -> Vec<Module> {
+  let mut modules = vec![];
+  let mut chars = string.chars().filter(|c| !c.is_whitespace()).peekable();
+  while let Some(symbol) = chars.next() {
+    let mut params = vec![];
+    if chars.peek() == Some(&'(') {
+      chars.next();
+      let inside: String = chars.by_ref().take_while(|c| *c != ')').collect();
+      params = inside
+        .split(',')
+        .map(|p| {
+          p.parse()
+            .unwrap_or_else(|_| panic!("Invalid parameter \"{}\" for {}", p, symbol))
+        })
+        .collect();
+    }
+    modules.push(Module { symbol, params });
+  }
+  modules
+}
+
+/// Writes modules back out in the format `parse` reads
+pub fn to_string(modules: &[Module]) -> String {
+  modules
+    .iter()
+    .map(|m| {
+      if m.params.is_empty() {
+        m.symbol.to_string()
+      } else {
+        let params: Vec<String> = m.params.iter().map(|p| p.to_string()).collect();
+        format!("{}({})", m.symbol, params.join(","))
+      }
+    })
+    .collect()
+}
+
+pub struct Rule {
+  predecessor: char,
+  successor: Box<dyn Fn(&[f32]) -> String>,
+  left: Option<char>,
+  right: Option<char>,
+  condition: Option<Box<dyn Fn(&[f32]) -> bool>>,
+  weight: f32,
+}
+
+impl Rule {
+  pub fn new(predecessor: char, successor: &str) -> Self {
+    let successor = String::from(successor);
+    Self::parametric(predecessor, move |_| successor.clone())
+  }
+
+  /// The successor is built from the predecessor's parameters, e.g.
+  /// Rule::parametric('A', |p| format!("F({})[+A({})]", p[0], p[0] * 0.7))
+  pub fn parametric(predecessor: char, successor: impl Fn(&[f32]) -> String + 'static) -> Self {
+    Rule {
+      predecessor,
+      successor: Box::new(successor),
+      left: None,
+      right: None,
+      condition: None,
+      weight: 1.0,
+    }
+  }
+
+  /// Only applies when this symbol is right before the predecessor
+  pub fn left(mut self, symbol: char) -> Self {
+    self.left = Some(symbol);
+    self
+  }
+
+  /// Only applies when this symbol is right after the predecessor
+  pub fn right(mut self, symbol: char) -> Self {
+    self.right = Some(symbol);
+    self
+  }
+
+  /// Only applies when the predecessor's parameters pass
+  pub fn condition(mut self, condition: impl Fn(&[f32]) -> bool + 'static) -> Self {
+    self.condition = Some(Box::new(condition));
+    self
+  }
+
+  /// Relative chance of being picked when several rules match the same symbol
+  pub fn weight(mut self, weight: f32) -> Self {
+    self.weight = weight;
+    self
+  }
+
+  fn is_context_sensitive(&self) -> bool {
+    self.left.is_some() || self.right.is_some()
+  }
+}
+
+impl std::fmt::Debug for Rule {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Rule")
+      .field("predecessor", &self.predecessor)
+      .field("left", &self.left)
+      .field("right", &self.right)
+      .field("conditional", &self.condition.is_some())
+      .field("weight", &self.weight)
+      .finish()
+  }
+}
+
+#[derive(Debug)]
+pub struct LSystem {
+  axiom: Vec<Module>,
+  rules: Vec<Rule>,
+  // symbols skipped over when matching context, usually the turtle commands
+  ignore: Vec<char>,
+}
+
+impl LSystem {
+  pub fn new(axiom: &str) -> Self {
+    LSystem {
+      axiom: parse(axiom),
+      rules: vec![],
+      ignore: vec![],
+    }
+  }
+
+  pub fn rule(mut self, rule: Rule) -> Self {
+    self.rules.push(rule);
+    self
+  }
+
+  pub fn ignore(mut self, symbols: &str) -> Self {
+    self.ignore = symbols.chars().collect();
+    self
+  }
+
+  pub fn axiom(&self) -> &[Module] {
+    &self.axiom
+  }
+
+  /// The axiom after `iterations` rewrites
+  pub fn generate(&self, iterations: usize) -> Vec<Module> {
+    (0..iterations).fold(self.axiom.clone(), |modules, _| self.rewrite(&modules))
+  }
+
+  /// One rewrite, every module is replaced at the same time
+  pub fn rewrite(&self, modules: &[Module]) -> Vec<Module> {
+    let mut next = Vec::with_capacity(modules.len() * 2);
+    for (index, module) in modules.iter().enumerate() {
+      let matches: Vec<&Rule> = self
+        .rules
+        .iter()
+        .filter(|rule| self.matches(rule, modules, index))
+        .collect();
+      // context-sensitive rules are more specific, so they win
+      let matches: Vec<&Rule> = if matches.iter().any(|r| r.is_context_sensitive()) {
+        matches
+          .into_iter()
+          .filter(|r| r.is_context_sensitive())
+          .collect()
+      } else {
+        matches
+      };
+      match pick(&matches) {
+        Some(rule) => next.extend(parse(&(rule.successor)(&module.params))),
+        None => next.push(module.clone()),
+      }
+    }
+    next
+  }
+
+  fn matches(&self, rule: &Rule, modules: &[Module], index: usize) -> bool {
+    let module = &modules[index];
+    if rule.predecessor != module.symbol {
+      return false;
+    }
+    if let Some(left) = rule.left {
+      if self.left_context(modules, index) != Some(left) {
+        return false;
+      }
+    }
+    if let Some(right) = rule.right {
+      if self.right_context(modules, index) != Some(right) {
+        return false;
+      }
+    }
+    match &rule.condition {
+      Some(condition) => condition(&module.params),
+      None => true,
+    }
+  }
+
+  // Nearest symbol to the left that isn't ignored.
+  // Whole branches are skipped, and the start of a branch is skipped to get to its parent
+  fn left_context(&self, modules: &[Module], index: usize) -> Option<char> {
+    let mut depth = 0;
+    for module in modules[..index].iter().rev() {
+      match module.symbol {
+        ']' => depth += 1,
+        '[' => depth = (depth - 1).max(0),
+        c if depth == 0 && !self.ignore.contains(&c) => return Some(c),
+        _ => {}
+      }
+    }
+    None
+  }
+
+  // Nearest symbol to the right that isn't ignored, skipping over any branches.
+  // The end of the current branch is the end of the line
+  fn right_context(&self, modules: &[Module], index: usize) -> Option<char> {
+    let mut depth = 0;
+    for module in &modules[index + 1..] {
+      match module.symbol {
+        '[' => depth += 1,
+        ']' if depth == 0 => return None,
+        ']' => depth -= 1,
+        c if depth == 0 && !self.ignore.contains(&c) => return Some(c),
+        _ => {}
+      }
+    }
+    None
+  }
+}
+
+fn pick<'a>(rules: &[&'a Rule]) -> Option<&'a Rule> {
+  if rules.len() < 2 {
+    return rules.first().cloned();
+  }
+  let total: f32 = rules.iter().map(|r| r.weight).sum();
+  let mut choice = random_range(0.0, total);
+  for rule in rules {
+    if choice < rule.weight {
+      return Some(rule);
+    }
+    choice -= rule.weight;
+  }
+  rules.last().cloned()
+}
+
+/// A continuous stroke drawn by the turtle
+#[derive(Debug, Clone)]
+pub struct TurtlePath {
+  pub points: Line2,
+  // how many branches deep, 0 is the trunk
+  pub depth: usize,
+  pub width: f32,
+}
+
+#[derive(Debug, Clone)]
+struct TurtleState {
+  position: Point2,
+  heading: f32,
+  length: f32,
+  angle: f32,
+  width: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Turtle {
+  start: Point2,
+  // radians, 0 is to the right
+  heading: f32,
+  length: f32,
+  // radians
+  angle: f32,
+  length_factor: f32,
+  angle_factor: f32,
+  width: f32,
+  width_factor: f32,
+  // max random change to every turn (radians) and step (fraction of the length)
+  angle_jitter: f32,
+  length_jitter: f32,
+  noise_scale: f64,
+  seed: u32,
+}
+
+impl Turtle {
+  /// Starts at the origin, pointing up
+  pub fn new(length: f32, angle_degrees: f32) -> Self {
+    Turtle {
+      start: pt2(0.0, 0.0),
+      heading: PI / 2.0,
+      length,
+      angle: deg_to_rad(angle_degrees),
+      length_factor: 0.7,
+      angle_factor: 0.9,
+      width: 1.0,
+      width_factor: 0.7,
+      angle_jitter: 0.0,
+      length_jitter: 0.0,
+      noise_scale: 0.01,
+      seed: random(),
+    }
+  }
+
+  pub fn position(mut self, position: Point2) -> Self {
+    self.start = position;
+    self
+  }
+
+  pub fn heading(mut self, radians: f32) -> Self {
+    self.heading = radians;
+    self
+  }
+
+  pub fn length_factor(mut self, factor: f32) -> Self {
+    self.length_factor = factor;
+    self
+  }
+
+  pub fn angle_factor(mut self, factor: f32) -> Self {
+    self.angle_factor = factor;
+    self
+  }
+
+  pub fn width(mut self, width: f32) -> Self {
+    self.width = width;
+    self
+  }
+
+  pub fn width_factor(mut self, factor: f32) -> Self {
+    self.width_factor = factor;
+    self
+  }
+
+  /// Wobbles every turn by up to `angle` radians and every step by up to `length` times its length.
+  /// The wobble comes from Perlin noise at the turtle's position, so nearby branches bend together
+  pub fn jitter(mut self, angle: f32, length: f32) -> Self {
+    self.angle_jitter = angle;
+    self.length_jitter = length;
+    self
+  }
+
+  pub fn noise_scale(mut self, scale: f64) -> Self {
+    self.noise_scale = scale;
+    self
+  }
+
+  pub fn seed(mut self, seed: u32) -> Self {
+    self.seed = seed;
+    self
+  }
+
+  /// Walks the modules and returns every stroke.
+  /// A branch's stroke starts where it leaves its parent, and the parent's stroke carries on after the branch
+  pub fn paths(&self, modules: &[Module]) -> Vec<TurtlePath> {
+    let noise = Perlin::new().set_seed(self.seed);
+    let wobble = |p: Point2, offset: f64| -> f32 {
+      noise.get([
+        p.x as f64 * self.noise_scale + offset,
+        p.y as f64 * self.noise_scale,
+      ]) as f32
+    };
+
+    let mut state = TurtleState {
+      position: self.start,
+      heading: self.heading,
+      length: self.length,
+      angle: self.angle,
+      width: self.width,
+    };
+    let mut current = vec![state.position];
+    let mut stack: Vec<(TurtleState, Line2)> = vec![];
+    let mut paths = vec![];
+
+    // finishes the current stroke if it has anything in it
+    let finish = |line: Line2, depth: usize, width: f32, paths: &mut Vec<TurtlePath>| {
+      if line.len() > 1 {
+        paths.push(TurtlePath {
+          points: line,
+          depth,
+          width,
+        });
+      }
+    };
+
+    for module in modules {
+      match module.symbol {
+        'F' | 'G' | 'f' => {
+          let length = module.param(0).unwrap_or(state.length)
+            * (1.0 + wobble(state.position, 100.0) * self.length_jitter);
+          state.position = state.position + vec2(state.heading.cos(), state.heading.sin()) * length;
+          if module.symbol == 'f' {
+            let line = std::mem::replace(&mut current, vec![state.position]);
+            finish(line, stack.len(), state.width, &mut paths);
+          } else {
+            current.push(state.position);
+          }
+        }
+        '+' | '-' => {
+          let angle = module.param(0).map(deg_to_rad).unwrap_or(state.angle)
+            + wobble(state.position, 0.0) * self.angle_jitter;
+          state.heading += if module.symbol == '+' { angle } else { -angle };
+        }
+        '|' => state.heading += PI,
+        '[' => {
+          let line = std::mem::replace(&mut current, vec![state.position]);
+          stack.push((state.clone(), line));
+        }
+        ']' => {
+          if let Some((saved, line)) = stack.pop() {
+            let branch = std::mem::replace(&mut current, line);
+            finish(branch, stack.len() + 1, state.width, &mut paths);
+            state = saved;
+          }
+        }
+        '>' => state.length *= self.length_factor,
+        '<' => state.length /= self.length_factor,
+        '*' => state.angle *= self.angle_factor,
+        '/' => state.angle /= self.angle_factor,
+        '!' => state.width *= self.width_factor,
+        _ => {}
+      }
+    }
+    // anything left open, innermost first
+    finish(current, stack.len(), state.width, &mut paths);
+    while let Some((saved, line)) = stack.pop() {
+      finish(line, stack.len(), saved.width, &mut paths);
+    }
+    paths
+  }
+
+  /// Just the points of every stroke
+  pub fn lines(&self, modules: &[Module]) -> Vec<Line2> {
+    self.paths(modules).into_iter().map(|p| p.points).collect()
+  }
+}
+
+/// Scales and moves the paths to fit inside `rect`, keeping their proportions.
+/// Widths are scaled too
+pub fn fit(paths: &mut [TurtlePath], rect: &Rect) {
+  let points = paths.iter().flat_map(|p| p.points.iter());
+  let (min, max) = points.fold(
+    (
+      pt2(std::f32::MAX, std::f32::MAX),
+      pt2(std::f32::MIN, std::f32::MIN),
+    ),
+    |(min, max), p| {
+      (
+        pt2(min.x.min(p.x), min.y.min(p.y)),
+        pt2(max.x.max(p.x), max.y.max(p.y)),
+      )
+    },
+  );
+  if min.x > max.x {
+    return;
+  }
+  let size = max - min;
+  let scale = (rect.w() / size.x.max(1e-6)).min(rect.h() / size.y.max(1e-6));
+  let center = (min + max) / 2.0;
+  for path in paths.iter_mut() {
+    for p in path.points.iter_mut() {
+      *p = rect.xy() + (*p - center) * scale;
+    }
+    path.width *= scale;
+  }
+}
+
+/// An L-system along with the turn angle and number of iterations it's meant for
+#[derive(Debug)]
+pub struct Preset {
+  pub system: LSystem,
+  // degrees
+  pub angle: f32,
+  pub iterations: usize,
+}
+
+pub const PRESETS: [&str; 11] = [
+  "koch",
+  "koch-snowflake",
+  "dragon",
+  "hilbert",
+  "sierpinski",
+  "sierpinski-arrowhead",
+  "plant",
+  "bush",
+  "stochastic-plant",
+  "parametric-tree",
+  "context-plant",
+];
+
+/// Panics on unknown names, see PRESETS
+pub fn preset(name: &str) -> Preset {
+  let preset = |system, angle, iterations| Preset {
+    system,
+    angle,
+    iterations,
+  };
+  match name {
+    "koch" => preset(LSystem::new("F").rule(Rule::new('F', "F+F-F-F+F")), 90.0, 4),
+    "koch-snowflake" => preset(
+      LSystem::new("F--F--F").rule(Rule::new('F', "F+F--F+F")),
+      60.0,
+      4,
+    ),
+    "dragon" => preset(
+      LSystem::new("FX")
+        .rule(Rule::new('X', "X+YF+"))
+        .rule(Rule::new('Y', "-FX-Y")),
+      90.0,
+      12,
+    ),
+    "hilbert" => preset(
+      LSystem::new("A")
+        .rule(Rule::new('A', "+BF-AFA-FB+"))
+        .rule(Rule::new('B', "-AF+BFB+FA-")),
+      90.0,
+      5,
+    ),
+    "sierpinski" => preset(
+      LSystem::new("F-G-G")
+        .rule(Rule::new('F', "F-G+F+G-F"))
+        .rule(Rule::new('G', "GG")),
+      120.0,
+      6,
+    ),
+    "sierpinski-arrowhead" => preset(
+      LSystem::new("F")
+        .rule(Rule::new('F', "G-F-G"))
+        .rule(Rule::new('G', "F+G+F")),
+      60.0,
+      7,
+    ),
+    // ABOP figure 1.24 f
+    "plant" => preset(
+      LSystem::new("X")
+        .rule(Rule::new('X', "F+[[X]-X]-F[-FX]+X"))
+        .rule(Rule::new('F', "FF")),
+      25.0,
+      6,
+    ),
+    // ABOP figure 1.24 d
+    "bush" => preset(
+      LSystem::new("F").rule(Rule::new('F', "FF-[-F+F+F]+[+F-F-F]")),
+      22.5,
+      4,
+    ),
+    // ABOP figure 1.27
+    "stochastic-plant" => preset(
+      LSystem::new("F")
+        .rule(Rule::new('F', "F[+F]F[-F]F").weight(0.33))
+        .rule(Rule::new('F', "F[+F]F").weight(0.33))
+        .rule(Rule::new('F', "F[-F]F").weight(0.34)),
+      25.7,
+      5,
+    ),
+    // branches get shorter and thinner until they're too short to bother with
+    "parametric-tree" => preset(
+      LSystem::new("A(100)").rule(
+        Rule::parametric('A', |p| {
+          format!("F({})[+!A({})][-!A({})]", p[0], p[0] * 0.7, p[0] * 0.65)
+        })
+        .condition(|p| p[0] > 4.0),
+      ),
+      30.0,
+      12,
+    ),
+    // ABOP figure 1.31 a: a signal travels up the plant and sprouts branches on its way
+    "context-plant" => preset(
+      LSystem::new("F1F1F1")
+        .ignore("+-F")
+        .rule(Rule::new('0', "0").left('0').right('0'))
+        .rule(Rule::new('0', "1[+F1F1]").left('0').right('1'))
+        .rule(Rule::new('1', "1").left('0').right('0'))
+        .rule(Rule::new('1', "1").left('0').right('1'))
+        .rule(Rule::new('0', "0").left('1').right('0'))
+        .rule(Rule::new('0', "1F1").left('1').right('1'))
+        .rule(Rule::new('1', "1").left('1').right('0'))
+        .rule(Rule::new('1', "0").left('1').right('1'))
+        .rule(Rule::new('+', "-"))
+        .rule(Rule::new('-', "+")),
+      22.5,
+      30,
+    ),
+    _ => panic!(
+      "Unknown L-system \"{}\", expected one of {:?}",
+      name, PRESETS
+    ),
+  }
+}
@@ -9,6 +9,9 @@ use nannou::prelude::*;
 use std::fmt::Debug;
 use std::fs::File;
 use std::io::prelude::*;
+use std::sync::Mutex;
+
+use self::rng::{random_f32, random_range};
 
 pub mod args;
 pub mod blob;
@@ -18,8 +21,9 @@ pub mod color;
 pub mod grid;
 pub mod hexagon;
 pub mod interp;
-pub use self::grid::{grid, point_cloud};
+pub use self::grid::{grid, point_cloud, poisson_disk};
 pub mod gravity;
+pub mod harmonograph;
 pub use self::gravity::{GravitationalBody, GravitySystem};
 pub mod prism;
 pub use self::prism::Prism;
@@ -27,6 +31,58 @@ pub mod walker;
 pub use self::walker::PrismaticWalker;
 pub mod contours;
 pub use self::contours::{Deque2, Triangle3D};
+pub mod density;
+pub use self::density::DensityMap;
+pub mod spatial;
+pub use self::spatial::SpatialHash;
+pub mod polygon;
+pub mod stipple;
+pub use self::stipple::Stippler;
+pub mod hatch;
+pub use self::hatch::{Hatch, HatchStyle};
+pub mod hidden_line;
+pub use self::hidden_line::Horizon;
+pub mod reaction_diffusion;
+pub use self::reaction_diffusion::{GrayScott, ReactionDiffusion};
+pub mod simulation;
+pub use self::simulation::Simulation;
+pub mod turing;
+pub use self::turing::MultiScaleTuring;
+pub mod attractors;
+pub use self::attractors::{Histogram, Map2};
+pub mod automata;
+pub mod dla;
+pub use self::dla::Dla;
+pub mod growth;
+pub use self::growth::{DifferentialGrowth, SpaceColonization};
+pub mod evolve;
+pub use self::evolve::{Genome, Population};
+pub mod gallery;
+pub use self::gallery::{Gallery, Params};
+pub mod approximate;
+pub use self::approximate::{Approximation, Primitive, PrimitiveKind};
+pub mod ifs;
+pub use self::ifs::{Flame, FlameHistogram};
+pub mod lsystem;
+pub use self::lsystem::{LSystem, Rule, Turtle};
+pub mod space_filling;
+pub use self::space_filling::{CurveKind, SpaceFillingCurve};
+pub mod polyline;
+pub mod stroke;
+pub use self::stroke::Stroke;
+pub mod raster;
+pub use self::raster::Raster;
+pub mod layers;
+pub use self::layers::{Layer, Layers};
+pub mod texture;
+pub use self::texture::{Paper, Texture};
+pub mod watercolor;
+pub use self::watercolor::Watercolor;
+pub mod animation;
+pub use self::animation::{Clock, Recorder};
+pub mod provenance;
+pub use self::provenance::Provenance;
+pub mod rng;
 
 pub type Line2 = Vec<Point2>;
 // Why have two type aliases for the same thing?
@@ -34,6 +90,19 @@ pub type Line2 = Vec<Point2>;
 // even though their structure is the same
 pub type PointCloud = Vec<Point2>;
 
+// the time of the first capture, so every file from one run shares a prefix
+static RUN_STARTED: Mutex<Option<String>> = Mutex::new(None);
+
+/// `{exe name}_{timestamp}`, where the timestamp is fixed the first time this is called.
+/// Used to name captured frames, so frame sequences from one run sort together
+pub fn run_id(app: &App) -> String {
+  let mut started = RUN_STARTED.lock().unwrap();
+  let timestamp = started
+    .get_or_insert_with(|| Local::now().format("%Y-%m-%dT%H-%M-%S").to_string())
+    .clone();
+  format!("{}_{}", app.exe_name().unwrap(), timestamp)
+}
+
 pub fn captured_frame_path_multi(
   app: &App,
   frame: &Frame,
@@ -48,9 +117,8 @@ pub fn captured_frame_path_multi(
     .join(app.exe_name().unwrap())
     // Name each file after the number of the frame.
     .join(format!(
-      "{}_{}_{:03}{}{}",
-      app.exe_name().unwrap(),
-      Local::now().format("%Y-%m-%dT%H-%M-%S"),
+      "{}_{:03}{}{}",
+      run_id(app),
       frame.nth(),
       letter,
       formatted
@@ -74,21 +142,53 @@ pub fn captured_frame_path_txt(app: &App, frame: &Frame) -> std::path::PathBuf {
     .join("assets")
     .join(app.exe_name().unwrap())
     // Name each file after the number of the frame.
-    .join(format!(
-      "{}_{}_{:03}",
-      app.exe_name().unwrap(),
-      Local::now().format("%Y-%m-%dT%H-%M-%S"),
-      frame.nth()
-    ))
+    .join(format!("{}_{:03}", run_id(app), frame.nth()))
     .with_extension("txt")
 }
 
+/// Captures the main window to `captured_frame_path` and embeds the run's provenance once it's written,
+/// see `embed_provenance`. Returns the path, for sketches that save other files next to it
+pub fn capture(app: &App, frame: &Frame) -> std::path::PathBuf {
+  let path = captured_frame_path(app, frame);
+  app.main_window().capture_frame(path.clone());
+  embed_provenance(app, path.clone());
+  path
+}
+
+/// Same as `capture`, with nannou's capture_frame_threaded
+pub fn capture_threaded(app: &App, frame: &Frame) -> std::path::PathBuf {
+  let path = captured_frame_path(app, frame);
+  app.main_window().capture_frame_threaded(path.clone());
+  embed_provenance(app, path.clone());
+  path
+}
+
+/// Embeds the sketch name, resolved args, rng seed, palette and commit into a captured PNG
+/// once nannou has written it (see util::provenance). `--xmp true` also writes an XMP sidecar.
+/// For sketches that capture to their own path instead of using `capture`
+pub fn embed_provenance(app: &App, path: std::path::PathBuf) {
+  queue_provenance(Provenance::new(app), path);
+}
+
+/// Saves the model next to the captured frame, and adds it to the frame's provenance
 pub fn capture_model<T: Debug>(app: &App, frame: &Frame, model: &T) {
   let path = captured_frame_path_txt(app, frame);
   match File::create(path) {
     Ok(mut file) => file.write_all(format!("{:?}", model).as_bytes()).unwrap(),
     Err(err) => println!("{}", err),
   }
+  queue_provenance(
+    Provenance::new(app).model(model),
+    captured_frame_path(app, frame),
+  );
+}
+
+fn queue_provenance(provenance: Provenance, path: std::path::PathBuf) {
+  // peek rather than get, so every capture's params don't end up with an "xmp" in them
+  if args::ArgParser::new().peek("xmp").unwrap_or(false) {
+    provenance.write_xmp(&path);
+  }
+  provenance.embed_when_written(path);
 }
 
 // simple default smoothing function
@@ -97,7 +197,7 @@ pub fn smooth(line: &Line2) -> Line2 {
 }
 
 // basic line-smoothing algorithm
-// TODO: investigate other smoothing algorithms, not sure this is very efficient/good
+// see util::polyline for Chaikin, Catmull-Rom and B-spline smoothing
 pub fn smooth_by(smoothness: usize, line: &Line2) -> Line2 {
   let length = line.len();
   line
@@ -147,7 +247,7 @@ pub fn meander(old_line: &Line2, depth: i32, divergence: f32) -> Line2 {
       let x_mid = (two.x + one.x) / 2.0;
       let y_mid = (two.y + one.y) / 2.0;
       let distance = one.distance(two);
-      let orientation = ((two.y - one.y) / (two.x - one.x)).atan();
+      let orientation = polyline::orientation(one, two);
       let perpendicular = orientation + PI / 2.;
       // must use a conditional here because random_range doesn't like it when start == end
       let offset = if divergence == 0.0 {
@@ -0,0 +1,790 @@
+// Polygon geometry over plain `Line2`s.
+// A polygon is just a Line2 describing a closed ring.
+// The closing point may or may not be repeated at the end (Blob::points() repeats it, Hexagon::points() does not),
+// every function here accepts both.
+//
+// The boolean operations are an implementation of the Greiner-Hormann algorithm,
+// which is a simplified Weiler-Atherton:
+// http://www.inf.usi.ch/hormann/papers/Greiner.1998.ECO.pdf
+// Like Weiler-Atherton, it only works on simple polygons (no self-intersections).
+// Greiner-Hormann falls over when a vertex of one polygon sits exactly on an edge of the other,
+// so in that case we nudge one of the polygons by a fraction of a pixel and try again.
+// That's completely invisible in a drawing, and a lot less code than handling every degenerate case.
+use nannou::prelude::*;
+
+use super::Line2;
+
+const EPSILON: f32 = 1e-4;
+
+/// Returns the ring without the repeated closing point (if there was one)
+pub fn open_ring(polygon: &Line2) -> Line2 {
+  let mut ring = polygon.clone();
+  while ring.len() > 1 && ring[0].distance(ring[ring.len() - 1]) < EPSILON {
+    ring.pop();
+  }
+  ring
+}
+
+/// Returns the ring with the first point repeated at the end, which is handy for drawing polylines.
+pub fn close_ring(polygon: &Line2) -> Line2 {
+  let mut ring = open_ring(polygon);
+  if let Some(first) = ring.first().cloned() {
+    ring.push(first);
+  }
+  ring
+}
+
+// iterate the edges of an open ring, including the closing edge
+fn edges(ring: &Line2) -> impl Iterator<Item = (Point2, Point2)> + '_ {
+  (0..ring.len()).map(move |i| (ring[i], ring[(i + 1) % ring.len()]))
+}
+
+/// Shoelace formula. Positive for counter-clockwise polygons, negative for clockwise.
+pub fn signed_area(polygon: &Line2) -> f32 {
+  let ring = open_ring(polygon);
+  edges(&ring)
+    .map(|(a, b)| a.x * b.y - b.x * a.y)
+    .sum::<f32>()
+    / 2.0
+}
+
+pub fn area(polygon: &Line2) -> f32 {
+  signed_area(polygon).abs()
+}
+
+pub fn is_clockwise(polygon: &Line2) -> bool {
+  signed_area(polygon) < 0.0
+}
+
+pub fn centroid(polygon: &Line2) -> Point2 {
+  let ring = open_ring(polygon);
+  let a = signed_area(&ring);
+  if a.abs() < EPSILON {
+    // degenerate polygon, fall back to the average of the vertices
+    let n = ring.len().max(1) as f32;
+    return pt2(
+      ring.iter().map(|p| p.x).sum::<f32>() / n,
+      ring.iter().map(|p| p.y).sum::<f32>() / n,
+    );
+  }
+  let (x, y) = edges(&ring).fold((0.0, 0.0), |(x, y), (p, q)| {
+    let cross = p.x * q.y - q.x * p.y;
+    (x + (p.x + q.x) * cross, y + (p.y + q.y) * cross)
+  });
+  pt2(x / (6.0 * a), y / (6.0 * a))
+}
+
+pub fn bounds(polygon: &Line2) -> Rect {
+  let (min, max) = polygon.iter().fold(
+    (
+      pt2(std::f32::MAX, std::f32::MAX),
+      pt2(std::f32::MIN, std::f32::MIN),
+    ),
+    |(min, max), p| {
+      (
+        pt2(min.x.min(p.x), min.y.min(p.y)),
+        pt2(max.x.max(p.x), max.y.max(p.y)),
+      )
+    },
+  );
+  Rect::from_corners(min, max)
+}
+
+/// Even-odd ray casting point-in-polygon test.
+/// Points exactly on the boundary may go either way.
+pub fn contains(polygon: &Line2, point: Point2) -> bool {
+  let ring = open_ring(polygon);
+  let mut inside = false;
+  for (a, b) in edges(&ring) {
+    if (a.y > point.y) != (b.y > point.y) {
+      let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+      if point.x < x {
+        inside = !inside;
+      }
+    }
+  }
+  inside
+}
+
+/// Even-odd point-in-polygon test for a shape made of several rings, so rings inside rings are holes.
+pub fn contains_rings(rings: &[Line2], point: Point2) -> bool {
+  rings.iter().filter(|ring| contains(ring, point)).count() % 2 == 1
+}
+
+/// Distance from a point to the closest point on the polygon's boundary
+pub fn distance_to_boundary(polygon: &Line2, point: Point2) -> f32 {
+  let ring = open_ring(polygon);
+  edges(&ring)
+    .map(|(a, b)| distance_to_segment(point, a, b))
+    .fold(std::f32::MAX, f32::min)
+}
+
+pub fn distance_to_segment(point: Point2, a: Point2, b: Point2) -> f32 {
+  point.distance(closest_point_on_segment(point, a, b).0)
+}
+
+/// The closest point to `point` on segment a->b, along with how far along the segment it is, in [0, 1]
+pub fn closest_point_on_segment(point: Point2, a: Point2, b: Point2) -> (Point2, f32) {
+  let ab = pt2(b.x - a.x, b.y - a.y);
+  let length2 = ab.x * ab.x + ab.y * ab.y;
+  if length2 == 0.0 {
+    return (a, 0.0);
+  }
+  let t = (((point.x - a.x) * ab.x + (point.y - a.y) * ab.y) / length2)
+    .max(0.0)
+    .min(1.0);
+  (pt2(a.x + ab.x * t, a.y + ab.y * t), t)
+}
+
+// strictly inside, i.e. not within EPSILON of the boundary
+fn contains_strictly(polygon: &Line2, point: Point2) -> bool {
+  contains(polygon, point) && distance_to_boundary(polygon, point) > EPSILON
+}
+
+/// Intersection of segments a1->a2 and b1->b2.
+/// Returns the point along with how far along each segment it occurs, both in [0, 1].
+/// Parallel segments never intersect, even if they overlap.
+pub fn segment_intersection(
+  a1: Point2,
+  a2: Point2,
+  b1: Point2,
+  b2: Point2,
+) -> Option<(Point2, f32, f32)> {
+  let r = pt2(a2.x - a1.x, a2.y - a1.y);
+  let s = pt2(b2.x - b1.x, b2.y - b1.y);
+  let denominator = r.x * s.y - r.y * s.x;
+  if denominator.abs() < std::f32::EPSILON {
+    return None;
+  }
+  let qp = pt2(b1.x - a1.x, b1.y - a1.y);
+  let t = (qp.x * s.y - qp.y * s.x) / denominator;
+  let u = (qp.x * r.y - qp.y * r.x) / denominator;
+  if t < 0.0 || t > 1.0 || u < 0.0 || u > 1.0 {
+    return None;
+  }
+  Some((pt2(a1.x + r.x * t, a1.y + r.y * t), t, u))
+}
+
+/// True when the interiors of the two polygons overlap.
+/// Polygons that only share an edge or a vertex (like tiled hexagons) do not count as overlapping.
+pub fn intersects(a: &Line2, b: &Line2) -> bool {
+  let a = open_ring(a);
+  let b = open_ring(b);
+  // cheap rejection with the bounding boxes
+  let (ba, bb) = (bounds(&a), bounds(&b));
+  if ba.right() < bb.left()
+    || bb.right() < ba.left()
+    || ba.top() < bb.bottom()
+    || bb.top() < ba.bottom()
+  {
+    return false;
+  }
+  // any proper crossing of edges
+  for (a1, a2) in edges(&a) {
+    for (b1, b2) in edges(&b) {
+      if let Some((_, t, u)) = segment_intersection(a1, a2, b1, b2) {
+        if t > EPSILON && t < 1.0 - EPSILON && u > EPSILON && u < 1.0 - EPSILON {
+          return true;
+        }
+      }
+    }
+  }
+  // no crossings, so either one is inside the other, they are identical, or they are disjoint
+  a.iter().any(|p| contains_strictly(&b, *p))
+    || b.iter().any(|p| contains_strictly(&a, *p))
+    || contains_strictly(&b, centroid(&a))
+    || contains_strictly(&a, centroid(&b))
+}
+
+/// Union of two simple polygons.
+/// Disjoint polygons are returned unchanged as two rings.
+pub fn union(a: &Line2, b: &Line2) -> Vec<Line2> {
+  boolean(a, b, Operation::Union)
+}
+
+/// Intersection of two simple polygons. May return several rings, or none.
+pub fn intersection(a: &Line2, b: &Line2) -> Vec<Line2> {
+  boolean(a, b, Operation::Intersection)
+}
+
+/// `a` with `b` cut out of it. May return several rings, or none.
+/// When `b` sits entirely inside `a` the result has a hole,
+/// which is returned as a second ring with the opposite winding of the first.
+pub fn difference(a: &Line2, b: &Line2) -> Vec<Line2> {
+  boolean(a, b, Operation::Difference)
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Operation {
+  Union,
+  Intersection,
+  Difference,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Node {
+  point: Point2,
+  intersection: bool,
+  // for intersections: does walking forward from here enter the other polygon?
+  entry: bool,
+  // for intersections: index of the same intersection in the other polygon's node list
+  neighbor: usize,
+  visited: bool,
+}
+
+impl Node {
+  fn vertex(point: Point2) -> Self {
+    Node {
+      point,
+      intersection: false,
+      entry: false,
+      neighbor: 0,
+      visited: false,
+    }
+  }
+}
+
+fn boolean(a: &Line2, b: &Line2, operation: Operation) -> Vec<Line2> {
+  let a = open_ring(a);
+  let mut b = open_ring(b);
+  if a.len() < 3 || b.len() < 3 {
+    return vec![];
+  }
+  // nudge b off of any degenerate configuration.
+  // The nudge grows each attempt in case we're unlucky enough to land on another degeneracy
+  let mut attempt = 0;
+  while is_degenerate(&a, &b) && attempt < 10 {
+    attempt += 1;
+    let nudge = EPSILON * 10.0 * attempt as f32;
+    b = b
+      .iter()
+      .map(|p| pt2(p.x + nudge, p.y + nudge * 0.618))
+      .collect();
+  }
+
+  let (mut nodes_a, mut nodes_b) = build_nodes(&a, &b);
+
+  if !nodes_a.iter().any(|n| n.intersection) {
+    return boolean_without_crossings(&a, &b, operation);
+  }
+
+  // Mark entry/exit. Walking along `a`, each intersection alternates between entering and exiting `b`.
+  // For a union we want to walk the parts *outside* the other polygon, so everything is flipped.
+  // For a difference we want the parts of `a` outside `b`, and the parts of `b` inside `a` (walked backwards).
+  let (flip_a, flip_b) = match operation {
+    Operation::Intersection => (false, false),
+    Operation::Union => (true, true),
+    Operation::Difference => (true, false),
+  };
+  mark_entries(&mut nodes_a, &b, flip_a);
+  mark_entries(&mut nodes_b, &a, flip_b);
+
+  // Traverse. Forward on entries, backward on exits, and hop to the other polygon at every intersection
+  let mut rings = vec![];
+  while let Some(start) = nodes_a.iter().position(|n| n.intersection && !n.visited) {
+    let mut ring = vec![];
+    let mut on_a = true;
+    let mut current = start;
+    loop {
+      {
+        let (nodes, other) = if on_a {
+          (&mut nodes_a, &mut nodes_b)
+        } else {
+          (&mut nodes_b, &mut nodes_a)
+        };
+        nodes[current].visited = true;
+        let neighbor = nodes[current].neighbor;
+        other[neighbor].visited = true;
+        ring.push(nodes[current].point);
+        let forward = nodes[current].entry;
+        let len = nodes.len();
+        loop {
+          current = if forward {
+            (current + 1) % len
+          } else {
+            (current + len - 1) % len
+          };
+          if nodes[current].intersection {
+            break;
+          }
+          ring.push(nodes[current].point);
+        }
+        current = nodes[current].neighbor;
+      }
+      on_a = !on_a;
+      let back_at_start = if on_a {
+        current == start
+      } else {
+        nodes_b[current].neighbor == start
+      };
+      if back_at_start || ring.len() > nodes_a.len() + nodes_b.len() {
+        break;
+      }
+    }
+    if ring.len() >= 3 {
+      rings.push(ring);
+    }
+  }
+  rings
+}
+
+// true when any vertex of one polygon is (nearly) on an edge of the other,
+// or two edges are parallel and overlapping
+fn is_degenerate(a: &Line2, b: &Line2) -> bool {
+  a.iter()
+    .any(|p| distance_to_boundary(b, *p) < EPSILON * 10.0)
+    || b
+      .iter()
+      .any(|p| distance_to_boundary(a, *p) < EPSILON * 10.0)
+}
+
+// Builds the node lists for both polygons, with intersections inserted in order along each edge
+fn build_nodes(a: &Line2, b: &Line2) -> (Vec<Node>, Vec<Node>) {
+  // (edge index in a, alpha along a, edge index in b, alpha along b, point)
+  let mut crossings = vec![];
+  for (i, (a1, a2)) in edges(a).enumerate() {
+    for (j, (b1, b2)) in edges(b).enumerate() {
+      if let Some((point, t, u)) = segment_intersection(a1, a2, b1, b2) {
+        crossings.push((i, t, j, u, point));
+      }
+    }
+  }
+
+  // position of each crossing within the node lists
+  let mut position_a = vec![0; crossings.len()];
+  let mut position_b = vec![0; crossings.len()];
+  let mut nodes_a = vec![];
+  for (i, p) in a.iter().enumerate() {
+    nodes_a.push(Node::vertex(*p));
+    let mut on_edge: Vec<usize> = (0..crossings.len())
+      .filter(|k| crossings[*k].0 == i)
+      .collect();
+    on_edge.sort_by(|k1, k2| crossings[*k1].1.partial_cmp(&crossings[*k2].1).unwrap());
+    for k in on_edge {
+      position_a[k] = nodes_a.len();
+      let mut node = Node::vertex(crossings[k].4);
+      node.intersection = true;
+      nodes_a.push(node);
+    }
+  }
+  let mut nodes_b = vec![];
+  for (j, p) in b.iter().enumerate() {
+    nodes_b.push(Node::vertex(*p));
+    let mut on_edge: Vec<usize> = (0..crossings.len())
+      .filter(|k| crossings[*k].2 == j)
+      .collect();
+    on_edge.sort_by(|k1, k2| crossings[*k1].3.partial_cmp(&crossings[*k2].3).unwrap());
+    for k in on_edge {
+      position_b[k] = nodes_b.len();
+      let mut node = Node::vertex(crossings[k].4);
+      node.intersection = true;
+      nodes_b.push(node);
+    }
+  }
+  for k in 0..crossings.len() {
+    nodes_a[position_a[k]].neighbor = position_b[k];
+    nodes_b[position_b[k]].neighbor = position_a[k];
+  }
+  (nodes_a, nodes_b)
+}
+
+fn mark_entries(nodes: &mut Vec<Node>, other: &Line2, flip: bool) {
+  // the first node is always an original vertex, never an intersection
+  let mut inside = contains(other, nodes[0].point);
+  for node in nodes.iter_mut() {
+    if node.intersection {
+      node.entry = !inside != flip;
+      inside = !inside;
+    }
+  }
+}
+
+fn boolean_without_crossings(a: &Line2, b: &Line2, operation: Operation) -> Vec<Line2> {
+  let a_in_b = contains(b, a[0]);
+  let b_in_a = contains(a, b[0]);
+  match operation {
+    Operation::Union if a_in_b => vec![b.clone()],
+    Operation::Union if b_in_a => vec![a.clone()],
+    Operation::Union => vec![a.clone(), b.clone()],
+    Operation::Intersection if a_in_b => vec![a.clone()],
+    Operation::Intersection if b_in_a => vec![b.clone()],
+    Operation::Intersection => vec![],
+    Operation::Difference if a_in_b => vec![],
+    Operation::Difference if b_in_a => {
+      // a hole: the inner ring winds the opposite way of the outer ring
+      let hole = if is_clockwise(a) == is_clockwise(b) {
+        b.iter().rev().cloned().collect()
+      } else {
+        b.clone()
+      };
+      vec![a.clone(), hole]
+    }
+    Operation::Difference => vec![a.clone()],
+  }
+}
+
+/// Offsets (a.k.a. insets/outsets, or "buffers") a polygon by `distance`.
+/// Positive distances grow the polygon, negative distances shrink it.
+/// Corners sharper than the miter limit are beveled so they don't shoot off to infinity.
+///
+/// Large inward offsets of concave shapes produce loops where the offset edges cross over each other,
+/// those loops are cut off, which can split the result into several rings.
+/// An empty Vec means the polygon shrank away to nothing.
+pub fn offset(polygon: &Line2, distance: f32) -> Vec<Line2> {
+  let ring = open_ring(polygon);
+  let n = ring.len();
+  if n < 3 || distance == 0.0 {
+    return vec![ring];
+  }
+  // normals point outwards, so which side that is depends on the winding
+  let winding = if is_clockwise(&ring) { -1.0 } else { 1.0 };
+  let miter_limit = 4.0;
+  let mut points = Vec::with_capacity(n * 2);
+  for i in 0..n {
+    let prev = ring[(i + n - 1) % n];
+    let curr = ring[i];
+    let next = ring[(i + 1) % n];
+    let n1 = outward_normal(prev, curr, winding);
+    let n2 = outward_normal(curr, next, winding);
+    let bisector = pt2(n1.x + n2.x, n1.y + n2.y);
+    let bisector_length = bisector.x.hypot(bisector.y);
+    // cos of half the turning angle
+    let cos_half = bisector_length / 2.0;
+    if bisector_length < EPSILON || 1.0 / cos_half > miter_limit {
+      // bevel: one point for each adjacent edge
+      points.push(pt2(curr.x + n1.x * distance, curr.y + n1.y * distance));
+      points.push(pt2(curr.x + n2.x * distance, curr.y + n2.y * distance));
+    } else {
+      let miter = distance / cos_half;
+      points.push(pt2(
+        curr.x + bisector.x / bisector_length * miter,
+        curr.y + bisector.y / bisector_length * miter,
+      ));
+    }
+  }
+
+  // keep only the loops that wind the same way as the original polygon,
+  // loops that wind backwards are where the offset turned itself inside out.
+  // A loop can also flip "twice" and keep its winding (e.g. shrinking a square past its center),
+  // but then its vertices end up closer to the original boundary than `distance`.
+  // Shrunk far enough, it flips all the way around and ends up outside the original,
+  // so an inward offset also has to stay inside
+  let original_clockwise = is_clockwise(&ring);
+  let min_distance = distance.abs() * 0.99 - EPSILON;
+  split_self_intersections(&points)
+    .into_iter()
+    .filter(|r| r.len() >= 3 && is_clockwise(r) == original_clockwise && area(r) > EPSILON)
+    .filter(|r| {
+      r.iter()
+        .all(|p| distance_to_boundary(&ring, *p) >= min_distance)
+    })
+    .filter(|r| distance > 0.0 || r.iter().all(|p| contains(&ring, *p)))
+    .collect()
+}
+
+// unit normal of the edge a->b, pointing away from the polygon's interior
+fn outward_normal(a: Point2, b: Point2, winding: f32) -> Point2 {
+  let dx = b.x - a.x;
+  let dy = b.y - a.y;
+  let length = dx.hypot(dy).max(std::f32::EPSILON);
+  // for a counter-clockwise polygon, the outside is on the right of each edge
+  pt2(dy / length * winding, -dx / length * winding)
+}
+
+/// Splits a self-intersecting ring into simple rings at each crossing.
+/// Places where the ring touches itself (a vertex sitting on another edge) count as crossings too.
+pub fn split_self_intersections(polygon: &Line2) -> Vec<Line2> {
+  // every split produces two smaller loops, so this can't recurse deeper than the number of points
+  split_self_intersections_to_depth(polygon, polygon.len())
+}
+
+fn split_self_intersections_to_depth(polygon: &Line2, depth: usize) -> Vec<Line2> {
+  let ring = dedupe(&open_ring(polygon));
+  let n = ring.len();
+  if n < 4 || depth == 0 {
+    return vec![ring];
+  }
+  for i in 0..n {
+    // j starts at i + 2 to skip the adjacent edge, and the last edge is adjacent to the first
+    for j in (i + 2)..n {
+      if i == 0 && j == n - 1 {
+        continue;
+      }
+      let (a1, a2) = (ring[i], ring[(i + 1) % n]);
+      let (b1, b2) = (ring[j], ring[(j + 1) % n]);
+      if let Some((point, t, u)) = segment_intersection(a1, a2, b1, b2) {
+        // touching at the *start* of an edge is the same as touching at the end of the previous edge,
+        // so only count the end to avoid splitting twice at the same place
+        if t <= EPSILON || u <= EPSILON {
+          continue;
+        }
+        // the loop between the two edges, and everything else
+        let mut inner = vec![point];
+        inner.extend_from_slice(&ring[i + 1..=j]);
+        let mut outer = vec![point];
+        outer.extend_from_slice(&ring[j + 1..]);
+        outer.extend_from_slice(&ring[..=i]);
+        let (inner, outer) = (dedupe(&inner), dedupe(&outer));
+        // touching at a shared vertex can leave a "loop" with no area, which isn't a split at all
+        if inner.len() < 3 || outer.len() < 3 {
+          continue;
+        }
+        let mut rings = split_self_intersections_to_depth(&inner, depth - 1);
+        rings.extend(split_self_intersections_to_depth(&outer, depth - 1));
+        return rings;
+      }
+    }
+  }
+  vec![ring]
+}
+
+// removes consecutive points that sit on top of each other, including across the closing edge
+fn dedupe(ring: &Line2) -> Line2 {
+  let mut deduped: Line2 = Vec::with_capacity(ring.len());
+  for p in ring {
+    if deduped
+      .last()
+      .map_or(true, |last| last.distance(*p) > EPSILON)
+    {
+      deduped.push(*p);
+    }
+  }
+  open_ring(&deduped)
+}
+
+/// The parts of a polyline that fall inside the polygon
+pub fn clip_inside(line: &Line2, polygon: &Line2) -> Vec<Line2> {
+  clip(line, polygon, true)
+}
+
+/// The parts of a polyline that fall outside the polygon.
+/// Useful for occlusion: anything drawn "behind" a shape is clipped to the outside of the shape.
+pub fn clip_outside(line: &Line2, polygon: &Line2) -> Vec<Line2> {
+  clip(line, polygon, false)
+}
+
+/// The parts of a polyline inside a shape made of several rings, using the even-odd rule.
+/// Rings inside other rings are holes, which matches the output of `difference`.
+pub fn clip_inside_rings(line: &Line2, rings: &[Line2]) -> Vec<Line2> {
+  clip_rings(line, rings, true)
+}
+
+fn clip(line: &Line2, polygon: &Line2, keep_inside: bool) -> Vec<Line2> {
+  clip_rings(line, &[polygon.clone()], keep_inside)
+}
+
+fn clip_rings(line: &Line2, rings: &[Line2], keep_inside: bool) -> Vec<Line2> {
+  let rings: Vec<Line2> = rings
+    .iter()
+    .map(open_ring)
+    .filter(|r| r.len() >= 3)
+    .collect();
+  let mut pieces: Vec<Line2> = vec![];
+  let mut current: Line2 = vec![];
+  if line.len() < 2 || rings.is_empty() {
+    return pieces;
+  }
+  for w in line.windows(2) {
+    let (start, end) = (w[0], w[1]);
+    // split the segment at every place it crosses a boundary
+    let mut ts: Vec<f32> = rings
+      .iter()
+      .flat_map(|ring| edges(ring).collect::<Vec<_>>())
+      .filter_map(|(a, b)| segment_intersection(start, end, a, b))
+      .map(|(_, t, _)| t)
+      .collect();
+    ts.push(0.0);
+    ts.push(1.0);
+    ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    for pair in ts.windows(2) {
+      let (t0, t1) = (pair[0], pair[1]);
+      if t1 - t0 < std::f32::EPSILON {
+        continue;
+      }
+      let p0 = lerp_points(start, end, t0);
+      let p1 = lerp_points(start, end, t1);
+      let mid = lerp_points(start, end, (t0 + t1) / 2.0);
+      if contains_rings(&rings, mid) == keep_inside {
+        if current.is_empty() {
+          current.push(p0);
+        }
+        current.push(p1);
+      } else if !current.is_empty() {
+        pieces.push(current);
+        current = vec![];
+      }
+    }
+  }
+  if current.len() > 1 {
+    pieces.push(current);
+  }
+  pieces
+}
+
+/// Clips a polyline against several polygons at once, keeping only the parts outside all of them.
+pub fn clip_outside_all(line: &Line2, polygons: &[Line2]) -> Vec<Line2> {
+  polygons.iter().fold(vec![line.clone()], |pieces, polygon| {
+    pieces
+      .iter()
+      .flat_map(|piece| clip_outside(piece, polygon))
+      .collect()
+  })
+}
+
+fn lerp_points(a: Point2, b: Point2, t: f32) -> Point2 {
+  pt2(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn square(size: f32) -> Line2 {
+    vec![
+      pt2(0.0, 0.0),
+      pt2(size, 0.0),
+      pt2(size, size),
+      pt2(0.0, size),
+    ]
+  }
+
+  fn square_at(x: f32, y: f32, size: f32) -> Line2 {
+    square(size).iter().map(|p| pt2(p.x + x, p.y + y)).collect()
+  }
+
+  // holes wind the other way, so the signed areas add up to the covered area
+  fn total_area(rings: &[Line2]) -> f32 {
+    let sign = match rings.first() {
+      Some(ring) if is_clockwise(ring) => -1.0,
+      _ => 1.0,
+    };
+    rings.iter().map(|ring| sign * signed_area(ring)).sum()
+  }
+
+  fn length(lines: &[Line2]) -> f32 {
+    lines
+      .iter()
+      .map(|line| line.windows(2).map(|w| w[0].distance(w[1])).sum::<f32>())
+      .sum()
+  }
+
+  fn assert_close(actual: f32, expected: f32) {
+    assert!(
+      (actual - expected).abs() < 1e-2,
+      "expected {}, got {}",
+      expected,
+      actual
+    );
+  }
+
+  #[test]
+  fn overlapping_squares() {
+    let (a, b) = (square(2.0), square_at(1.0, 1.0, 2.0));
+    assert!(intersects(&a, &b));
+    assert_eq!(union(&a, &b).len(), 1);
+    assert_close(total_area(&union(&a, &b)), 7.0);
+    assert_close(total_area(&intersection(&a, &b)), 1.0);
+    assert_close(total_area(&difference(&a, &b)), 3.0);
+    assert_close(total_area(&difference(&b, &a)), 3.0);
+  }
+
+  #[test]
+  fn one_inside_the_other() {
+    let (outer, inner) = (square(4.0), square_at(1.0, 1.0, 2.0));
+    assert!(intersects(&outer, &inner));
+    assert!(intersects(&inner, &outer));
+    assert_close(total_area(&union(&outer, &inner)), 16.0);
+    assert_close(total_area(&intersection(&outer, &inner)), 4.0);
+    let hole = difference(&outer, &inner);
+    assert_eq!(hole.len(), 2);
+    assert_ne!(is_clockwise(&hole[0]), is_clockwise(&hole[1]));
+    assert_close(total_area(&hole), 12.0);
+    assert!(difference(&inner, &outer).is_empty());
+  }
+
+  #[test]
+  fn disjoint_polygons() {
+    let (a, b) = (square(1.0), square_at(3.0, 3.0, 1.0));
+    assert!(!intersects(&a, &b));
+    assert_eq!(union(&a, &b).len(), 2);
+    assert_close(total_area(&union(&a, &b)), 2.0);
+    assert!(intersection(&a, &b).is_empty());
+    assert_eq!(difference(&a, &b), vec![a.clone()]);
+  }
+
+  #[test]
+  fn shared_edge() {
+    let (a, b) = (square(1.0), square_at(1.0, 0.0, 1.0));
+    assert!(!intersects(&a, &b));
+    assert_close(total_area(&union(&a, &b)), 2.0);
+    assert_close(total_area(&intersection(&a, &b)), 0.0);
+    assert_close(total_area(&difference(&a, &b)), 1.0);
+  }
+
+  #[test]
+  fn shared_vertex() {
+    let (a, b) = (square(1.0), square_at(1.0, 1.0, 1.0));
+    assert!(!intersects(&a, &b));
+    assert_close(total_area(&union(&a, &b)), 2.0);
+    assert_close(total_area(&intersection(&a, &b)), 0.0);
+    assert_close(total_area(&difference(&a, &b)), 1.0);
+  }
+
+  #[test]
+  fn clip_line_through_square() {
+    let line = vec![pt2(-1.0, 1.0), pt2(3.0, 1.0)];
+    let inside = clip_inside(&line, &square(2.0));
+    assert_eq!(inside.len(), 1);
+    assert_close(inside[0][0].x, 0.0);
+    assert_close(inside[0][inside[0].len() - 1].x, 2.0);
+    let outside = clip_outside(&line, &square(2.0));
+    assert_eq!(outside.len(), 2);
+    assert_close(length(&outside), 2.0);
+  }
+
+  #[test]
+  fn clip_line_inside_or_away_from_square() {
+    let inside = vec![pt2(0.5, 1.0), pt2(1.5, 1.0)];
+    assert_close(length(&clip_inside(&inside, &square(2.0))), 1.0);
+    assert!(clip_outside(&inside, &square(2.0)).is_empty());
+    let away = vec![pt2(5.0, 5.0), pt2(6.0, 5.0)];
+    assert!(clip_inside(&away, &square(2.0)).is_empty());
+    assert_close(length(&clip_outside(&away, &square(2.0))), 1.0);
+  }
+
+  #[test]
+  fn clip_line_through_hole() {
+    let rings = difference(&square(4.0), &square_at(1.0, 1.0, 2.0));
+    let line = vec![pt2(-1.0, 2.0), pt2(5.0, 2.0)];
+    let inside = clip_inside_rings(&line, &rings);
+    assert_eq!(inside.len(), 2);
+    assert_close(length(&inside), 2.0);
+  }
+
+  #[test]
+  fn clip_outside_every_polygon() {
+    let line = vec![pt2(-1.0, 0.5), pt2(6.0, 0.5)];
+    let squares = vec![square(1.0), square_at(3.0, 0.0, 1.0)];
+    let outside = clip_outside_all(&line, &squares);
+    assert_eq!(outside.len(), 3);
+    assert_close(length(&outside), 5.0);
+  }
+
+  #[test]
+  fn offset_inward_shrinks() {
+    let rings = offset(&square(2.0), -0.5);
+    assert_eq!(rings.len(), 1);
+    assert!((area(&rings[0]) - 1.0).abs() < 1e-3);
+  }
+
+  #[test]
+  fn offset_past_inradius_is_empty() {
+    for distance in &[-1.5, -7.0, -10.0, -50.0] {
+      assert!(
+        offset(&square(2.0), *distance).is_empty(),
+        "offset by {} should shrink the square away",
+        distance
+      );
+    }
+  }
+}
@@ -0,0 +1,447 @@
+// Open and closed polylines: resampling, simplification, smoothing and offsets.
+//
+// Every function that cares takes a `closed` flag.
+// Closed lines may or may not repeat their first point at the end (same as util::polygon),
+// and closed results always repeat it, so they can go straight into draw.polyline().
+//
+// Simplification
+// - Ramer-Douglas-Peucker keeps the points that stick out more than `epsilon` from a straight line
+//   https://en.wikipedia.org/wiki/Ramer%E2%80%93Douglas%E2%80%93Peucker_algorithm
+// - Visvalingam-Whyatt removes the point making the smallest triangle until every triangle is bigger than `min_area`,
+//   which tends to look more natural on wiggly lines
+//   https://bost.ocks.org/mike/simplify/
+//
+// Smoothing
+// - Chaikin corner cutting, shrinks corners a bit every iteration
+// - Catmull-Rom goes through every point. Uses the centripetal variant, which can't make loops or cusps
+//   https://en.wikipedia.org/wiki/Centripetal_Catmull%E2%80%93Rom_spline
+// - cubic B-spline is smoother than Catmull-Rom but only passes near the points (and through the ends of open lines)
+use nannou::prelude::*;
+
+use super::polygon::{close_ring, distance_to_segment, open_ring};
+use super::Line2;
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Angle of the segment a->b in radians, in (-PI, PI].
+/// Works for vertical segments and keeps the quadrant, unlike atan(dy / dx)
+pub fn orientation(a: Point2, b: Point2) -> f32 {
+  (b.y - a.y).atan2(b.x - a.x)
+}
+
+// the points of a closed line as an open ring, or an open line as is
+fn points(line: &Line2, closed: bool) -> Line2 {
+  if closed {
+    open_ring(line)
+  } else {
+    line.clone()
+  }
+}
+
+// walkable version of the line, with the closing segment for closed lines
+fn segments(line: &Line2, closed: bool) -> Line2 {
+  if closed {
+    close_ring(line)
+  } else {
+    line.clone()
+  }
+}
+
+pub fn length(line: &Line2, closed: bool) -> f32 {
+  segments(line, closed)
+    .windows(2)
+    .map(|w| w[0].distance(w[1]))
+    .sum()
+}
+
+/// The point `distance` along the line, clamped to the ends
+pub fn point_at(line: &Line2, distance: f32, closed: bool) -> Point2 {
+  let path = segments(line, closed);
+  let mut travelled = 0.0;
+  for w in path.windows(2) {
+    let d = w[0].distance(w[1]);
+    if travelled + d >= distance && d > 0.0 {
+      return w[0] + (w[1] - w[0]) * ((distance - travelled) / d).max(0.0);
+    }
+    travelled += d;
+  }
+  path.last().cloned().unwrap_or(pt2(0.0, 0.0))
+}
+
+/// `n` points evenly spaced by arc length.
+/// Open lines keep both end points, closed lines get `n` distinct points plus the closing point.
+pub fn resample_count(line: &Line2, n: usize, closed: bool) -> Line2 {
+  let path = segments(line, closed);
+  if path.len() < 2 || n < 2 {
+    return path;
+  }
+  let total = length(line, closed);
+  let spacing = total / if closed { n } else { n - 1 } as f32;
+  let mut resampled = Vec::with_capacity(n + 1);
+  let mut targets = (0..n).map(|i| i as f32 * spacing).peekable();
+  let mut travelled = 0.0;
+  for w in path.windows(2) {
+    let d = w[0].distance(w[1]);
+    while let Some(target) = targets.peek().cloned() {
+      if target > travelled + d {
+        break;
+      }
+      let t = if d > 0.0 {
+        (target - travelled) / d
+      } else {
+        0.0
+      };
+      resampled.push(w[0] + (w[1] - w[0]) * t);
+      targets.next();
+    }
+    travelled += d;
+  }
+  // float error can leave the last target just past the end
+  if targets.next().is_some() {
+    resampled.push(path[path.len() - 1]);
+  }
+  if closed {
+    resampled.push(resampled[0]);
+  }
+  resampled
+}
+
+/// Points evenly spaced by arc length, roughly `spacing` apart.
+/// The spacing is adjusted slightly so it divides the length evenly and the ends aren't bunched up.
+pub fn resample(line: &Line2, spacing: f32, closed: bool) -> Line2 {
+  let n = (length(line, closed) / spacing.max(1e-6)).round() as usize;
+  resample_count(line, if closed { n.max(3) } else { n + 1 }, closed)
+}
+
+/// Ramer-Douglas-Peucker simplification
+pub fn simplify_rdp(line: &Line2, epsilon: f32, closed: bool) -> Line2 {
+  let ring = points(line, closed);
+  if ring.len() < 3 {
+    return segments(&ring, closed);
+  }
+  if !closed {
+    let mut keep = vec![false; ring.len()];
+    keep[0] = true;
+    keep[ring.len() - 1] = true;
+    rdp(&ring, 0, ring.len() - 1, epsilon, &mut keep);
+    return ring
+      .iter()
+      .zip(keep)
+      .filter(|(_, keep)| *keep)
+      .map(|(p, _)| *p)
+      .collect();
+  }
+  // a closed line has no end points, so split it at the point furthest from the first one
+  // and simplify the two halves
+  let furthest = (1..ring.len())
+    .max_by(|a, b| {
+      ring[0]
+        .distance(ring[*a])
+        .partial_cmp(&ring[0].distance(ring[*b]))
+        .unwrap_or(Ordering::Equal)
+    })
+    .unwrap();
+  let mut first_half = simplify_rdp(&ring[..=furthest].to_vec(), epsilon, false);
+  let mut second_half = ring[furthest..].to_vec();
+  second_half.push(ring[0]);
+  let second_half = simplify_rdp(&second_half, epsilon, false);
+  first_half.pop();
+  first_half.extend(second_half);
+  first_half
+}
+
+fn rdp(line: &Line2, start: usize, end: usize, epsilon: f32, keep: &mut Vec<bool>) {
+  if end <= start + 1 {
+    return;
+  }
+  let (index, distance) = (start + 1..end)
+    .map(|i| (i, distance_to_segment(line[i], line[start], line[end])))
+    .fold(
+      (start, -1.0),
+      |best, (i, d)| if d > best.1 { (i, d) } else { best },
+    );
+  if distance > epsilon {
+    keep[index] = true;
+    rdp(line, start, index, epsilon, keep);
+    rdp(line, index, end, epsilon, keep);
+  }
+}
+
+// BinaryHeap is a max heap and f32 isn't Ord, so this orders by smallest area first
+#[derive(Debug, PartialEq)]
+struct Candidate {
+  area: f32,
+  index: usize,
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for Candidate {
+  fn cmp(&self, other: &Self) -> Ordering {
+    other
+      .area
+      .partial_cmp(&self.area)
+      .unwrap_or(Ordering::Equal)
+  }
+}
+
+fn triangle_area(a: Point2, b: Point2, c: Point2) -> f32 {
+  ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)).abs() / 2.0
+}
+
+/// Visvalingam-Whyatt simplification.
+/// Points are removed smallest triangle first, until every remaining triangle has at least `min_area`
+pub fn simplify_visvalingam(line: &Line2, min_area: f32, closed: bool) -> Line2 {
+  let ring = points(line, closed);
+  let n = ring.len();
+  // a closed line needs at least a triangle, an open one needs its two ends
+  let min_points = if closed { 3 } else { 2 };
+  if n <= min_points {
+    return segments(&ring, closed);
+  }
+  // doubly linked list over the indices, so removing a point is cheap
+  let mut previous: Vec<usize> = (0..n).map(|i| (i + n - 1) % n).collect();
+  let mut next: Vec<usize> = (0..n).map(|i| (i + 1) % n).collect();
+  let mut removed = vec![false; n];
+  let mut areas = vec![std::f32::MAX; n];
+  let is_end = |i: usize| !closed && (i == 0 || i == n - 1);
+
+  let mut heap = BinaryHeap::new();
+  for i in 0..n {
+    if !is_end(i) {
+      areas[i] = triangle_area(ring[previous[i]], ring[i], ring[next[i]]);
+      heap.push(Candidate {
+        area: areas[i],
+        index: i,
+      });
+    }
+  }
+  let mut remaining = n;
+  while let Some(Candidate { area, index }) = heap.pop() {
+    // stale entry, the point was already removed or its area changed since
+    if removed[index] || area != areas[index] {
+      continue;
+    }
+    if area >= min_area || remaining <= min_points {
+      break;
+    }
+    removed[index] = true;
+    remaining -= 1;
+    let (p, q) = (previous[index], next[index]);
+    next[p] = q;
+    previous[q] = p;
+    for &i in &[p, q] {
+      if !is_end(i) {
+        // a neighbor's area can't be smaller than the point just removed,
+        // otherwise points would be removed out of order
+        areas[i] = triangle_area(ring[previous[i]], ring[i], ring[next[i]]).max(area);
+        heap.push(Candidate {
+          area: areas[i],
+          index: i,
+        });
+      }
+    }
+  }
+  let simplified: Line2 = ring
+    .iter()
+    .zip(removed)
+    .filter(|(_, removed)| !*removed)
+    .map(|(p, _)| *p)
+    .collect();
+  segments(&simplified, closed)
+}
+
+/// Chaikin's corner cutting.
+/// Every iteration replaces each segment with points 1/4 and 3/4 along it.
+/// Open lines keep their end points
+pub fn chaikin(line: &Line2, iterations: usize, closed: bool) -> Line2 {
+  let mut ring = points(line, closed);
+  for _ in 0..iterations {
+    if ring.len() < 3 {
+      break;
+    }
+    let path = segments(&ring, closed);
+    let mut cut = Vec::with_capacity(path.len() * 2);
+    if !closed {
+      cut.push(path[0]);
+    }
+    for w in path.windows(2) {
+      cut.push(w[0] * 0.75 + w[1] * 0.25);
+      cut.push(w[0] * 0.25 + w[1] * 0.75);
+    }
+    if !closed {
+      cut.push(path[path.len() - 1]);
+    }
+    ring = cut;
+  }
+  segments(&ring, closed)
+}
+
+// neighbors for the curves that need one point either side of each segment.
+// Open lines get extra points mirrored off the ends
+fn padded(line: &Line2, closed: bool) -> Line2 {
+  let ring = points(line, closed);
+  let n = ring.len();
+  if closed {
+    let mut padded = vec![ring[n - 1]];
+    padded.extend(ring.iter().cloned());
+    padded.push(ring[0]);
+    padded.push(ring[1 % n]);
+    padded
+  } else {
+    let mut padded = vec![ring[0] * 2.0 - ring[1]];
+    padded.extend(ring.iter().cloned());
+    padded.push(ring[n - 1] * 2.0 - ring[n - 2]);
+    padded
+  }
+}
+
+/// Centripetal Catmull-Rom spline through every point,
+/// with `samples` points per segment
+pub fn catmull_rom(line: &Line2, samples: usize, closed: bool) -> Line2 {
+  if points(line, closed).len() < 2 {
+    return segments(line, closed);
+  }
+  let padded = padded(line, closed);
+  let samples = samples.max(1);
+  let mut curve = vec![];
+  for w in padded.windows(4) {
+    for s in 0..samples {
+      curve.push(catmull_rom_point(w, s as f32 / samples as f32));
+    }
+  }
+  curve.push(padded[padded.len() - 2]);
+  curve
+}
+
+// Barry and Goldman's pyramid formulation, with alpha = 0.5 for the centripetal knots
+fn catmull_rom_point(p: &[Point2], t: f32) -> Point2 {
+  let knot = |a: Point2, b: Point2| a.distance(b).sqrt().max(1e-4);
+  let t0 = 0.0;
+  let t1 = t0 + knot(p[0], p[1]);
+  let t2 = t1 + knot(p[1], p[2]);
+  let t3 = t2 + knot(p[2], p[3]);
+  let t = t1 + (t2 - t1) * t;
+  let lerp = |a: Point2, b: Point2, ta: f32, tb: f32| {
+    a * ((tb - t) / (tb - ta)) + b * ((t - ta) / (tb - ta))
+  };
+  let a1 = lerp(p[0], p[1], t0, t1);
+  let a2 = lerp(p[1], p[2], t1, t2);
+  let a3 = lerp(p[2], p[3], t2, t3);
+  let b1 = lerp(a1, a2, t0, t2);
+  let b2 = lerp(a2, a3, t1, t3);
+  lerp(b1, b2, t1, t2)
+}
+
+/// Uniform cubic B-spline with the points as control points,
+/// with `samples` points per segment.
+/// Open lines start and end exactly on their end points
+pub fn b_spline(line: &Line2, samples: usize, closed: bool) -> Line2 {
+  let ring = points(line, closed);
+  let n = ring.len();
+  if n < 3 {
+    return segments(&ring, closed);
+  }
+  let controls = if closed {
+    let mut controls = ring.clone();
+    controls.extend(ring[..3].iter().cloned());
+    controls
+  } else {
+    // tripling the end points clamps the curve to them
+    let mut controls = vec![ring[0], ring[0]];
+    controls.extend(ring.iter().cloned());
+    controls.push(ring[n - 1]);
+    controls.push(ring[n - 1]);
+    controls
+  };
+  let samples = samples.max(1);
+  let mut curve = vec![];
+  for w in controls.windows(4) {
+    for s in 0..samples {
+      let t = s as f32 / samples as f32;
+      let it = 1.0 - t;
+      let b0 = it * it * it / 6.0;
+      let b1 = (3.0 * t * t * t - 6.0 * t * t + 4.0) / 6.0;
+      let b2 = (-3.0 * t * t * t + 3.0 * t * t + 3.0 * t + 1.0) / 6.0;
+      let b3 = t * t * t / 6.0;
+      curve.push(w[0] * b0 + w[1] * b1 + w[2] * b2 + w[3] * b3);
+    }
+  }
+  if closed {
+    curve.push(curve[0]);
+  } else {
+    curve.push(ring[n - 1]);
+  }
+  curve
+}
+
+/// Unit normals at every point, pointing to the left of the direction of travel.
+/// Corners get the average of the two segments' normals
+pub fn normals(line: &Line2, closed: bool) -> Vec<Vector2> {
+  let ring = points(line, closed);
+  let mut normals = vertex_normals(&ring, closed);
+  if closed && !normals.is_empty() {
+    normals.push(normals[0]);
+  }
+  normals
+}
+
+fn vertex_normals(ring: &Line2, closed: bool) -> Vec<Vector2> {
+  let n = ring.len();
+  if n < 2 {
+    return vec![vec2(0.0, 0.0); n];
+  }
+  (0..n)
+    .map(|i| {
+      let angle = if !closed && i == 0 {
+        orientation(ring[0], ring[1])
+      } else if !closed && i == n - 1 {
+        orientation(ring[n - 2], ring[n - 1])
+      } else {
+        let incoming = orientation(ring[(i + n - 1) % n], ring[i]);
+        let outgoing = orientation(ring[i], ring[(i + 1) % n]);
+        // average the two angles the short way round
+        let mut turn = outgoing - incoming;
+        if turn > PI {
+          turn -= 2.0 * PI;
+        } else if turn < -PI {
+          turn += 2.0 * PI;
+        }
+        incoming + turn / 2.0
+      } + PI / 2.0;
+      vec2(angle.cos(), angle.sin())
+    })
+    .collect()
+}
+
+/// Offsets every point `distance` along its normal, positive to the left.
+/// Corners are mitered so the offset line stays parallel to the original,
+/// up to `miter_limit` times the distance so sharp corners don't shoot off
+pub fn offset(line: &Line2, distance: f32, miter_limit: f32, closed: bool) -> Line2 {
+  let ring = points(line, closed);
+  let n = ring.len();
+  let normals = vertex_normals(&ring, closed);
+  let offset: Line2 = (0..n)
+    .map(|i| {
+      // the miter gets longer as the corner gets sharper: distance / cos(half the turn),
+      // and cos(half the turn) is the dot product of the corner normal with either segment's normal
+      let (a, b) = if closed || i + 1 < n {
+        (ring[i], ring[(i + 1) % n])
+      } else {
+        (ring[i - 1], ring[i])
+      };
+      let angle = orientation(a, b) + PI / 2.0;
+      let cos = normals[i].x * angle.cos() + normals[i].y * angle.sin();
+      let scale = (1.0 / cos.max(1e-6)).min(miter_limit.max(1.0));
+      ring[i] + normals[i] * distance * scale
+    })
+    .collect();
+  segments(&offset, closed)
+}
@@ -98,6 +98,11 @@ impl Prism {
     vec![self.vertex, self.d_(), self.e(), self.f()]
   }
 
+  // the visible faces as closed shapes, in drawing order: left, right, top
+  pub fn faces(&self) -> Vec<Line2> {
+    vec![self.left(), self.right(), self.top()]
+  }
+
   pub fn w(mut self, width: f32) -> Self {
     self.width = width;
     self
@@ -0,0 +1,435 @@
+// Records how an image was made inside the PNG itself, so the settings travel with the image
+// when it gets uploaded or moved away from its .txt model dump.
+//
+// Written as PNG text chunks (tEXt, or iTXt when a value isn't plain ASCII):
+// - sketch: the example name
+// - params: every argument the sketch read, with the value it used (defaults included),
+//   one `key=value` per line, see args::resolved
+// - seed: the util::rng seed, also in the params as "rng-seed"
+// - palette: pulled out of the params, with its colors
+// - version and commit: the crate version and `git describe`-ish commit (with "-dirty" for local changes)
+// - model: the Debug dump of the model, same as the .txt file
+//
+// Optionally also an XMP sidecar (`{image}.xmp`) for tools that read that instead.
+//
+// `cargo run --release --example reproduce -- path/to/image.png` reads the chunks back and re-runs the sketch.
+// Every parameter comes back the same (including random defaults like `args.get("n", random_range(10, 20))`),
+// and so does everything the util modules generate, since they draw from the seeded util::rng.
+// Randomness a sketch gets straight from nannou's random_* is not seeded and still comes out different,
+// so a sketch is only reproduced exactly if it imports util::rng's versions instead.
+use nannou::prelude::*;
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::args;
+use super::color::PALETTES;
+use super::rng;
+
+const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+// the IEND chunk every PNG ends with: zero length, type, CRC
+const PNG_END: [u8; 12] = [0, 0, 0, 0, 73, 69, 78, 68, 174, 66, 96, 130];
+// how long to wait for nannou to write a captured frame before giving up on it
+const EMBED_TIMEOUT: Duration = Duration::from_secs(60);
+
+// the commit can't change under a running binary, so it's only looked up once per run
+static COMMIT: Mutex<Option<Option<String>>> = Mutex::new(None);
+// frames waiting to be embedded, see `embed_when_written`
+static EMBED_QUEUE: Mutex<Option<Sender<(Provenance, PathBuf, Instant)>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+  pub sketch: String,
+  pub params: BTreeMap<String, String>,
+  pub seed: Option<String>,
+  pub palette: Option<String>,
+  pub version: String,
+  pub commit: Option<String>,
+  pub model: Option<String>,
+}
+
+impl Provenance {
+  /// Everything known about the current run
+  pub fn new(app: &App) -> Self {
+    // before the params, so the seed is recorded even if nothing has used the rng yet
+    let seed = rng::seed();
+    let params = args::resolved();
+    Provenance {
+      sketch: app.exe_name().unwrap(),
+      seed: Some(seed.to_string()),
+      palette: params.get("palette").map(|name| describe_palette(name)),
+      params,
+      version: String::from(env!("CARGO_PKG_VERSION")),
+      commit: git_commit(app),
+      model: None,
+    }
+  }
+
+  pub fn model<T: Debug>(mut self, model: &T) -> Self {
+    self.model = Some(format!("{:?}", model));
+    self
+  }
+
+  /// The chunks as keyword/text pairs
+  pub fn chunks(&self) -> Vec<(&str, String)> {
+    let mut chunks = vec![
+      ("Software", format!("generative-art-rs {}", self.version)),
+      ("sketch", self.sketch.clone()),
+      ("params", params_to_string(&self.params)),
+      ("version", self.version.clone()),
+    ];
+    if let Some(ref seed) = self.seed {
+      chunks.push(("seed", seed.clone()));
+    }
+    if let Some(ref palette) = self.palette {
+      chunks.push(("palette", palette.clone()));
+    }
+    if let Some(ref commit) = self.commit {
+      chunks.push(("commit", commit.clone()));
+    }
+    if let Some(ref model) = self.model {
+      chunks.push(("model", model.clone()));
+    }
+    chunks
+  }
+
+  /// Adds the text chunks to a PNG that's already on disk
+  pub fn embed(&self, path: &Path) -> std::io::Result<()> {
+    let bytes = std::fs::read(path)?;
+    let chunks = read_chunks(&bytes)
+      .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "not a complete PNG"))?;
+    let mut out = PNG_SIGNATURE.to_vec();
+    for (kind, data) in chunks {
+      // drop text from an earlier embed so keys don't pile up
+      if (kind == *b"tEXt" || kind == *b"iTXt") && is_ours(&data) {
+        continue;
+      }
+      write_chunk(&mut out, &kind, &data);
+      // text goes right after the header, so it can be read without loading the image data
+      if kind == *b"IHDR" {
+        for (keyword, text) in self.chunks() {
+          if text.is_ascii() {
+            write_chunk(&mut out, b"tEXt", &text_chunk(keyword, &text));
+          } else {
+            write_chunk(&mut out, b"iTXt", &itxt_chunk(keyword, &text));
+          }
+        }
+      }
+    }
+    std::fs::write(path, out)
+  }
+
+  /// nannou writes captured frames in the background, so this queues the PNG for a background thread
+  /// (one per run) that embeds it once it's complete
+  pub fn embed_when_written(self, path: PathBuf) {
+    let mut queue = EMBED_QUEUE.lock().unwrap();
+    let sender = queue.get_or_insert_with(|| {
+      let (sender, receiver) = channel();
+      std::thread::spawn(move || embed_worker(receiver));
+      sender
+    });
+    if let Err(err) = sender.send((self, path, Instant::now())) {
+      println!("{}", err);
+    }
+  }
+
+  /// Writes an XMP sidecar next to the image, `{image}.xmp`
+  pub fn write_xmp(&self, path: &Path) {
+    let mut fields: Vec<String> = self
+      .chunks()
+      .iter()
+      .filter(|(keyword, _)| *keyword != "Software")
+      .map(|(keyword, text)| format!("   <ga:{}>{}</ga:{}>", keyword, escape_xml(text), keyword))
+      .collect();
+    fields.insert(
+      0,
+      format!(
+        "   <xmp:CreatorTool>generative-art-rs {}</xmp:CreatorTool>",
+        self.version
+      ),
+    );
+    let xmp = format!(
+      r#"<?xpacket begin="" id="W5M0MpCehiHzreSzNTczkc9d"?>
+<x:xmpmeta xmlns:x="adobe:ns:meta/">
+ <rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#">
+  <rdf:Description rdf:about=""
+    xmlns:xmp="http://ns.adobe.com/xap/1.0/"
+    xmlns:ga="https://github.com/ericyd/generative-art/ns/1.0/">
+{}
+  </rdf:Description>
+ </rdf:RDF>
+</x:xmpmeta>
+<?xpacket end="r"?>
+"#,
+      fields.join("\n")
+    );
+    let sidecar = PathBuf::from(format!("{}.xmp", path.display()));
+    if let Err(err) = std::fs::write(sidecar, xmp) {
+      println!("{}", err);
+    }
+  }
+
+  /// Reads the chunks back out of a PNG written with `embed`
+  pub fn read(path: &Path) -> Option<Provenance> {
+    let bytes = std::fs::read(path).ok()?;
+    let mut text = BTreeMap::new();
+    for (kind, data) in read_chunks(&bytes)? {
+      let parsed = match &kind {
+        b"tEXt" => parse_text_chunk(&data),
+        b"iTXt" => parse_itxt_chunk(&data),
+        _ => None,
+      };
+      if let Some((keyword, value)) = parsed {
+        text.insert(keyword, value);
+      }
+    }
+    Some(Provenance {
+      sketch: text.get("sketch")?.clone(),
+      params: params_from_string(text.get("params").map_or("", |p| p.as_str())),
+      seed: text.get("seed").cloned(),
+      palette: text.get("palette").cloned(),
+      version: text.get("version").cloned().unwrap_or_default(),
+      commit: text.get("commit").cloned(),
+      model: text.get("model").cloned(),
+    })
+  }
+
+  /// The params as command line arguments, `--key value`
+  pub fn args(&self) -> Vec<String> {
+    self
+      .params
+      .iter()
+      .flat_map(|(key, value)| vec![format!("--{}", key), value.clone()])
+      .collect()
+  }
+}
+
+// "name: #aaaaaa, #bbbbbb, ..." for named palettes, so the colors survive even if the palette changes later
+fn describe_palette(name: &str) -> String {
+  match PALETTES.iter().find(|(title, _)| *title == name) {
+    Some((title, colors)) => format!("{}: {}", title, colors.join(", ")),
+    None => String::from(name),
+  }
+}
+
+// checks every pending frame a few times a second, frames that never show up
+// (e.g. because the sketch captured to a different path) are dropped after EMBED_TIMEOUT
+fn embed_worker(receiver: Receiver<(Provenance, PathBuf, Instant)>) {
+  let mut pending = vec![];
+  loop {
+    if pending.is_empty() {
+      // nothing to check, so sleep until there is
+      match receiver.recv() {
+        Ok(job) => merge(&mut pending, job),
+        Err(_) => return,
+      }
+    }
+    for job in receiver.try_iter() {
+      merge(&mut pending, job);
+    }
+    pending.retain(|(provenance, path, queued)| {
+      if is_complete(path) {
+        if let Err(err) = provenance.embed(path) {
+          println!("{}", err);
+        }
+        return false;
+      }
+      queued.elapsed() < EMBED_TIMEOUT
+    });
+    if !pending.is_empty() {
+      std::thread::sleep(Duration::from_millis(100));
+    }
+  }
+}
+
+// a frame queued twice (e.g. by capture and then capture_model) is only embedded once,
+// with the newest provenance, keeping the model if either one had it
+fn merge(pending: &mut Vec<(Provenance, PathBuf, Instant)>, job: (Provenance, PathBuf, Instant)) {
+  let (mut provenance, path, queued) = job;
+  match pending
+    .iter_mut()
+    .find(|(_, pending_path, _)| *pending_path == path)
+  {
+    Some(existing) => {
+      if provenance.model.is_none() {
+        provenance.model = existing.0.model.take();
+      }
+      existing.0 = provenance;
+    }
+    None => pending.push((provenance, path, queued)),
+  }
+}
+
+/// True once a PNG has been completely written (it ends with IEND), without reading the whole image
+pub fn is_complete(path: &Path) -> bool {
+  let mut file = match File::open(path) {
+    Ok(file) => file,
+    Err(_) => return false,
+  };
+  let mut end = [0; 12];
+  file.seek(SeekFrom::End(-12)).is_ok() && file.read_exact(&mut end).is_ok() && end == PNG_END
+}
+
+fn git_commit(app: &App) -> Option<String> {
+  COMMIT
+    .lock()
+    .unwrap()
+    .get_or_insert_with(|| lookup_commit(app))
+    .clone()
+}
+
+fn lookup_commit(app: &App) -> Option<String> {
+  let dir = app.project_path().ok()?;
+  let git = |args: &[&str]| {
+    Command::new("git")
+      .args(args)
+      .current_dir(&dir)
+      .output()
+      .ok()
+      .filter(|output| output.status.success())
+      .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+  };
+  let commit = git(&["rev-parse", "HEAD"])?;
+  let dirty = git(&["status", "--porcelain"]).map_or(false, |status| !status.is_empty());
+  Some(if dirty {
+    format!("{}-dirty", commit)
+  } else {
+    commit
+  })
+}
+
+fn params_to_string(params: &BTreeMap<String, String>) -> String {
+  params
+    .iter()
+    .map(|(key, value)| format!("{}={}", key, value))
+    .collect::<Vec<String>>()
+    .join("\n")
+}
+
+fn params_from_string(params: &str) -> BTreeMap<String, String> {
+  params
+    .lines()
+    .filter_map(|line| {
+      let mut parts = line.splitn(2, '=');
+      Some((parts.next()?.to_string(), parts.next()?.to_string()))
+    })
+    .collect()
+}
+
+fn escape_xml(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+}
+
+// text chunks written by `embed`, recognized by keyword
+fn is_ours(data: &[u8]) -> bool {
+  let keyword = data.split(|b| *b == 0).next().unwrap_or(&[]);
+  [
+    "Software", "sketch", "params", "version", "seed", "palette", "commit", "model",
+  ]
+  .iter()
+  .any(|k| k.as_bytes() == keyword)
+}
+
+// splits a PNG into (type, data) chunks.
+// None if it isn't a PNG or doesn't end with IEND yet, e.g. because it's still being written
+fn read_chunks(bytes: &[u8]) -> Option<Vec<([u8; 4], Vec<u8>)>> {
+  if bytes.len() < 8 || bytes[..8] != PNG_SIGNATURE {
+    return None;
+  }
+  let mut chunks = vec![];
+  let mut at = 8;
+  while at + 12 <= bytes.len() {
+    let length =
+      u32::from_be_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]) as usize;
+    let end = at + 12 + length;
+    if end > bytes.len() {
+      return None;
+    }
+    let kind = [bytes[at + 4], bytes[at + 5], bytes[at + 6], bytes[at + 7]];
+    chunks.push((kind, bytes[at + 8..at + 8 + length].to_vec()));
+    at = end;
+    if kind == *b"IEND" {
+      return Some(chunks);
+    }
+  }
+  None
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+  out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+  let start = out.len();
+  out.extend_from_slice(kind);
+  out.extend_from_slice(data);
+  let crc = crc32(&out[start..]);
+  out.extend_from_slice(&crc.to_be_bytes());
+}
+
+// keyword, null separator, Latin-1 text
+fn text_chunk(keyword: &str, text: &str) -> Vec<u8> {
+  let mut data = keyword.as_bytes().to_vec();
+  data.push(0);
+  data.extend_from_slice(text.as_bytes());
+  data
+}
+
+// keyword, null, uncompressed flag and method, empty language tag and translated keyword, UTF-8 text
+fn itxt_chunk(keyword: &str, text: &str) -> Vec<u8> {
+  let mut data = keyword.as_bytes().to_vec();
+  data.extend_from_slice(&[0, 0, 0, 0, 0]);
+  data.extend_from_slice(text.as_bytes());
+  data
+}
+
+fn parse_text_chunk(data: &[u8]) -> Option<(String, String)> {
+  let split = data.iter().position(|b| *b == 0)?;
+  Some((
+    String::from_utf8_lossy(&data[..split]).to_string(),
+    // Latin-1 maps straight onto the first 256 chars
+    data[split + 1..].iter().map(|b| *b as char).collect(),
+  ))
+}
+
+fn parse_itxt_chunk(data: &[u8]) -> Option<(String, String)> {
+  let split = data.iter().position(|b| *b == 0)?;
+  let keyword = String::from_utf8_lossy(&data[..split]).to_string();
+  // compressed text isn't something `embed` writes, so skip it
+  if *data.get(split + 1)? != 0 {
+    return None;
+  }
+  // after the flags: language tag and translated keyword, both null terminated
+  let rest = &data[split + 3..];
+  let language_end = rest.iter().position(|b| *b == 0)?;
+  let rest = &rest[language_end + 1..];
+  let translated_end = rest.iter().position(|b| *b == 0)?;
+  Some((
+    keyword,
+    String::from_utf8_lossy(&rest[translated_end + 1..]).to_string(),
+  ))
+}
+
+// the CRC every PNG chunk ends with (ISO 3309, same as zlib)
+fn crc32(bytes: &[u8]) -> u32 {
+  let mut crc = 0xffff_ffffu32;
+  for byte in bytes {
+    crc ^= *byte as u32;
+    for _ in 0..8 {
+      crc = if crc & 1 != 0 {
+        (crc >> 1) ^ 0xedb8_8320
+      } else {
+        crc >> 1
+      };
+    }
+  }
+  !crc
+}
@@ -0,0 +1,389 @@
+// An offscreen RGBA image with f32 channels, for effects that are easier to paint pixel by pixel
+// than to build out of draw calls.
+// Colors are straight (not premultiplied) [r, g, b, a] in [0, 1].
+// Same layout as DensityMap: row-major, with j = 0 at the *top*.
+//
+// Rasters double as layers: composite one onto another with a blend mode, opacity and mask,
+// or stack named ones with util::layers::Layers.
+// The filters (blur, grain, vignette, displace) change the raster in place.
+extern crate image;
+
+use nannou::noise::NoiseFn;
+use nannou::prelude::*;
+
+use self::image::RgbaImage;
+use super::texture::random_from;
+use super::DensityMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlendMode {
+  Normal,
+  Multiply,
+  Screen,
+  Overlay,
+  SoftLight,
+  Darken,
+  Lighten,
+  Difference,
+}
+
+pub const BLEND_MODES: [&str; 8] = [
+  "normal",
+  "multiply",
+  "screen",
+  "overlay",
+  "soft-light",
+  "darken",
+  "lighten",
+  "difference",
+];
+
+/// Looks up a blend mode by name, see BLEND_MODES
+pub fn blend_mode(name: &str) -> Option<BlendMode> {
+  match name {
+    "normal" => Some(BlendMode::Normal),
+    "multiply" => Some(BlendMode::Multiply),
+    "screen" => Some(BlendMode::Screen),
+    "overlay" => Some(BlendMode::Overlay),
+    "soft-light" => Some(BlendMode::SoftLight),
+    "darken" => Some(BlendMode::Darken),
+    "lighten" => Some(BlendMode::Lighten),
+    "difference" => Some(BlendMode::Difference),
+    _ => None,
+  }
+}
+
+#[derive(Clone)]
+pub struct Raster {
+  pub width: usize,
+  pub height: usize,
+  pixels: Vec<[f32; 4]>,
+}
+
+impl Raster {
+  /// A transparent raster
+  pub fn new(width: usize, height: usize) -> Self {
+    Self::filled(width, height, [0.0, 0.0, 0.0, 0.0])
+  }
+
+  pub fn filled(width: usize, height: usize, color: [f32; 4]) -> Self {
+    Raster {
+      width,
+      height,
+      pixels: vec![color; width * height],
+    }
+  }
+
+  /// Builds a raster from a function of (i, j) pixel coordinates
+  pub fn from_fn(width: usize, height: usize, f: impl Fn(usize, usize) -> [f32; 4]) -> Self {
+    let mut pixels = Vec::with_capacity(width * height);
+    for j in 0..height {
+      for i in 0..width {
+        pixels.push(f(i, j));
+      }
+    }
+    Raster {
+      width,
+      height,
+      pixels,
+    }
+  }
+
+  pub fn get(&self, i: usize, j: usize) -> [f32; 4] {
+    self.pixels[j * self.width + i]
+  }
+
+  pub fn set(&mut self, i: usize, j: usize, color: [f32; 4]) {
+    self.pixels[j * self.width + i] = color;
+  }
+
+  pub fn pixels(&self) -> &[[f32; 4]] {
+    &self.pixels
+  }
+
+  /// Paints `color` over the pixel with normal "source over" alpha blending
+  pub fn blend(&mut self, i: usize, j: usize, color: [f32; 4]) {
+    let index = j * self.width + i;
+    self.pixels[index] = over(color, self.pixels[index]);
+  }
+
+  /// Blends `top` onto this raster. `top` is stretched to fit if it's a different size.
+  /// Follows the W3C compositing spec: the blend mode mixes the colors where both layers are opaque,
+  /// then the result is painted over with normal alpha blending
+  pub fn composite(&mut self, top: &Raster, mode: BlendMode, opacity: f32) {
+    self.composite_masked(top, mode, opacity, None);
+  }
+
+  /// Same as composite, but `top` only shows where the mask is 1 (also stretched to fit).
+  /// Note that a DensityMap loaded from an image is 1 where the image is *black*
+  pub fn composite_masked(
+    &mut self,
+    top: &Raster,
+    mode: BlendMode,
+    opacity: f32,
+    mask: Option<&DensityMap>,
+  ) {
+    for j in 0..self.height {
+      for i in 0..self.width {
+        let [sr, sg, sb, sa] = top.get(
+          (i * top.width / self.width).min(top.width - 1),
+          (j * top.height / self.height).min(top.height - 1),
+        );
+        let shown = match mask {
+          Some(mask) => mask.at(
+            (i * mask.width / self.width).min(mask.width - 1),
+            (j * mask.height / self.height).min(mask.height - 1),
+          ),
+          None => 1.0,
+        };
+        if sa * shown <= 0.0 {
+          continue;
+        }
+        let backdrop = self.get(i, j);
+        let ba = backdrop[3];
+        let mix = |s: f32, b: f32| (1.0 - ba) * s + ba * blend_channel(mode, b, s);
+        let source = [
+          mix(sr, backdrop[0]),
+          mix(sg, backdrop[1]),
+          mix(sb, backdrop[2]),
+          sa * opacity * shown,
+        ];
+        self.set(i, j, over(source, backdrop));
+      }
+    }
+  }
+
+  /// The alpha channel as a mask, 1 where the raster is opaque
+  pub fn alpha_mask(&self) -> DensityMap {
+    DensityMap::from_fn(self.width, self.height, |i, j| self.get(i, j)[3])
+  }
+
+  /// Color at a fractional pixel position, interpolated between the 4 nearest pixels.
+  /// Positions past the edges repeat the edge pixels
+  pub fn sample(&self, x: f32, y: f32) -> [f32; 4] {
+    // pixel centers are at +0.5
+    let x = (x - 0.5).max(0.0).min(self.width as f32 - 1.0);
+    let y = (y - 0.5).max(0.0).min(self.height as f32 - 1.0);
+    let (i, j) = (x.floor() as usize, y.floor() as usize);
+    let (i1, j1) = ((i + 1).min(self.width - 1), (j + 1).min(self.height - 1));
+    let (fx, fy) = (x - i as f32, y - j as f32);
+    // interpolate premultiplied, so transparent pixels don't bleed their color in
+    let corners = [
+      (self.get(i, j), (1.0 - fx) * (1.0 - fy)),
+      (self.get(i1, j), fx * (1.0 - fy)),
+      (self.get(i, j1), (1.0 - fx) * fy),
+      (self.get(i1, j1), fx * fy),
+    ];
+    let mut sum = [0.0; 4];
+    for (color, weight) in corners.iter() {
+      let a = color[3] * weight;
+      sum[0] += color[0] * a;
+      sum[1] += color[1] * a;
+      sum[2] += color[2] * a;
+      sum[3] += a;
+    }
+    unpremultiply(sum)
+  }
+
+  /// Gaussian blur with standard deviation `sigma` in pixels
+  pub fn blur(&mut self, sigma: f32) {
+    if sigma <= 0.0 {
+      return;
+    }
+    let radius = (sigma * 3.0).ceil() as isize;
+    let kernel: Vec<f32> = (-radius..=radius)
+      .map(|x| (-(x * x) as f32 / (2.0 * sigma * sigma)).exp())
+      .collect();
+    let total: f32 = kernel.iter().sum();
+    let kernel: Vec<f32> = kernel.iter().map(|k| k / total).collect();
+
+    let premultiplied: Vec<[f32; 4]> = self.pixels.iter().map(|p| premultiply(*p)).collect();
+    let (w, h) = (self.width as isize, self.height as isize);
+    let pass = |source: &[[f32; 4]], horizontal: bool| -> Vec<[f32; 4]> {
+      let mut out = vec![[0.0; 4]; source.len()];
+      for j in 0..h {
+        for i in 0..w {
+          let mut sum = [0.0; 4];
+          for (k, weight) in kernel.iter().enumerate() {
+            let offset = k as isize - radius;
+            // repeat the edge pixels
+            let (si, sj) = if horizontal {
+              ((i + offset).max(0).min(w - 1), j)
+            } else {
+              (i, (j + offset).max(0).min(h - 1))
+            };
+            let p = source[(sj * w + si) as usize];
+            for (total, value) in sum.iter_mut().zip(p.iter()) {
+              *total += value * weight;
+            }
+          }
+          out[(j * w + i) as usize] = sum;
+        }
+      }
+      out
+    };
+    let blurred = pass(&pass(&premultiplied, true), false);
+    self.pixels = blurred.into_iter().map(unpremultiply).collect();
+  }
+
+  /// Film grain: brightens or darkens every pixel by up to `amount`, the same for all three channels
+  pub fn grain(&mut self, amount: f32, seed: u32) {
+    for (n, pixel) in self.pixels.iter_mut().enumerate() {
+      let offset = (random_from(seed, n) - 0.5) * 2.0 * amount;
+      for c in pixel.iter_mut().take(3) {
+        *c = (*c + offset).max(0.0).min(1.0);
+      }
+    }
+  }
+
+  /// Darkens towards the corners. `strength` is how dark the corners get,
+  /// `radius` is how far out the center stays untouched, as a fraction of the distance to the corners
+  pub fn vignette(&mut self, strength: f32, radius: f32) {
+    let (cx, cy) = (self.width as f32 / 2.0, self.height as f32 / 2.0);
+    let corner = (cx * cx + cy * cy).sqrt();
+    for j in 0..self.height {
+      for i in 0..self.width {
+        let (dx, dy) = (i as f32 + 0.5 - cx, j as f32 + 0.5 - cy);
+        let d = (dx * dx + dy * dy).sqrt() / corner;
+        let t = ((d - radius) / (1.0 - radius).max(1e-6)).max(0.0).min(1.0);
+        // smoothstep
+        let shade = 1.0 - strength * t * t * (3.0 - 2.0 * t);
+        let index = j * self.width + i;
+        for c in self.pixels[index].iter_mut().take(3) {
+          *c *= shade;
+        }
+      }
+    }
+  }
+
+  /// Moves every pixel by the offset `displacement(i, j)` returns, in pixels.
+  /// The offset says where to look up the color, so it's the inverse of where paint moves
+  pub fn displace_fn(&mut self, displacement: impl Fn(usize, usize) -> Vector2) {
+    let mut pixels = Vec::with_capacity(self.pixels.len());
+    for j in 0..self.height {
+      for i in 0..self.width {
+        let offset = displacement(i, j);
+        pixels.push(self.sample(i as f32 + 0.5 + offset.x, j as f32 + 0.5 + offset.y));
+      }
+    }
+    self.pixels = pixels;
+  }
+
+  /// Displacement map from a noise function: pixels move up to `amount` pixels,
+  /// with features about `scale` pixels across
+  pub fn displace(&mut self, noise: &impl NoiseFn<[f64; 3]>, amount: f32, scale: f32) {
+    let scale = scale.max(1.0) as f64;
+    self.displace_fn(|i, j| {
+      let (x, y) = (i as f64 / scale, j as f64 / scale);
+      // two far apart slices of the noise, so x and y move independently
+      vec2(
+        noise.get([x, y, 0.5]) as f32,
+        noise.get([x, y, 100.5]) as f32,
+      ) * amount
+    });
+  }
+
+  /// Maps a point in pixel space to the window.
+  /// The raster is stretched to fill `win`, so pick a window with the same aspect ratio.
+  pub fn pixel_to_window(&self, point: Point2, win: &Rect) -> Point2 {
+    pt2(
+      map_range(point.x, 0.0, self.width as f32, win.left(), win.right()),
+      map_range(point.y, 0.0, self.height as f32, win.top(), win.bottom()),
+    )
+  }
+
+  /// Maps a point in the window to pixel space
+  pub fn window_to_pixel(&self, point: Point2, win: &Rect) -> Point2 {
+    pt2(
+      map_range(point.x, win.left(), win.right(), 0.0, self.width as f32),
+      map_range(point.y, win.top(), win.bottom(), 0.0, self.height as f32),
+    )
+  }
+
+  /// Uploads the raster as a texture and draws it stretched over `win`
+  pub fn draw(&self, app: &App, draw: &Draw, win: &Rect) {
+    let image = image::DynamicImage::ImageRgba8(self.to_image());
+    let texture = wgpu::Texture::from_image(app, &image);
+    draw.texture(&texture).xy(win.xy()).wh(win.wh());
+  }
+
+  pub fn to_image(&self) -> RgbaImage {
+    let byte = |v: f32| (v.max(0.0).min(1.0) * 255.0).round() as u8;
+    RgbaImage::from_fn(self.width as u32, self.height as u32, |i, j| {
+      let [r, g, b, a] = self.get(i as usize, j as usize);
+      image::Rgba([byte(r), byte(g), byte(b), byte(a)])
+    })
+  }
+
+  pub fn save(&self, path: &std::path::Path) {
+    if let Err(err) = self.to_image().save(path) {
+      println!("{}", err);
+    }
+  }
+}
+
+// the blend functions from https://www.w3.org/TR/compositing-1/#blending
+// `b` is the backdrop (bottom) and `s` the source (top)
+fn blend_channel(mode: BlendMode, b: f32, s: f32) -> f32 {
+  match mode {
+    BlendMode::Normal => s,
+    BlendMode::Multiply => b * s,
+    BlendMode::Screen => b + s - b * s,
+    BlendMode::Overlay => {
+      if b <= 0.5 {
+        2.0 * b * s
+      } else {
+        1.0 - 2.0 * (1.0 - b) * (1.0 - s)
+      }
+    }
+    BlendMode::SoftLight => {
+      if s <= 0.5 {
+        b - (1.0 - 2.0 * s) * b * (1.0 - b)
+      } else {
+        let d = if b <= 0.25 {
+          ((16.0 * b - 12.0) * b + 4.0) * b
+        } else {
+          b.sqrt()
+        };
+        b + (2.0 * s - 1.0) * (d - b)
+      }
+    }
+    BlendMode::Darken => b.min(s),
+    BlendMode::Lighten => b.max(s),
+    BlendMode::Difference => (b - s).abs(),
+  }
+}
+
+fn premultiply([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+  [r * a, g * a, b * a, a]
+}
+
+fn unpremultiply([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+  if a <= 1e-6 {
+    return [0.0, 0.0, 0.0, 0.0];
+  }
+  [r / a, g / a, b / a, a]
+}
+
+// Porter-Duff "source over" with straight alpha
+pub fn over(source: [f32; 4], destination: [f32; 4]) -> [f32; 4] {
+  let [sr, sg, sb, sa] = source;
+  let [dr, dg, db, da] = destination;
+  let a = sa + da * (1.0 - sa);
+  if a <= 0.0 {
+    return [0.0, 0.0, 0.0, 0.0];
+  }
+  let mix = |s: f32, d: f32| (s * sa + d * da * (1.0 - sa)) / a;
+  [mix(sr, dr), mix(sg, dg), mix(sb, db), a]
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every pixel
+impl std::fmt::Debug for Raster {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Raster")
+      .field("width", &self.width)
+      .field("height", &self.height)
+      .finish()
+  }
+}
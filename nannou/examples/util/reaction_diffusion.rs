@@ -0,0 +1,665 @@
+// A reusable Gray-Scott reaction-diffusion simulation
+// http://www.karlsims.com/rd.html
+//
+// This is what the xp_diffusion experiments were working towards.
+// The big speedups compared to those:
+//   - each chemical lives in a flat Vec<f32> instead of Vec<Vec<Pixel>>
+//   - the fields are double-buffered, so a step writes into `next` and then swaps pointers instead of copying
+//   - rows are computed in parallel with rayon
+//   - the simulation is completely decoupled from drawing, so you can run thousands of steps
+//     and only render once at the end (see `Simulation::steps`)
+//
+// Besides Gray-Scott, `ReactionDiffusion` runs a few other classic two-chemical systems
+// on the same kind of grid: FitzHugh-Nagumo, Brusselator and Schnakenberg.
+//
+// Grid coordinates are (i, j) with i = column, j = row, and j = 0 is the top row, same as DensityMap.
+extern crate rayon;
+use nannou::prelude::*;
+
+use self::rayon::prelude::*;
+
+use super::circle::Circle;
+use super::contours::field_contours;
+use super::polygon;
+use super::rng::random_range;
+use super::simulation::Simulation;
+use super::Line2;
+
+// Note the SIMS kernel is a quarter of the scale of the other two,
+// so they need diffusion rates about 4x smaller to give the same patterns.
+pub type Kernel = [[f32; 3]; 3];
+
+/// The kernel from Karl Sims' tutorial, and the one used in the xp_diffusion experiments
+pub const KERNEL_SIMS: Kernel = [[0.05, 0.2, 0.05], [0.2, -1.0, 0.2], [0.05, 0.2, 0.05]];
+/// Classic 5-point stencil. Only uses direct neighbors, so patterns tend to line up with the grid.
+pub const KERNEL_FIVE_POINT: Kernel = [[0.0, 1.0, 0.0], [1.0, -4.0, 1.0], [0.0, 1.0, 0.0]];
+/// 9-point stencil (Oono-Puri). The most isotropic of the three.
+pub const KERNEL_NINE_POINT: Kernel = [[0.25, 0.5, 0.25], [0.5, -3.0, 0.5], [0.25, 0.5, 0.25]];
+
+/// What happens to the neighbors of cells on the edge of the grid
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Boundary {
+  /// the grid is a torus: the left edge touches the right edge, top touches bottom
+  Wrap,
+  /// cells past the edge have the same value as the edge
+  Clamp,
+  /// cells past the edge mirror the cells just inside the edge
+  Reflect,
+}
+
+// Feed and kill rates can be a single value, or one value per cell
+#[derive(Clone)]
+enum Param {
+  Constant(f32),
+  Map(Vec<f32>),
+}
+
+// don't log every value in the map
+impl std::fmt::Debug for Param {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Param::Constant(value) => write!(f, "{}", value),
+      Param::Map(values) => write!(f, "Map({} values)", values.len()),
+    }
+  }
+}
+
+impl Param {
+  fn at(&self, index: usize) -> f32 {
+    match self {
+      Param::Constant(value) => *value,
+      Param::Map(values) => values[index],
+    }
+  }
+}
+
+pub struct GrayScott {
+  pub width: usize,
+  pub height: usize,
+  // concentrations of chemicals A and B, one value per cell
+  a: Vec<f32>,
+  b: Vec<f32>,
+  // buffers that `step` writes into, swapped with `a` and `b` afterwards
+  next_a: Vec<f32>,
+  next_b: Vec<f32>,
+  // diffusion rates
+  d_a: f32,
+  d_b: f32,
+  // "feed" rate of A and "kill" rate of B
+  f: Param,
+  k: Param,
+  dt: f32,
+  kernel: Kernel,
+  boundary: Boundary,
+  iterations: usize,
+}
+
+impl GrayScott {
+  /// A grid full of A with no B. Nothing happens until some B is seeded.
+  pub fn new(width: usize, height: usize) -> Self {
+    let n = width * height;
+    GrayScott {
+      width,
+      height,
+      a: vec![1.0; n],
+      b: vec![0.0; n],
+      next_a: vec![1.0; n],
+      next_b: vec![0.0; n],
+      d_a: 1.0,
+      d_b: 0.5,
+      f: Param::Constant(0.055),
+      k: Param::Constant(0.062),
+      dt: 1.0,
+      kernel: KERNEL_SIMS,
+      boundary: Boundary::Wrap,
+      iterations: 0,
+    }
+  }
+
+  pub fn diffusion(mut self, d_a: f32, d_b: f32) -> Self {
+    self.d_a = d_a;
+    self.d_b = d_b;
+    self
+  }
+
+  pub fn feed(mut self, f: f32) -> Self {
+    self.f = Param::Constant(f);
+    self
+  }
+
+  pub fn kill(mut self, k: f32) -> Self {
+    self.k = Param::Constant(k);
+    self
+  }
+
+  /// Spatially varying feed rate. `f(i, j)` is called once per cell.
+  /// Handy with a noise function or `DensityMap::sample` to get different patterns in different regions.
+  pub fn feed_fn(mut self, f: impl Fn(usize, usize) -> f32) -> Self {
+    self.f = Param::Map(self.map(f));
+    self
+  }
+
+  /// Spatially varying kill rate. `k(i, j)` is called once per cell.
+  pub fn kill_fn(mut self, k: impl Fn(usize, usize) -> f32) -> Self {
+    self.k = Param::Map(self.map(k));
+    self
+  }
+
+  pub fn dt(mut self, dt: f32) -> Self {
+    self.dt = dt;
+    self
+  }
+
+  pub fn kernel(mut self, kernel: Kernel) -> Self {
+    self.kernel = kernel;
+    self
+  }
+
+  pub fn boundary(mut self, boundary: Boundary) -> Self {
+    self.boundary = boundary;
+    self
+  }
+
+  pub fn a(&self, i: usize, j: usize) -> f32 {
+    self.a[j * self.width + i]
+  }
+
+  pub fn b(&self, i: usize, j: usize) -> f32 {
+    self.b[j * self.width + i]
+  }
+
+  pub fn set(&mut self, i: usize, j: usize, a: f32, b: f32) {
+    let index = j * self.width + i;
+    self.a[index] = a;
+    self.b[index] = b;
+  }
+
+  /// number of steps run so far
+  pub fn iterations(&self) -> usize {
+    self.iterations
+  }
+
+  /// Maps a cell to the center of its area in the window.
+  /// The grid is stretched to fill `win`.
+  pub fn cell_to_window(&self, i: usize, j: usize, win: &Rect) -> Point2 {
+    pt2(
+      map_range(
+        i as f32 + 0.5,
+        0.0,
+        self.width as f32,
+        win.left(),
+        win.right(),
+      ),
+      map_range(
+        j as f32 + 0.5,
+        0.0,
+        self.height as f32,
+        win.top(),
+        win.bottom(),
+      ),
+    )
+  }
+
+  /// Fills every cell inside the circle (in window coordinates) with B
+  pub fn seed_circle(&mut self, circle: &Circle, win: &Rect) {
+    let center = pt2(circle.x, circle.y);
+    self.seed_where(win, |p| p.distance(center) < circle.radius);
+  }
+
+  /// Fills every cell inside the polygon (in window coordinates) with B.
+  /// Works with `Blob::points()`, hexagons, etc.
+  pub fn seed_polygon(&mut self, points: &[Point2], win: &Rect) {
+    let points = points.to_vec();
+    self.seed_where(win, |p| polygon::contains(&points, p));
+  }
+
+  /// Drops `n` small squares of B at random positions
+  pub fn seed_random(&mut self, n: usize, size: usize) {
+    for _ in 0..n {
+      let i0 = random_range(0, self.width.max(1));
+      let j0 = random_range(0, self.height.max(1));
+      for j in j0..(j0 + size).min(self.height) {
+        for i in i0..(i0 + size).min(self.width) {
+          self.set(i, j, 0.0, 1.0);
+        }
+      }
+    }
+  }
+
+  fn seed_where(&mut self, win: &Rect, inside: impl Fn(Point2) -> bool) {
+    for j in 0..self.height {
+      for i in 0..self.width {
+        if inside(self.cell_to_window(i, j, win)) {
+          self.set(i, j, 0.0, 1.0);
+        }
+      }
+    }
+  }
+
+  fn map(&self, f: impl Fn(usize, usize) -> f32) -> Vec<f32> {
+    (0..self.width * self.height)
+      .map(|index| f(index % self.width, index / self.width))
+      .collect()
+  }
+
+  /// Advance the simulation by one step
+  pub fn step(&mut self) {
+    let width = self.width;
+    let columns = neighbor_table(width, self.boundary);
+    let rows = neighbor_table(self.height, self.boundary);
+
+    let GrayScott {
+      ref a,
+      ref b,
+      ref mut next_a,
+      ref mut next_b,
+      ref f,
+      ref k,
+      d_a,
+      d_b,
+      dt,
+      kernel,
+      ..
+    } = *self;
+
+    next_a
+      .par_chunks_mut(width)
+      .zip(next_b.par_chunks_mut(width))
+      .enumerate()
+      .for_each(|(j, (row_a, row_b))| {
+        for i in 0..width {
+          let index = j * width + i;
+          let (lap_a, lap_b) = laplace(a, b, &kernel, &columns[i], &rows[j], width);
+          let a_ = a[index];
+          let b_ = b[index];
+          let feed = f.at(index);
+          let kill = k.at(index);
+          let reaction = a_ * b_ * b_;
+          row_a[i] = (a_ + (d_a * lap_a - reaction + feed * (1.0 - a_)) * dt)
+            .max(0.0)
+            .min(1.0);
+          row_b[i] = (b_ + (d_b * lap_b + reaction - (kill + feed) * b_) * dt)
+            .max(0.0)
+            .min(1.0);
+        }
+      });
+
+    std::mem::swap(&mut self.a, &mut self.next_a);
+    std::mem::swap(&mut self.b, &mut self.next_b);
+    self.iterations += 1;
+  }
+
+  /// Isolines where the concentration of B equals `concentration`, in window coordinates.
+  /// Unlike `draw`, the output isn't tied to the grid resolution, so it works for SVG/plotters and big prints.
+  pub fn contours(&self, concentration: f32, win: &Rect) -> Vec<Line2> {
+    field_contours(
+      self.width,
+      self.height,
+      concentration,
+      |i, j| self.b(i, j),
+      |i, j| self.cell_to_window(i, j, win),
+    )
+  }
+
+  /// Draws every cell as a rectangle colored by `color(a, b)`
+  pub fn draw(&self, draw: &Draw, win: &Rect, color: impl Fn(f32, f32) -> Hsla) {
+    let w = win.w() / self.width as f32;
+    let h = win.h() / self.height as f32;
+    for j in 0..self.height {
+      for i in 0..self.width {
+        let p = self.cell_to_window(i, j, win);
+        draw
+          .rect()
+          .x_y(p.x, p.y)
+          .w_h(w, h)
+          .color(color(self.a(i, j), self.b(i, j)));
+      }
+    }
+  }
+}
+
+impl Simulation for GrayScott {
+  fn width(&self) -> usize {
+    self.width
+  }
+
+  fn height(&self) -> usize {
+    self.height
+  }
+
+  fn step(&mut self) {
+    GrayScott::step(self)
+  }
+
+  // B is what makes the pattern. Same as drawing with lightness `a - b`, inverted
+  fn sample(&self, i: usize, j: usize) -> f32 {
+    1.0 - (self.a(i, j) - self.b(i, j)).max(0.0).min(1.0)
+  }
+}
+
+/// The reaction half of a two-chemical reaction-diffusion system: how fast the
+/// concentrations u and v change at a single cell, ignoring diffusion.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Reaction {
+  /// An excitable medium, originally a model of nerve impulses.
+  /// du = u - u^3 - v, dv = epsilon * (u - a1 * v - a0).
+  /// Makes labyrinths and spots when v diffuses faster than u.
+  FitzHughNagumo { a0: f32, a1: f32, epsilon: f32 },
+  /// A model of an autocatalytic chemical reaction.
+  /// du = a - (b + 1) * u + u^2 * v, dv = b * u - u^2 * v.
+  /// Turing patterns need (1 + a * sqrt(d_u / d_v))^2 < b < 1 + a^2, which only has room when v diffuses
+  /// a lot faster than u. Above 1 + a^2 the whole grid oscillates instead.
+  Brusselator { a: f32, b: f32 },
+  /// The simplest reaction that makes Turing patterns.
+  /// du = gamma * (a - u + u^2 * v), dv = gamma * (b - u^2 * v).
+  Schnakenberg { a: f32, b: f32, gamma: f32 },
+}
+
+impl Reaction {
+  // some parameters that make nice patterns
+  pub fn fitzhugh_nagumo() -> Self {
+    Reaction::FitzHughNagumo {
+      a0: -0.1,
+      a1: 2.0,
+      epsilon: 0.05,
+    }
+  }
+
+  pub fn brusselator() -> Self {
+    Reaction::Brusselator { a: 2.0, b: 4.0 }
+  }
+
+  pub fn schnakenberg() -> Self {
+    Reaction::Schnakenberg {
+      a: 0.1,
+      b: 0.9,
+      gamma: 1.0,
+    }
+  }
+
+  fn rate(&self, u: f32, v: f32) -> (f32, f32) {
+    match *self {
+      Reaction::FitzHughNagumo { a0, a1, epsilon } => {
+        (u - u * u * u - v, epsilon * (u - a1 * v - a0))
+      }
+      Reaction::Brusselator { a, b } => (a - (b + 1.0) * u + u * u * v, b * u - u * u * v),
+      Reaction::Schnakenberg { a, b, gamma } => {
+        (gamma * (a - u + u * u * v), gamma * (b - u * u * v))
+      }
+    }
+  }
+
+  // the uniform state where nothing changes. Patterns grow out of small disturbances to this.
+  fn steady_state(&self) -> (f32, f32) {
+    match *self {
+      // close enough, the noise takes care of the rest
+      Reaction::FitzHughNagumo { .. } => (0.0, 0.0),
+      Reaction::Brusselator { a, b } => (a, b / a),
+      Reaction::Schnakenberg { a, b, .. } => (a + b, b / ((a + b) * (a + b))),
+    }
+  }
+}
+
+/// A two-chemical reaction-diffusion system on the same kind of grid as GrayScott.
+/// The grid starts at the reaction's steady state plus some noise, so no seeding is required.
+pub struct ReactionDiffusion {
+  pub width: usize,
+  pub height: usize,
+  reaction: Reaction,
+  u: Vec<f32>,
+  v: Vec<f32>,
+  next_u: Vec<f32>,
+  next_v: Vec<f32>,
+  d_u: f32,
+  d_v: f32,
+  dt: f32,
+  kernel: Kernel,
+  boundary: Boundary,
+  iterations: usize,
+  // range of u in the last step, used to normalize `sample`
+  min_u: f32,
+  max_u: f32,
+}
+
+impl ReactionDiffusion {
+  pub fn new(width: usize, height: usize, reaction: Reaction) -> Self {
+    let n = width * height;
+    // defaults that make features roughly 10-20 cells across
+    let (d_u, d_v, dt) = match reaction {
+      Reaction::FitzHughNagumo { .. } => (0.5, 10.0, 0.03),
+      Reaction::Brusselator { .. } => (4.0, 32.0, 0.004),
+      Reaction::Schnakenberg { .. } => (1.0, 16.0, 0.02),
+    };
+    let mut rd = ReactionDiffusion {
+      width,
+      height,
+      reaction,
+      u: vec![0.0; n],
+      v: vec![0.0; n],
+      next_u: vec![0.0; n],
+      next_v: vec![0.0; n],
+      d_u,
+      d_v,
+      dt,
+      kernel: KERNEL_SIMS,
+      boundary: Boundary::Wrap,
+      iterations: 0,
+      min_u: 0.0,
+      max_u: 1.0,
+    };
+    rd.randomize(0.05);
+    rd
+  }
+
+  pub fn diffusion(mut self, d_u: f32, d_v: f32) -> Self {
+    self.d_u = d_u;
+    self.d_v = d_v;
+    self
+  }
+
+  // Smaller is slower but more stable. If the output is a flat color, dt is probably too large
+  pub fn dt(mut self, dt: f32) -> Self {
+    self.dt = dt;
+    self
+  }
+
+  pub fn kernel(mut self, kernel: Kernel) -> Self {
+    self.kernel = kernel;
+    self
+  }
+
+  pub fn boundary(mut self, boundary: Boundary) -> Self {
+    self.boundary = boundary;
+    self
+  }
+
+  /// Resets every cell to the steady state, disturbed by up to `amount` in either direction
+  pub fn randomize(&mut self, amount: f32) {
+    let (u, v) = self.reaction.steady_state();
+    for index in 0..self.u.len() {
+      self.u[index] = u + random_range(-amount, amount);
+      self.v[index] = v + random_range(-amount, amount);
+    }
+    self.update_range();
+  }
+
+  pub fn u(&self, i: usize, j: usize) -> f32 {
+    self.u[j * self.width + i]
+  }
+
+  pub fn v(&self, i: usize, j: usize) -> f32 {
+    self.v[j * self.width + i]
+  }
+
+  pub fn iterations(&self) -> usize {
+    self.iterations
+  }
+
+  fn update_range(&mut self) {
+    let (min, max) = self
+      .u
+      .iter()
+      .fold((std::f32::MAX, std::f32::MIN), |(min, max), u| {
+        (min.min(*u), max.max(*u))
+      });
+    self.min_u = min;
+    self.max_u = max;
+  }
+}
+
+impl Simulation for ReactionDiffusion {
+  fn width(&self) -> usize {
+    self.width
+  }
+
+  fn height(&self) -> usize {
+    self.height
+  }
+
+  fn step(&mut self) {
+    let width = self.width;
+    let columns = neighbor_table(width, self.boundary);
+    let rows = neighbor_table(self.height, self.boundary);
+
+    let ReactionDiffusion {
+      ref u,
+      ref v,
+      ref mut next_u,
+      ref mut next_v,
+      reaction,
+      d_u,
+      d_v,
+      dt,
+      kernel,
+      ..
+    } = *self;
+
+    next_u
+      .par_chunks_mut(width)
+      .zip(next_v.par_chunks_mut(width))
+      .enumerate()
+      .for_each(|(j, (row_u, row_v))| {
+        for i in 0..width {
+          let index = j * width + i;
+          let (lap_u, lap_v) = laplace(u, v, &kernel, &columns[i], &rows[j], width);
+          let (du, dv) = reaction.rate(u[index], v[index]);
+          // these systems aren't bounded like Gray-Scott, so clamp to something sane to keep
+          // a bad choice of parameters from blowing up into NaN
+          row_u[i] = (u[index] + (d_u * lap_u + du) * dt).max(-100.0).min(100.0);
+          row_v[i] = (v[index] + (d_v * lap_v + dv) * dt).max(-100.0).min(100.0);
+        }
+      });
+
+    std::mem::swap(&mut self.u, &mut self.next_u);
+    std::mem::swap(&mut self.v, &mut self.next_v);
+    self.update_range();
+    self.iterations += 1;
+  }
+
+  // u normalized to the range of the whole grid
+  fn sample(&self, i: usize, j: usize) -> f32 {
+    let range = self.max_u - self.min_u;
+    if range <= std::f32::EPSILON {
+      return 0.0;
+    }
+    (self.u(i, j) - self.min_u) / range
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every cell
+impl std::fmt::Debug for ReactionDiffusion {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("ReactionDiffusion")
+      .field("width", &self.width)
+      .field("height", &self.height)
+      .field("reaction", &self.reaction)
+      .field("d_u", &self.d_u)
+      .field("d_v", &self.d_v)
+      .field("dt", &self.dt)
+      .field("kernel", &self.kernel)
+      .field("boundary", &self.boundary)
+      .field("iterations", &self.iterations)
+      .finish()
+  }
+}
+
+/// The index `offset` cells away from `i` along an axis of length `n`,
+/// for when the offset goes past the edge of the grid.
+pub fn offset_index(i: usize, offset: isize, n: usize, boundary: Boundary) -> usize {
+  if n < 2 {
+    return 0;
+  }
+  let i = i as isize + offset;
+  let n = n as isize;
+  let index = match boundary {
+    Boundary::Wrap => i.rem_euclid(n),
+    Boundary::Clamp => i.max(0).min(n - 1),
+    Boundary::Reflect => {
+      // bounce back and forth between the edges without repeating the edge cell
+      let period = 2 * (n - 1);
+      let m = i.rem_euclid(period);
+      if m < n {
+        m
+      } else {
+        period - m
+      }
+    }
+  };
+  index as usize
+}
+
+/// Indices of the previous cell, the cell itself, and the next cell for every position along one axis.
+/// Looking up neighbors is the hot path, so steps figure out the boundary handling once
+/// instead of once per cell
+pub fn neighbor_table(n: usize, boundary: Boundary) -> Vec<[usize; 3]> {
+  (0..n)
+    .map(|i| {
+      [
+        offset_index(i, -1, n, boundary),
+        i,
+        offset_index(i, 1, n, boundary),
+      ]
+    })
+    .collect()
+}
+
+// applies the 3x3 kernel to both chemicals at once
+fn laplace(
+  a: &[f32],
+  b: &[f32],
+  kernel: &Kernel,
+  columns: &[usize; 3],
+  rows: &[usize; 3],
+  width: usize,
+) -> (f32, f32) {
+  let mut sum_a = 0.0;
+  let mut sum_b = 0.0;
+  for (kernel_row, j) in kernel.iter().zip(rows.iter()) {
+    for (weight, i) in kernel_row.iter().zip(columns.iter()) {
+      let index = j * width + i;
+      sum_a += weight * a[index];
+      sum_b += weight * b[index];
+    }
+  }
+  (sum_a, sum_b)
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every cell
+impl std::fmt::Debug for GrayScott {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("GrayScott")
+      .field("width", &self.width)
+      .field("height", &self.height)
+      .field("d_a", &self.d_a)
+      .field("d_b", &self.d_b)
+      .field("f", &self.f)
+      .field("k", &self.k)
+      .field("dt", &self.dt)
+      .field("kernel", &self.kernel)
+      .field("boundary", &self.boundary)
+      .field("iterations", &self.iterations)
+      .finish()
+  }
+}
@@ -0,0 +1,63 @@
+// Seeded stand-ins for nannou's random_range, random_f32, random_f64 and random,
+// so a run can be repeated exactly (see util::provenance and the reproduce example).
+//
+// The seed is `--rng-seed` if it was given, otherwise it's picked once per run.
+// Either way it's recorded as the "rng-seed" argument, so captures remember it.
+//
+// The util modules use these instead of the prelude versions. Sketches can too,
+// an explicit import wins over the prelude's glob import:
+//   use nannou::prelude::*;
+//   use util::rng::{random_f32, random_range};
+use nannou::rand::distributions::uniform::SampleUniform;
+use nannou::rand::distributions::{Distribution, Standard};
+use nannou::rand::rngs::StdRng;
+use nannou::rand::{Rng, SeedableRng};
+
+use std::sync::Mutex;
+
+use super::args::{self, ArgParser};
+
+static SEED: Mutex<Option<u64>> = Mutex::new(None);
+static RNG: Mutex<Option<StdRng>> = Mutex::new(None);
+
+/// The seed for this run
+pub fn seed() -> u64 {
+  let mut seed = SEED.lock().unwrap();
+  *seed.get_or_insert_with(|| {
+    let args = ArgParser::new();
+    let value = args
+      .peek("rng-seed")
+      // small enough to type back in
+      .unwrap_or_else(|| nannou::rand::random::<u32>() as u64);
+    args::record("rng-seed", &value.to_string());
+    value
+  })
+}
+
+fn with_rng<T>(f: impl FnOnce(&mut StdRng) -> T) -> T {
+  let mut rng = RNG.lock().unwrap();
+  f(rng.get_or_insert_with(|| StdRng::seed_from_u64(seed())))
+}
+
+/// Same as nannou's random_range: min and max can come in either order, but must not be equal
+pub fn random_range<T: PartialOrd + SampleUniform>(min: T, max: T) -> T {
+  let (min, max) = if min <= max { (min, max) } else { (max, min) };
+  with_rng(|rng| rng.gen_range(min, max))
+}
+
+/// In [0, 1)
+pub fn random_f32() -> f32 {
+  random()
+}
+
+/// In [0, 1)
+pub fn random_f64() -> f64 {
+  random()
+}
+
+pub fn random<T>() -> T
+where
+  Standard: Distribution<T>,
+{
+  with_rng(|rng| rng.gen())
+}
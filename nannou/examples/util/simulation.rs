@@ -0,0 +1,78 @@
+// Common interface for grid-based pattern simulations:
+// reaction-diffusion (util::reaction_diffusion), multi-scale Turing patterns (util::turing)
+// and cellular automata (util::automata).
+//
+// Anything that implements Simulation can be stepped and drawn by the same view code,
+// so a sketch can keep a Box<dyn Simulation> and pick the model with a flag.
+use nannou::prelude::*;
+
+use super::contours::field_contours;
+use super::Line2;
+
+pub trait Simulation: std::fmt::Debug {
+  /// number of columns in the grid
+  fn width(&self) -> usize;
+
+  /// number of rows in the grid
+  fn height(&self) -> usize;
+
+  /// Advance the simulation by one step
+  fn step(&mut self);
+
+  /// The state of a cell in [0, 1], where 0 is "empty" and 1 is "full".
+  /// What that means depends on the simulation, but it's always something worth drawing.
+  /// (i, j) are (column, row) and j = 0 is the top row.
+  fn sample(&self, i: usize, j: usize) -> f32;
+
+  /// Advance the simulation by `n` steps. Do all the work up front and render once at the end,
+  /// because drawing the grid is way slower than simulating it.
+  fn steps(&mut self, n: usize) {
+    for _ in 0..n {
+      self.step();
+    }
+  }
+}
+
+/// Draws every cell as a rectangle colored by `color(sample)`. The grid is stretched to fill `win`.
+pub fn draw_simulation(
+  simulation: &dyn Simulation,
+  draw: &Draw,
+  win: &Rect,
+  color: impl Fn(f32) -> Hsla,
+) {
+  let (width, height) = (simulation.width(), simulation.height());
+  let w = win.w() / width as f32;
+  let h = win.h() / height as f32;
+  for j in 0..height {
+    for i in 0..width {
+      draw
+        .rect()
+        .x_y(
+          win.left() + (i as f32 + 0.5) * w,
+          win.top() - (j as f32 + 0.5) * h,
+        )
+        .w_h(w, h)
+        .color(color(simulation.sample(i, j)));
+    }
+  }
+}
+
+/// Isolines where `sample` equals `threshold`, stretched to fill `win`.
+/// Resolution-independent alternative to `draw_simulation`.
+pub fn simulation_contours(simulation: &dyn Simulation, threshold: f32, win: &Rect) -> Vec<Line2> {
+  let (width, height) = (simulation.width(), simulation.height());
+  let w = win.w() / width as f32;
+  let h = win.h() / height as f32;
+  field_contours(
+    width,
+    height,
+    threshold,
+    |i, j| simulation.sample(i, j),
+    |i, j| {
+      pt2(
+        win.left() + (i as f32 + 0.5) * w,
+        win.top() - (j as f32 + 0.5) * h,
+      )
+    },
+  )
+}
@@ -0,0 +1,485 @@
+// Space-filling curves as one continuous Line2, for plotting without lifting the pen.
+// https://en.wikipedia.org/wiki/Space-filling_curve
+//
+// Hilbert, Moore, Peano and Z-order curves are recursive subdivisions of a cell.
+// A cell is an origin and two edge vectors (like the classic recursive Hilbert algorithm),
+// so each child can be rotated/reflected relative to its parent without any lookup tables.
+// The curve enters a cell at its origin and leaves at
+// - origin + y for Hilbert (and the four Hilbert cells that make a Moore curve)
+// - origin + x + y for Peano
+// Z-order doesn't care, it jumps diagonally between quadrants anyway.
+// The line visits the center of every leaf cell in order.
+//
+// Gosper (the "flowsnake") is an edge-rewriting curve on a triangular grid instead:
+// every segment is replaced by 7 shorter segments, so it's handled separately.
+// It fills a hexagon-ish island rather than a rectangle, and gets scaled to fit inside the rect.
+//
+// Adaptive depth: with a density function (darkness in [0, 1], e.g. DensityMap::sample or noise)
+// a cell is only subdivided while its depth is below
+//   min_depth + darkness * (depth - min_depth)
+// so dark areas get a tight, busy curve and light areas get a few long strokes.
+// Neighboring leaves always share an edge or corner, so the line stays continuous at mixed depths.
+//
+// Masking: leaves outside the mask polygon are dropped, and when the line would cross the
+// boundary it walks along the boundary instead, so the result is still a single line inside the mask.
+use nannou::prelude::*;
+
+use super::polygon::{bounds, closest_point_on_segment, contains, open_ring, segment_intersection};
+use super::Line2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurveKind {
+  Hilbert,
+  Moore,
+  Peano,
+  Gosper,
+  ZOrder,
+}
+
+pub const CURVES: [&str; 5] = ["hilbert", "moore", "peano", "gosper", "z-order"];
+
+/// Looks up a curve by name, see CURVES
+pub fn curve_kind(name: &str) -> Option<CurveKind> {
+  match name {
+    "hilbert" => Some(CurveKind::Hilbert),
+    "moore" => Some(CurveKind::Moore),
+    "peano" => Some(CurveKind::Peano),
+    "gosper" => Some(CurveKind::Gosper),
+    "z-order" => Some(CurveKind::ZOrder),
+    _ => None,
+  }
+}
+
+// rewriting rules for the Gosper curve. + and - turn by 60 degrees
+const GOSPER_F: &str = "F-G--G+F++FF+G-";
+const GOSPER_G: &str = "+F-GG--G-F++F+G";
+
+#[derive(Debug, Clone, Copy)]
+struct Cell {
+  origin: Point2,
+  x: Vector2,
+  y: Vector2,
+  depth: usize,
+}
+
+impl Cell {
+  fn center(&self) -> Point2 {
+    self.origin + (self.x + self.y) * 0.5
+  }
+
+  // point at (s, t) in the cell's own coordinates, both in [0, 1]
+  fn at(&self, s: f32, t: f32) -> Point2 {
+    self.origin + self.x * s + self.y * t
+  }
+
+  fn child(&self, origin: Point2, x: Vector2, y: Vector2) -> Cell {
+    Cell {
+      origin,
+      x,
+      y,
+      depth: self.depth + 1,
+    }
+  }
+
+  fn hilbert_children(&self) -> Vec<Cell> {
+    let (x, y) = (self.x * 0.5, self.y * 0.5);
+    let o = self.origin;
+    vec![
+      self.child(o, y, x),
+      self.child(o + x, x, y),
+      self.child(o + x + y, x, y),
+      self.child(o + x + y * 2.0, -y, -x),
+    ]
+  }
+
+  // 3x3 serpentine, column by column.
+  // The middle row is reflected in x and the middle column in y,
+  // which lines up every child's exit corner with the next child's entry corner
+  fn peano_children(&self) -> Vec<Cell> {
+    let (x, y) = (self.x / 3.0, self.y / 3.0);
+    let mut children = Vec::with_capacity(9);
+    for column in 0..3 {
+      for step in 0..3 {
+        let row = if column == 1 { 2 - step } else { step };
+        let flip_x = row == 1;
+        let flip_y = column == 1;
+        let origin = self.origin
+          + x * (column as f32 + if flip_x { 1.0 } else { 0.0 })
+          + y * (row as f32 + if flip_y { 1.0 } else { 0.0 });
+        children.push(self.child(
+          origin,
+          if flip_x { -x } else { x },
+          if flip_y { -y } else { y },
+        ));
+      }
+    }
+    children
+  }
+
+  fn z_order_children(&self) -> Vec<Cell> {
+    let (x, y) = (self.x * 0.5, self.y * 0.5);
+    let o = self.origin;
+    vec![
+      self.child(o, x, y),
+      self.child(o + x, x, y),
+      self.child(o + y, x, y),
+      self.child(o + x + y, x, y),
+    ]
+  }
+}
+
+pub struct SpaceFillingCurve {
+  kind: CurveKind,
+  rect: Rect,
+  depth: usize,
+  min_depth: usize,
+  // darkness in [0, 1] at a point in the window
+  density: Option<Box<dyn Fn(Point2) -> f32>>,
+  mask: Option<Line2>,
+}
+
+impl SpaceFillingCurve {
+  /// A curve filling `rect`. Without a density function every cell is subdivided to `depth`.
+  /// Hilbert, Moore, Peano and Z-order stretch to the rect's aspect ratio,
+  /// so use a square rect if you want square cells.
+  pub fn new(kind: CurveKind, rect: Rect) -> Self {
+    SpaceFillingCurve {
+      kind,
+      rect,
+      depth: 5,
+      min_depth: 0,
+      density: None,
+      mask: None,
+    }
+  }
+
+  /// Maximum subdivision depth.
+  /// Watch out, the number of points grows fast:
+  /// 4^depth for Hilbert, Moore and Z-order, 9^depth for Peano and 7^depth for Gosper
+  pub fn depth(mut self, depth: usize) -> Self {
+    self.depth = depth;
+    self
+  }
+
+  /// Cells are always subdivided at least this far, even where the density is zero
+  pub fn min_depth(mut self, min_depth: usize) -> Self {
+    self.min_depth = min_depth;
+    self
+  }
+
+  /// Darkness in [0, 1] at a point, which decides how deep each cell is subdivided
+  pub fn density(mut self, density: impl Fn(Point2) -> f32 + 'static) -> Self {
+    self.density = Some(Box::new(density));
+    self
+  }
+
+  /// Only keep the curve inside this polygon
+  pub fn mask(mut self, polygon: Line2) -> Self {
+    self.mask = Some(polygon);
+    self
+  }
+
+  pub fn line(&self) -> Line2 {
+    let line = match self.kind {
+      CurveKind::Gosper => self.gosper(),
+      _ => {
+        let mut line = vec![];
+        for cell in self.root_cells() {
+          self.subdivide(cell, &mut line);
+        }
+        if self.kind == CurveKind::Moore {
+          // Moore curves are closed loops
+          if let Some(first) = line.first().cloned() {
+            line.push(first);
+          }
+        }
+        line
+      }
+    };
+    match &self.mask {
+      Some(mask) => mask_line(&line, mask),
+      None => line,
+    }
+  }
+
+  fn root_cells(&self) -> Vec<Cell> {
+    let r = &self.rect;
+    let cell = |origin: Point2, x: Vector2, y: Vector2, depth: usize| Cell {
+      origin,
+      x,
+      y,
+      depth,
+    };
+    match self.kind {
+      // four Hilbert cells, up the left half and back down the right half
+      CurveKind::Moore if self.depth > 0 => {
+        let (half_w, half_h) = (r.w() / 2.0, r.h() / 2.0);
+        let (up, left, right) = (vec2(0.0, half_h), vec2(-half_w, 0.0), vec2(half_w, 0.0));
+        let bottom = pt2(r.x(), r.bottom());
+        let top = pt2(r.x(), r.top());
+        vec![
+          cell(bottom, left, up, 1),
+          cell(bottom + up, left, up, 1),
+          cell(top, right, -up, 1),
+          cell(top - up, right, -up, 1),
+        ]
+      }
+      _ => vec![cell(
+        pt2(r.left(), r.bottom()),
+        vec2(r.w(), 0.0),
+        vec2(0.0, r.h()),
+        0,
+      )],
+    }
+  }
+
+  fn subdivide(&self, cell: Cell, line: &mut Line2) {
+    // a few samples so a big cell doesn't miss a dark patch next to its center
+    let samples = [
+      cell.center(),
+      cell.at(0.25, 0.25),
+      cell.at(0.75, 0.25),
+      cell.at(0.25, 0.75),
+      cell.at(0.75, 0.75),
+    ];
+    if !self.should_subdivide(cell.depth, &samples) {
+      line.push(cell.center());
+      return;
+    }
+    let children = match self.kind {
+      CurveKind::Peano => cell.peano_children(),
+      CurveKind::ZOrder => cell.z_order_children(),
+      _ => cell.hilbert_children(),
+    };
+    for child in children {
+      self.subdivide(child, line);
+    }
+  }
+
+  fn should_subdivide(&self, depth: usize, samples: &[Point2]) -> bool {
+    if depth >= self.depth {
+      return false;
+    }
+    if depth < self.min_depth {
+      return true;
+    }
+    match &self.density {
+      Some(density) => {
+        let darkness = samples.iter().map(|p| density(*p)).sum::<f32>() / samples.len() as f32;
+        let min_depth = self.min_depth.min(self.depth) as f32;
+        let target = min_depth + darkness.max(0.0).min(1.0) * (self.depth as f32 - min_depth);
+        (depth as f32) < target.round()
+      }
+      None => true,
+    }
+  }
+
+  fn gosper(&self) -> Line2 {
+    let rules = [rule_steps(GOSPER_F, 60.0), rule_steps(GOSPER_G, 60.0)];
+    // fit the island into the rect using a cheap low-depth version of the curve
+    let mut unit = vec![pt2(0.0, 0.0)];
+    rewrite(
+      &rules,
+      'F',
+      pt2(0.0, 0.0),
+      pt2(1.0, 0.0),
+      0,
+      &|depth, _| depth < 4,
+      &mut unit,
+    );
+    let b = bounds(&unit);
+    let scale = (self.rect.w() / b.w()).min(self.rect.h() / b.h());
+    let start = self.rect.xy() - b.xy() * scale;
+    let end = start + vec2(scale, 0.0);
+
+    let mut line = vec![start];
+    let subdivide = |depth, samples: &[Point2]| self.should_subdivide(depth, samples);
+    rewrite(&rules, 'F', start, end, 0, &subdivide, &mut line);
+    line
+  }
+}
+
+// implement Debug custom because otherwise
+// the density function can't be printed
+impl std::fmt::Debug for SpaceFillingCurve {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("SpaceFillingCurve")
+      .field("kind", &self.kind)
+      .field("rect", &self.rect)
+      .field("depth", &self.depth)
+      .field("min_depth", &self.min_depth)
+      .field("density", &self.density.is_some())
+      .field("mask", &self.mask.as_ref().map(|m| m.len()))
+      .finish()
+  }
+}
+
+type RuleSteps = (Vec<(char, Vector2)>, Vector2);
+
+// replaces segment a->b with the rule for `symbol` (F or G) until `subdivide` says stop,
+// pushing the end point of every leaf segment
+fn rewrite(
+  rules: &[RuleSteps; 2],
+  symbol: char,
+  a: Point2,
+  b: Point2,
+  depth: usize,
+  subdivide: &dyn Fn(usize, &[Point2]) -> bool,
+  line: &mut Line2,
+) {
+  if !subdivide(depth, &[a, (a + b) * 0.5, b]) {
+    line.push(b);
+    return;
+  }
+  let (steps, total) = if symbol == 'F' { &rules[0] } else { &rules[1] };
+  let v = b - a;
+  let mut position = a;
+  for (symbol, step) in steps {
+    // rotate and scale the unit step so the whole rule spans a->b
+    let next = position + complex_mul(v, complex_div(*step, *total));
+    rewrite(rules, *symbol, position, next, depth + 1, subdivide, line);
+    position = next;
+  }
+}
+
+// walks a rule with unit steps, returning every drawn step and the total displacement
+fn rule_steps(rule: &str, angle: f32) -> RuleSteps {
+  let mut heading = 0.0;
+  let mut steps = vec![];
+  let mut total = vec2(0.0, 0.0);
+  for c in rule.chars() {
+    match c {
+      '+' => heading += deg_to_rad(angle),
+      '-' => heading -= deg_to_rad(angle),
+      _ => {
+        let step = vec2(heading.cos(), heading.sin());
+        steps.push((c, step));
+        total += step;
+      }
+    }
+  }
+  (steps, total)
+}
+
+// treating vectors as complex numbers makes "rotate and scale like this" a multiplication
+fn complex_mul(a: Vector2, b: Vector2) -> Vector2 {
+  vec2(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x)
+}
+
+fn complex_div(a: Vector2, b: Vector2) -> Vector2 {
+  let length2 = b.x * b.x + b.y * b.y;
+  vec2(a.x * b.x + a.y * b.y, a.y * b.x - a.x * b.y) / length2
+}
+
+/// Keeps the points of `line` that are inside `polygon`, as a single line.
+/// Wherever the line would leave the polygon it follows the polygon's boundary instead,
+/// taking the shorter way around.
+/// Only works for simple polygons.
+pub fn mask_line(line: &Line2, polygon: &Line2) -> Line2 {
+  let ring = open_ring(polygon);
+  let mut masked: Line2 = vec![];
+  for point in line.iter().filter(|p| contains(&ring, **p)) {
+    if let Some(last) = masked.last().cloned() {
+      if crosses_boundary(&ring, last, *point) {
+        masked.extend(boundary_path(&ring, last, *point));
+      }
+    }
+    masked.push(*point);
+  }
+  masked
+}
+
+fn crosses_boundary(ring: &Line2, a: Point2, b: Point2) -> bool {
+  (0..ring.len()).any(|i| segment_intersection(a, b, ring[i], ring[(i + 1) % ring.len()]).is_some())
+}
+
+// the closest point on the ring, and the index of the edge it's on
+fn closest_on_ring(ring: &Line2, point: Point2) -> (usize, Point2) {
+  (0..ring.len())
+    .map(|i| {
+      let (p, _) = closest_point_on_segment(point, ring[i], ring[(i + 1) % ring.len()]);
+      (i, p)
+    })
+    .fold((0, ring[0]), |best, (i, p)| {
+      if p.distance(point) < best.1.distance(point) {
+        (i, p)
+      } else {
+        best
+      }
+    })
+}
+
+// path along the ring between the closest boundary points to a and b.
+// The straight line from a point to its closest boundary point can't cross the boundary,
+// otherwise the crossing would be closer
+fn boundary_path(ring: &Line2, a: Point2, b: Point2) -> Line2 {
+  let n = ring.len();
+  let (edge_a, start) = closest_on_ring(ring, a);
+  let (edge_b, end) = closest_on_ring(ring, b);
+  if edge_a == edge_b {
+    return vec![start, end];
+  }
+  // forwards visits the vertices at the end of edge_a up to the start of edge_b
+  let mut forwards = vec![start];
+  let mut i = edge_a;
+  while i != edge_b {
+    i = (i + 1) % n;
+    forwards.push(ring[i]);
+  }
+  forwards.push(end);
+  // backwards visits the vertices at the start of edge_a down to the end of edge_b
+  let mut backwards = vec![start];
+  let stop = (edge_b + 1) % n;
+  let mut i = edge_a;
+  loop {
+    backwards.push(ring[i]);
+    if i == stop {
+      break;
+    }
+    i = (i + n - 1) % n;
+  }
+  backwards.push(end);
+  if path_length(&forwards) < path_length(&backwards) {
+    forwards
+  } else {
+    backwards
+  }
+}
+
+fn path_length(line: &Line2) -> f32 {
+  line.windows(2).map(|w| w[0].distance(w[1])).sum()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::collections::HashSet;
+
+  // endpoints rounded to a grid much finer than the segments, smaller end first so direction doesn't matter
+  fn segment_key(a: Point2, b: Point2) -> ((i64, i64), (i64, i64)) {
+    let round = |p: Point2| ((p.x * 100.0).round() as i64, (p.y * 100.0).round() as i64);
+    let (a, b) = (round(a), round(b));
+    if a < b {
+      (a, b)
+    } else {
+      (b, a)
+    }
+  }
+
+  #[test]
+  fn gosper_never_retraces_a_segment() {
+    let line = SpaceFillingCurve::new(CurveKind::Gosper, Rect::from_w_h(1000.0, 1000.0))
+      .depth(4)
+      .line();
+    assert_eq!(line.len(), 7usize.pow(4) + 1);
+    let mut segments = HashSet::new();
+    for w in line.windows(2) {
+      assert!(
+        segments.insert(segment_key(w[0], w[1])),
+        "segment {:?} -> {:?} is drawn twice",
+        w[0],
+        w[1]
+      );
+    }
+  }
+}
@@ -0,0 +1,164 @@
+// A uniform grid "spatial hash" for answering "what is near this point?" questions
+// without comparing every point against every other point.
+// Points are stored alongside their index in the caller's collection,
+// so the caller is free to keep its own Vec<Point2> (or Vec<Circle>, or whatever) as the source of truth.
+use nannou::prelude::*;
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct SpatialHash {
+  cell_size: f32,
+  cells: HashMap<(i32, i32), Vec<(usize, Point2)>>,
+  // bounds of the occupied cells, used to know when a nearest-neighbor search can give up
+  min_cell: (i32, i32),
+  max_cell: (i32, i32),
+  len: usize,
+}
+
+impl SpatialHash {
+  /// `cell_size` should be roughly the distance of the queries you plan to make,
+  /// e.g. the collision radius or the expected spacing between points.
+  pub fn new(cell_size: f32) -> Self {
+    SpatialHash {
+      cell_size,
+      cells: HashMap::new(),
+      min_cell: (i32::max_value(), i32::max_value()),
+      max_cell: (i32::min_value(), i32::min_value()),
+      len: 0,
+    }
+  }
+
+  pub fn from_points(points: &[Point2], cell_size: f32) -> Self {
+    let mut hash = Self::new(cell_size);
+    for (i, point) in points.iter().enumerate() {
+      hash.insert(i, *point);
+    }
+    hash
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  pub fn clear(&mut self) {
+    self.cells.clear();
+    self.min_cell = (i32::max_value(), i32::max_value());
+    self.max_cell = (i32::min_value(), i32::min_value());
+    self.len = 0;
+  }
+
+  fn cell(&self, point: Point2) -> (i32, i32) {
+    (
+      (point.x / self.cell_size).floor() as i32,
+      (point.y / self.cell_size).floor() as i32,
+    )
+  }
+
+  pub fn insert(&mut self, index: usize, point: Point2) {
+    let cell = self.cell(point);
+    self.min_cell = (self.min_cell.0.min(cell.0), self.min_cell.1.min(cell.1));
+    self.max_cell = (self.max_cell.0.max(cell.0), self.max_cell.1.max(cell.1));
+    self
+      .cells
+      .entry(cell)
+      .or_insert_with(Vec::new)
+      .push((index, point));
+    self.len += 1;
+  }
+
+  /// Removes the entry for `index`. `point` must be the same point it was inserted with.
+  pub fn remove(&mut self, index: usize, point: Point2) {
+    let cell = self.cell(point);
+    if let Some(entries) = self.cells.get_mut(&cell) {
+      if let Some(position) = entries.iter().position(|(i, _)| *i == index) {
+        entries.swap_remove(position);
+        self.len -= 1;
+      }
+    }
+  }
+
+  /// Indices of all points within `radius` of `point`
+  pub fn within(&self, point: Point2, radius: f32) -> Vec<usize> {
+    let (ci, cj) = self.cell(point);
+    let reach = (radius / self.cell_size).ceil() as i32;
+    let mut found = vec![];
+    for i in (ci - reach)..=(ci + reach) {
+      for j in (cj - reach)..=(cj + reach) {
+        if let Some(entries) = self.cells.get(&(i, j)) {
+          for (index, other) in entries {
+            if other.distance(point) <= radius {
+              found.push(*index);
+            }
+          }
+        }
+      }
+    }
+    found
+  }
+
+  /// True if any point is within `radius` of `point`.
+  /// Cheaper than `within` when you only care about collisions.
+  pub fn any_within(&self, point: Point2, radius: f32) -> bool {
+    let (ci, cj) = self.cell(point);
+    let reach = (radius / self.cell_size).ceil() as i32;
+    for i in (ci - reach)..=(ci + reach) {
+      for j in (cj - reach)..=(cj + reach) {
+        if let Some(entries) = self.cells.get(&(i, j)) {
+          if entries
+            .iter()
+            .any(|(_, other)| other.distance(point) <= radius)
+          {
+            return true;
+          }
+        }
+      }
+    }
+    false
+  }
+
+  /// Index and distance of the closest point, or None if the hash is empty.
+  /// Searches outwards in square "rings" of cells until no closer point could exist.
+  pub fn nearest(&self, point: Point2) -> Option<(usize, f32)> {
+    if self.is_empty() {
+      return None;
+    }
+    let (ci, cj) = self.cell(point);
+    // once the ring is past every occupied cell there is nothing left to find
+    let max_ring = (ci - self.min_cell.0)
+      .abs()
+      .max((self.max_cell.0 - ci).abs())
+      .max((cj - self.min_cell.1).abs())
+      .max((self.max_cell.1 - cj).abs());
+    let mut best: Option<(usize, f32)> = None;
+    for ring in 0..=max_ring {
+      for i in (ci - ring)..=(ci + ring) {
+        for j in (cj - ring)..=(cj + ring) {
+          // only visit the outer edge of the ring, the inside has already been searched
+          if (i - ci).abs() != ring && (j - cj).abs() != ring {
+            continue;
+          }
+          if let Some(entries) = self.cells.get(&(i, j)) {
+            for (index, other) in entries {
+              let distance = other.distance(point);
+              if best.map_or(true, |(_, d)| distance < d) {
+                best = Some((*index, distance));
+              }
+            }
+          }
+        }
+      }
+      // any point in a ring further out is at least `ring * cell_size` away
+      if let Some((_, d)) = best {
+        if d <= ring as f32 * self.cell_size {
+          break;
+        }
+      }
+    }
+    best
+  }
+}
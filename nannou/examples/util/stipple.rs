@@ -0,0 +1,200 @@
+// Weighted Voronoi stippling, as described by Adrian Secord
+// https://www.cs.ubc.ca/labs/imager/tr/2002/secord2002b/secord.2002b.pdf
+//
+// Algorithm in a nutshell:
+// 1. scatter points on the image, denser where the image is darker (rejection sampling)
+// 2. assign every pixel to its nearest point. Each point now "owns" a Voronoi cell of pixels
+// 3. move every point to the darkness-weighted centroid of its cell
+// 4. repeat 2 and 3 until the points settle down (Lloyd's relaxation)
+//
+// Everything is computed in the pixel space of the DensityMap,
+// and only mapped to the window at the very end.
+use nannou::prelude::*;
+
+use super::circle::Circle;
+use super::density::DensityMap;
+use super::spatial::SpatialHash;
+use super::Line2;
+
+pub struct Stippler<'a> {
+  density: &'a DensityMap,
+  n_points: usize,
+  min_radius: f32,
+  max_radius: f32,
+  points: Vec<Point2>,
+  // average darkness of each point's Voronoi cell, used to size the dots
+  tones: Vec<f32>,
+}
+
+impl<'a> Stippler<'a> {
+  pub fn new(density: &'a DensityMap) -> Self {
+    Stippler {
+      density,
+      n_points: 4000,
+      min_radius: 1.0,
+      max_radius: 3.0,
+      points: vec![],
+      tones: vec![],
+    }
+  }
+
+  pub fn points(mut self, n_points: usize) -> Self {
+    self.n_points = n_points;
+    self
+  }
+
+  // radius of the dots, for the lightest and darkest cells respectively
+  pub fn radius(mut self, min_radius: f32, max_radius: f32) -> Self {
+    self.min_radius = min_radius;
+    self.max_radius = max_radius;
+    self
+  }
+
+  /// Runs `iterations` rounds of Lloyd relaxation.
+  pub fn relax(mut self, iterations: usize) -> Self {
+    for n in 0..iterations {
+      println!("Relaxing stipples {} of {} ...", n + 1, iterations);
+      self.step();
+    }
+    self
+  }
+
+  /// A single round of weighted Lloyd relaxation.
+  /// Public so animated sketches can draw the points settling.
+  pub fn step(&mut self) {
+    if self.points.is_empty() {
+      self.points = self.density.sample_points(self.n_points);
+      self.tones = vec![0.0; self.points.len()];
+    }
+    let n = self.points.len();
+    if n == 0 {
+      return;
+    }
+    let (w, h) = (self.density.width, self.density.height);
+    // roughly one point per cell
+    let cell_size = ((w * h) as f32 / n as f32).sqrt().max(1.0);
+    let hash = SpatialHash::from_points(&self.points, cell_size);
+
+    // [sum(x * weight), sum(y * weight), sum(weight), pixel count]
+    let mut sums = vec![[0.0f32; 4]; n];
+    for j in 0..h {
+      for i in 0..w {
+        let weight = self.density.at(i, j);
+        let pixel = pt2(i as f32 + 0.5, j as f32 + 0.5);
+        if let Some((index, _distance)) = hash.nearest(pixel) {
+          let sum = &mut sums[index];
+          sum[0] += pixel.x * weight;
+          sum[1] += pixel.y * weight;
+          sum[2] += weight;
+          sum[3] += 1.0;
+        }
+      }
+    }
+
+    for (index, sum) in sums.iter().enumerate() {
+      // points sitting in pure white have nowhere to go, so leave them be
+      if sum[2] > 0.0 {
+        self.points[index] = pt2(sum[0] / sum[2], sum[1] / sum[2]);
+      }
+      self.tones[index] = if sum[3] > 0.0 { sum[2] / sum[3] } else { 0.0 };
+    }
+  }
+
+  /// The stipples as dots in the window, with radius modulated by darkness.
+  pub fn circles(&self, win: &Rect) -> Vec<Circle> {
+    self
+      .points
+      .iter()
+      .zip(self.tones.iter())
+      .map(|(point, tone)| {
+        let p = self.density.pixel_to_window(*point, win);
+        let radius = map_range(*tone, 0.0, 1.0, self.min_radius, self.max_radius);
+        Circle::new(p.x, p.y, radius)
+      })
+      .collect()
+  }
+
+  /// "TSP art": chains every stipple into a single continuous line for one-stroke plotting.
+  /// The tour is a greedy nearest-neighbor path, cleaned up with `passes` rounds of 2-opt.
+  /// It is not an optimal tour, but 2-opt removes all the self-intersections, which is what the eye notices.
+  pub fn tsp(&self, win: &Rect, passes: usize) -> Line2 {
+    let points: Vec<Point2> = self
+      .points
+      .iter()
+      .map(|p| self.density.pixel_to_window(*p, win))
+      .collect();
+    let mut tour = nearest_neighbor_tour(&points);
+    two_opt(&mut tour, passes);
+    tour
+  }
+}
+
+/// Greedy tour: start at the top-left-most point and always walk to the closest unvisited point.
+pub fn nearest_neighbor_tour(points: &[Point2]) -> Line2 {
+  if points.is_empty() {
+    return vec![];
+  }
+  let spacing = estimate_spacing(points);
+  let mut hash = SpatialHash::from_points(points, spacing);
+  let mut current = points
+    .iter()
+    .enumerate()
+    .min_by(|(_, a), (_, b)| (a.x - a.y).partial_cmp(&(b.x - b.y)).unwrap())
+    .map(|(i, _)| i)
+    .unwrap();
+  let mut tour = Vec::with_capacity(points.len());
+  loop {
+    tour.push(points[current]);
+    hash.remove(current, points[current]);
+    match hash.nearest(points[current]) {
+      Some((next, _distance)) => current = next,
+      None => break,
+    }
+  }
+  tour
+}
+
+/// Repeatedly reverses sections of the tour whenever doing so shortens it.
+/// Each pass is O(n^2), so keep `passes` small for large tours.
+pub fn two_opt(tour: &mut Line2, passes: usize) {
+  let n = tour.len();
+  if n < 4 {
+    return;
+  }
+  for pass in 0..passes {
+    println!("2-opt pass {} of {} ...", pass + 1, passes);
+    let mut improved = false;
+    for i in 0..n - 3 {
+      for j in i + 2..n - 1 {
+        let (a, b, c, d) = (tour[i], tour[i + 1], tour[j], tour[j + 1]);
+        let before = a.distance(b) + c.distance(d);
+        let after = a.distance(c) + b.distance(d);
+        if after < before - 1e-4 {
+          tour[i + 1..=j].reverse();
+          improved = true;
+        }
+      }
+    }
+    if !improved {
+      break;
+    }
+  }
+}
+
+// average distance between points if they were spread evenly over their bounding box
+fn estimate_spacing(points: &[Point2]) -> f32 {
+  let (min, max) = points.iter().fold(
+    (
+      pt2(std::f32::MAX, std::f32::MAX),
+      pt2(std::f32::MIN, std::f32::MIN),
+    ),
+    |(min, max), p| {
+      (
+        pt2(min.x.min(p.x), min.y.min(p.y)),
+        pt2(max.x.max(p.x), max.y.max(p.y)),
+      )
+    },
+  );
+  let area = ((max.x - min.x) * (max.y - min.y)).max(1.0);
+  (area / points.len() as f32).sqrt().max(1.0)
+}
@@ -0,0 +1,327 @@
+// Variable-width strokes as filled outlines.
+// Turns a Line2 and a width function into a single polygon, so a stroke can be
+// filled with draw.polygon(), hatched, clipped with util::polygon, or saved as a filled SVG path
+// for a plotter with a thick pen.
+//
+// The width function gets t in [0, 1], measured by arc length from the start of the line.
+// Only the vertices sample the width, so resample the line first (util::polyline::resample)
+// if the width changes faster than the points do.
+//
+// Joins are only added on the outside of a turn. The inside just uses the miter point,
+// capped so a short segment can't pull it way past the neighboring vertices.
+use nannou::prelude::*;
+
+use super::polyline::{length, point_at};
+use super::Line2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Join {
+  // falls back to a bevel when the miter is longer than limit * width / 2
+  Miter(f32),
+  Round,
+  Bevel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cap {
+  Butt,
+  Round,
+  Square,
+  // the width shrinks to a point over this distance from the end
+  Taper(f32),
+}
+
+pub struct Stroke {
+  width: Box<dyn Fn(f32) -> f32>,
+  join: Join,
+  start_cap: Cap,
+  end_cap: Cap,
+  // max angle between points on round joins and caps, in radians
+  arc_step: f32,
+}
+
+impl Stroke {
+  /// A stroke with constant width
+  pub fn new(width: f32) -> Self {
+    Stroke {
+      width: Box::new(move |_| width),
+      join: Join::Miter(4.0),
+      start_cap: Cap::Butt,
+      end_cap: Cap::Butt,
+      arc_step: deg_to_rad(10.0),
+    }
+  }
+
+  /// Width as a function of t in [0, 1] along the line
+  pub fn width_fn(mut self, width: impl Fn(f32) -> f32 + 'static) -> Self {
+    self.width = Box::new(width);
+    self
+  }
+
+  pub fn join(mut self, join: Join) -> Self {
+    self.join = join;
+    self
+  }
+
+  /// Same cap at both ends
+  pub fn cap(mut self, cap: Cap) -> Self {
+    self.start_cap = cap;
+    self.end_cap = cap;
+    self
+  }
+
+  pub fn start_cap(mut self, cap: Cap) -> Self {
+    self.start_cap = cap;
+    self
+  }
+
+  pub fn end_cap(mut self, cap: Cap) -> Self {
+    self.end_cap = cap;
+    self
+  }
+
+  pub fn arc_step(mut self, radians: f32) -> Self {
+    self.arc_step = radians.max(0.01);
+    self
+  }
+
+  /// The outline of the stroked line as a polygon (without a repeated closing point).
+  /// It goes up the left side of the line, around the end cap, back down the right side
+  /// and around the start cap.
+  pub fn outline(&self, line: &Line2) -> Line2 {
+    let line = self.with_taper_points(&dedupe(line));
+    let n = line.len();
+    if n < 2 {
+      return vec![];
+    }
+    let total = length(&line, false);
+    let mut travelled = 0.0;
+    let half_widths: Vec<f32> = (0..n)
+      .map(|i| {
+        if i > 0 {
+          travelled += line[i - 1].distance(line[i]);
+        }
+        self.half_width(travelled, total)
+      })
+      .collect();
+    let directions: Vec<Vector2> = line
+      .windows(2)
+      .map(|w| (w[1] - w[0]) / w[0].distance(w[1]))
+      .collect();
+
+    let mut left = vec![line[0] + left_normal(directions[0]) * half_widths[0]];
+    let mut right = vec![line[0] - left_normal(directions[0]) * half_widths[0]];
+    for i in 1..n - 1 {
+      let (before, after) = (directions[i - 1], directions[i]);
+      let hw = half_widths[i];
+      // positive for left turns, where the left side is on the inside
+      let turn = before.x * after.y - before.y * after.x;
+      let inner_limit = line[i - 1]
+        .distance(line[i])
+        .min(line[i].distance(line[i + 1]));
+      if turn > 0.0 {
+        left.push(self.inner_join(line[i], before, after, hw, 1.0, inner_limit));
+        right.extend(self.outer_join(line[i], before, after, hw, -1.0));
+      } else {
+        left.extend(self.outer_join(line[i], before, after, hw, 1.0));
+        right.push(self.inner_join(line[i], before, after, hw, -1.0, inner_limit));
+      }
+    }
+    let last = directions[n - 2];
+    left.push(line[n - 1] + left_normal(last) * half_widths[n - 1]);
+    right.push(line[n - 1] - left_normal(last) * half_widths[n - 1]);
+
+    let mut outline = left;
+    outline.extend(self.cap_points(self.end_cap, line[n - 1], last, half_widths[n - 1]));
+    outline.extend(right.into_iter().rev());
+    outline.extend(self.cap_points(self.start_cap, line[0], -directions[0], half_widths[0]));
+    outline
+  }
+
+  fn half_width(&self, distance: f32, total: f32) -> f32 {
+    let t = if total > 0.0 { distance / total } else { 0.0 };
+    let mut half_width = (self.width)(t).max(0.0) / 2.0;
+    // taper with a quarter circle, so the tip is pointy but the stroke doesn't look pinched
+    if let Cap::Taper(taper) = self.start_cap {
+      half_width *= taper_factor(distance / taper.max(1e-6));
+    }
+    if let Cap::Taper(taper) = self.end_cap {
+      half_width *= taper_factor((total - distance) / taper.max(1e-6));
+    }
+    half_width
+  }
+
+  // tapers need a vertex where they finish, or a long first segment would taper along its whole length
+  fn with_taper_points(&self, line: &Line2) -> Line2 {
+    let total = length(line, false);
+    let mut line = line.clone();
+    if let Cap::Taper(taper) = self.start_cap {
+      line = insert_at_distance(&line, taper);
+      // a few extra points so the curve of the taper shows
+      for i in 1..4 {
+        line = insert_at_distance(&line, taper * i as f32 / 4.0);
+      }
+    }
+    if let Cap::Taper(taper) = self.end_cap {
+      line = insert_at_distance(&line, total - taper);
+      for i in 1..4 {
+        line = insert_at_distance(&line, total - taper * i as f32 / 4.0);
+      }
+    }
+    line
+  }
+
+  // side is 1.0 for the left side and -1.0 for the right
+  fn outer_join(
+    &self,
+    point: Point2,
+    before: Vector2,
+    after: Vector2,
+    half_width: f32,
+    side: f32,
+  ) -> Line2 {
+    let from = point + left_normal(before) * half_width * side;
+    let to = point + left_normal(after) * half_width * side;
+    match self.join {
+      Join::Bevel => vec![from, to],
+      Join::Round => {
+        let start = (from.y - point.y).atan2(from.x - point.x);
+        let mut sweep = (to.y - point.y).atan2(to.x - point.x) - start;
+        // outer joins always go the short way round
+        if sweep > PI {
+          sweep -= 2.0 * PI;
+        } else if sweep < -PI {
+          sweep += 2.0 * PI;
+        }
+        arc(point, half_width, start, sweep, self.arc_step)
+      }
+      Join::Miter(limit) => {
+        let bisector = left_normal(before) + left_normal(after);
+        let cos = bisector.magnitude() / 2.0;
+        if cos < 1e-6 || 1.0 / cos > limit {
+          vec![from, to]
+        } else {
+          vec![point + bisector.normalize() * side * half_width / cos]
+        }
+      }
+    }
+  }
+
+  fn inner_join(
+    &self,
+    point: Point2,
+    before: Vector2,
+    after: Vector2,
+    half_width: f32,
+    side: f32,
+    limit: f32,
+  ) -> Point2 {
+    let bisector = left_normal(before) + left_normal(after);
+    let cos = bisector.magnitude() / 2.0;
+    if cos < 1e-6 {
+      // the line doubles back on itself
+      return point + left_normal(before) * half_width * side;
+    }
+    let miter = (half_width / cos).min((half_width * half_width + limit * limit).sqrt());
+    point + bisector.normalize() * side * miter
+  }
+
+  // points around the cap at `point`, going from the left side to the right side.
+  // `direction` points out of the line
+  fn cap_points(&self, cap: Cap, point: Point2, direction: Vector2, half_width: f32) -> Line2 {
+    let normal = left_normal(direction);
+    match cap {
+      Cap::Butt | Cap::Taper(_) => vec![],
+      Cap::Square => vec![
+        point + (normal + direction) * half_width,
+        point + (direction - normal) * half_width,
+      ],
+      Cap::Round => {
+        let start = normal.y.atan2(normal.x);
+        let mut arc = arc(point, half_width, start, -PI, self.arc_step);
+        // the end points are already on the sides
+        arc.pop();
+        arc.remove(0);
+        arc
+      }
+    }
+  }
+}
+
+// implement Debug custom because otherwise
+// the width function can't be printed
+impl std::fmt::Debug for Stroke {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Stroke")
+      .field("join", &self.join)
+      .field("start_cap", &self.start_cap)
+      .field("end_cap", &self.end_cap)
+      .field("arc_step", &self.arc_step)
+      .finish()
+  }
+}
+
+fn left_normal(direction: Vector2) -> Vector2 {
+  vec2(-direction.y, direction.x)
+}
+
+fn taper_factor(x: f32) -> f32 {
+  let x = x.max(0.0).min(1.0);
+  (1.0 - (1.0 - x) * (1.0 - x)).sqrt()
+}
+
+// `sweep` is signed, positive is counter-clockwise
+fn arc(center: Point2, radius: f32, start: f32, sweep: f32, step: f32) -> Line2 {
+  let n = (sweep.abs() / step).ceil().max(1.0) as usize;
+  (0..=n)
+    .map(|i| {
+      let angle = start + sweep * i as f32 / n as f32;
+      center + vec2(angle.cos(), angle.sin()) * radius
+    })
+    .collect()
+}
+
+// consecutive duplicate points have no direction
+fn dedupe(line: &Line2) -> Line2 {
+  let mut deduped: Line2 = Vec::with_capacity(line.len());
+  for p in line {
+    if deduped.last().map_or(true, |last| last.distance(*p) > 1e-4) {
+      deduped.push(*p);
+    }
+  }
+  deduped
+}
+
+// splits whichever segment contains the point `distance` along the line
+fn insert_at_distance(line: &Line2, distance: f32) -> Line2 {
+  if distance <= 0.0 || distance >= length(line, false) {
+    return line.clone();
+  }
+  let point = point_at(line, distance, false);
+  let mut travelled = 0.0;
+  let mut inserted = line.clone();
+  for i in 0..line.len() - 1 {
+    let d = line[i].distance(line[i + 1]);
+    if travelled + d > distance {
+      if point.distance(line[i]) > 1e-4 && point.distance(line[i + 1]) > 1e-4 {
+        inserted.insert(i + 1, point);
+      }
+      break;
+    }
+    travelled += d;
+  }
+  inserted
+}
+
+/// SVG path data for a filled outline, e.g. `<path d="..." fill="black" />`.
+/// Converts from window coordinates (y up, origin in the middle of `win`) to SVG coordinates
+pub fn svg_path(outline: &Line2, win: &Rect) -> String {
+  let points: Vec<String> = outline
+    .iter()
+    .map(|p| format!("{:.2},{:.2}", p.x - win.left(), win.top() - p.y))
+    .collect();
+  if points.is_empty() {
+    return String::new();
+  }
+  format!("M{} L{} Z", points[0], points[1..].join(" "))
+}
@@ -0,0 +1,348 @@
+// Procedural paper and canvas textures.
+// A texture is a height map in [0, 1] (1 is a peak of the paper) plus a paper color,
+// and can be composited under or over a drawing.
+//
+// Every texture tiles: the noise is sampled on a 4D torus
+// (x and y each go round a circle, see https://www.redblobgames.com/maps/terrain-from-noise/ ),
+// and the regular patterns always fit a whole number of periods into the tile.
+// That also makes the texture statistically the same everywhere in the frame,
+// unlike the draw_paper_texture lines which bunch up where they cross.
+//
+// Kinds
+// - grain: cold-press watercolor paper, bumpy tooth at `scale` pixels
+// - wove: smooth machine-made paper, soft mottling and very fine tooth
+// - laid: handmade paper with close horizontal laid lines and sparse vertical chain lines
+// - canvas: over/under weave of threads `scale` pixels wide
+// - fiber: short overlapping fibers on top of a wove base
+use nannou::noise::{NoiseFn, Perlin, Seedable};
+use nannou::prelude::*;
+
+use super::density::DensityMap;
+use super::raster::{BlendMode, Raster};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureKind {
+  Grain,
+  Wove,
+  Laid,
+  Canvas,
+  Fiber,
+}
+
+pub const TEXTURES: [&str; 5] = ["grain", "wove", "laid", "canvas", "fiber"];
+
+/// Looks up a texture by name, see TEXTURES
+pub fn texture_kind(name: &str) -> Option<TextureKind> {
+  match name {
+    "grain" => Some(TextureKind::Grain),
+    "wove" => Some(TextureKind::Wove),
+    "laid" => Some(TextureKind::Laid),
+    "canvas" => Some(TextureKind::Canvas),
+    "fiber" => Some(TextureKind::Fiber),
+    _ => None,
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Texture {
+  kind: TextureKind,
+  width: usize,
+  height: usize,
+  seed: u32,
+  // size of the main features in pixels: bumps, laid line spacing, thread width, fiber length
+  scale: f32,
+  // how dark the valleys get when shading, 0 is flat
+  contrast: f32,
+  color: [f32; 3],
+}
+
+impl Texture {
+  pub fn new(kind: TextureKind, width: usize, height: usize) -> Self {
+    Texture {
+      kind,
+      width,
+      height,
+      seed: 0,
+      scale: match kind {
+        TextureKind::Grain => 6.0,
+        TextureKind::Wove => 40.0,
+        TextureKind::Laid => 4.0,
+        TextureKind::Canvas => 5.0,
+        TextureKind::Fiber => 24.0,
+      },
+      contrast: 0.15,
+      color: [0.97, 0.95, 0.9],
+    }
+  }
+
+  pub fn seed(mut self, seed: u32) -> Self {
+    self.seed = seed;
+    self
+  }
+
+  pub fn scale(mut self, scale: f32) -> Self {
+    self.scale = scale.max(1.0);
+    self
+  }
+
+  pub fn contrast(mut self, contrast: f32) -> Self {
+    self.contrast = contrast.max(0.0).min(1.0);
+    self
+  }
+
+  /// Color of the paper at its peaks, rgb in [0, 1]
+  pub fn color(mut self, rgb: [f32; 3]) -> Self {
+    self.color = rgb;
+    self
+  }
+
+  pub fn generate(&self) -> Paper {
+    let perlin = Perlin::new().set_seed(self.seed);
+    let heights = match self.kind {
+      TextureKind::Grain => self.grain(&perlin),
+      TextureKind::Wove => self.wove(&perlin),
+      TextureKind::Laid => self.laid(&perlin),
+      TextureKind::Canvas => self.canvas(&perlin),
+      TextureKind::Fiber => self.fiber(&perlin),
+    };
+    Paper {
+      heights: normalize(heights),
+      contrast: self.contrast,
+      color: self.color,
+    }
+  }
+
+  // number of whole periods of a feature `size` pixels across, over `length` pixels
+  fn periods(&self, length: usize, size: f32) -> f64 {
+    (length as f32 / size).round().max(1.0) as f64
+  }
+
+  // like periods, but always even, for patterns that alternate every period
+  fn even_periods(&self, length: usize, size: f32) -> f64 {
+    (length as f32 / size / 2.0).round().max(1.0) as f64 * 2.0
+  }
+
+  // fractal noise that tiles, with `periods` bumps across the tile at the lowest octave
+  fn tiled_fbm(&self, perlin: &Perlin, i: usize, j: usize, size: f32, octaves: usize) -> f32 {
+    let (px, py) = (
+      self.periods(self.width, size),
+      self.periods(self.height, size),
+    );
+    let (u, v) = (i as f64 / self.width as f64, j as f64 / self.height as f64);
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut norm = 0.0;
+    for octave in 0..octaves {
+      // whole-number frequency multiples keep every octave tileable
+      let f = (1 << octave) as f64;
+      total += amplitude * torus_noise(perlin, u, v, px * f, py * f, octave as f64 * 17.0);
+      norm += amplitude;
+      amplitude *= 0.5;
+    }
+    (total / norm) as f32
+  }
+
+  fn grain(&self, perlin: &Perlin) -> DensityMap {
+    DensityMap::from_fn(self.width, self.height, |i, j| {
+      let tooth = self.tiled_fbm(perlin, i, j, self.scale, 3);
+      // rounded bumps with sharper valleys, like pressed cotton
+      1.0 - tooth.abs() + self.tiled_fbm(perlin, i, j, self.scale * 8.0, 2) * 0.3
+    })
+  }
+
+  fn wove(&self, perlin: &Perlin) -> DensityMap {
+    DensityMap::from_fn(self.width, self.height, |i, j| {
+      self.tiled_fbm(perlin, i, j, self.scale, 3) * 0.7 + self.tiled_fbm(perlin, i, j, 2.0, 1) * 0.3
+    })
+  }
+
+  fn laid(&self, perlin: &Perlin) -> DensityMap {
+    let rows = self.periods(self.height, self.scale) as f32;
+    // chain lines are much further apart than the laid lines
+    let chains = self.periods(self.width, self.scale * 25.0) as f32;
+    DensityMap::from_fn(self.width, self.height, |i, j| {
+      let (u, v) = (i as f32 / self.width as f32, j as f32 / self.height as f32);
+      // the laid lines wander a little, they were wires on a hand mould
+      let wobble = self.tiled_fbm(perlin, i, j, self.scale * 30.0, 1) * 0.3;
+      let laid = (((v * rows + wobble) * 2.0 * PI).sin() * 0.5 + 0.5).powi(2);
+      let chain_distance = ((u * chains).fract() - 0.5).abs() * self.width as f32 / chains;
+      let chain = (-(chain_distance / (self.scale * 0.6)).powi(2)).exp();
+      laid * 0.5 - chain * 0.4 + self.tiled_fbm(perlin, i, j, self.scale * 0.7, 2) * 0.3
+    })
+  }
+
+  fn canvas(&self, perlin: &Perlin) -> DensityMap {
+    // the over/under pattern repeats every 2 threads, so an odd count would leave a seam at the tile edge
+    let columns = self.even_periods(self.width, self.scale) as f32;
+    let rows = self.even_periods(self.height, self.scale) as f32;
+    DensityMap::from_fn(self.width, self.height, |i, j| {
+      let (x, y) = (
+        i as f32 / self.width as f32 * columns,
+        j as f32 / self.height as f32 * rows,
+      );
+      // round thread profile across the thread, and a bump where it goes over the crossing thread
+      let across_warp = (x.fract() * PI).sin();
+      let across_weft = (y.fract() * PI).sin();
+      let along_warp = (y.fract() * PI).sin();
+      let along_weft = (x.fract() * PI).sin();
+      let warp_on_top = (x.floor() as i64 + y.floor() as i64) % 2 == 0;
+      let weave = if warp_on_top {
+        across_warp.sqrt() * (0.6 + 0.4 * along_warp)
+      } else {
+        across_weft.sqrt() * (0.6 + 0.4 * along_weft)
+      };
+      // threads are never perfectly even
+      weave * 0.8 + self.tiled_fbm(perlin, i, j, self.scale * 4.0, 2) * 0.2
+    })
+  }
+
+  fn fiber(&self, perlin: &Perlin) -> DensityMap {
+    let (w, h) = (self.width, self.height);
+    let mut fibers = vec![0.0f32; w * h];
+    let n = (w * h) as f32 / (self.scale * self.scale) * 12.0;
+    for k in 0..n as usize {
+      // uniform positions from a hash of the seed, so the same seed makes the same paper
+      let mut x = random_from(self.seed, k * 4) * w as f32;
+      let mut y = random_from(self.seed, k * 4 + 1) * h as f32;
+      let mut angle = random_from(self.seed, k * 4 + 2) * 2.0 * PI;
+      let length = self.scale * (0.5 + random_from(self.seed, k * 4 + 3));
+      let bend = (random_from(self.seed.wrapping_add(1), k) - 0.5) * 0.2;
+      for _ in 0..length as usize {
+        // fibers wrap around the edges so the texture still tiles
+        let i = (x.floor() as i64).rem_euclid(w as i64) as usize;
+        let j = (y.floor() as i64).rem_euclid(h as i64) as usize;
+        fibers[j * w + i] += 1.0;
+        x += angle.cos();
+        y += angle.sin();
+        angle += bend;
+      }
+    }
+    DensityMap::from_fn(w, h, |i, j| {
+      // overlapping fibers pile up, but not linearly
+      let pile = 1.0 - (-fibers[j * w + i] * 0.7).exp();
+      pile * 0.5 + self.tiled_fbm(perlin, i, j, self.scale * 2.0, 3) * 0.5
+    })
+  }
+}
+
+// `periods_x` and `periods_y` must be whole numbers for the noise to tile
+fn torus_noise(perlin: &Perlin, u: f64, v: f64, periods_x: f64, periods_y: f64, z: f64) -> f64 {
+  let (a, b) = (
+    u * 2.0 * std::f64::consts::PI,
+    v * 2.0 * std::f64::consts::PI,
+  );
+  // the radius sets how many bumps fit around the circle
+  let (rx, ry) = (
+    periods_x / (2.0 * std::f64::consts::PI),
+    periods_y / (2.0 * std::f64::consts::PI),
+  );
+  perlin.get([rx * a.cos() + z, rx * a.sin(), ry * b.cos(), ry * b.sin()])
+}
+
+/// Deterministic value in [0, 1) for the nth draw from a seed (splitmix64)
+pub fn random_from(seed: u32, n: usize) -> f32 {
+  let mut z = (seed as u64) << 32 ^ n as u64;
+  z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+  z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+  z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+  z ^= z >> 31;
+  (z >> 40) as f32 / (1u64 << 24) as f32
+}
+
+// stretch the heights to fill [0, 1]
+fn normalize(map: DensityMap) -> DensityMap {
+  let mut min = std::f32::MAX;
+  let mut max = std::f32::MIN;
+  for j in 0..map.height {
+    for i in 0..map.width {
+      min = min.min(map.at(i, j));
+      max = max.max(map.at(i, j));
+    }
+  }
+  let range = (max - min).max(1e-6);
+  DensityMap::from_fn(map.width, map.height, |i, j| (map.at(i, j) - min) / range)
+}
+
+#[derive(Debug, Clone)]
+pub struct Paper {
+  pub heights: DensityMap,
+  pub contrast: f32,
+  pub color: [f32; 3],
+}
+
+impl Paper {
+  /// Height at a raster pixel, tiling the texture when the raster is bigger than it
+  pub fn height(&self, i: usize, j: usize) -> f32 {
+    self
+      .heights
+      .at(i % self.heights.width, j % self.heights.height)
+  }
+
+  // 1 on the peaks, 1 - contrast in the deepest valleys
+  fn shade(&self, i: usize, j: usize) -> f32 {
+    1.0 - self.contrast * (1.0 - self.height(i, j))
+  }
+
+  /// The paper on its own, opaque, for putting under a drawing.
+  /// Tiles the texture to fill `width` x `height`
+  pub fn colors(&self, width: usize, height: usize) -> Raster {
+    let [r, g, b] = self.color;
+    let mut raster = Raster::new(width, height);
+    for j in 0..height {
+      for i in 0..width {
+        let s = self.shade(i, j);
+        raster.set(i, j, [r * s, g * s, b * s, 1.0]);
+      }
+    }
+    raster
+  }
+
+  /// Just the light and shadow of the paper in gray, 1 on the peaks,
+  /// for multiplying or overlaying onto a drawing that already has its own background
+  pub fn shading(&self, width: usize, height: usize) -> Raster {
+    let mut raster = Raster::new(width, height);
+    for j in 0..height {
+      for i in 0..width {
+        let s = self.shade(i, j);
+        raster.set(i, j, [s, s, s, 1.0]);
+      }
+    }
+    raster
+  }
+
+  /// Puts the paper's texture into a raster that already has the drawing in it.
+  /// Multiply darkens the valleys, overlay also keeps the highlights.
+  /// Overlay is centered on mid gray, so it needs a bit more contrast to show
+  pub fn apply(&self, raster: &mut Raster, mode: BlendMode, opacity: f32) {
+    let mut shading = Raster::new(raster.width, raster.height);
+    for j in 0..raster.height {
+      for i in 0..raster.width {
+        let s = match mode {
+          // centered on mid gray, otherwise overlay would only ever lighten
+          BlendMode::Overlay => 0.5 + (self.height(i, j) - 0.5) * self.contrast,
+          _ => self.shade(i, j),
+        };
+        shading.set(i, j, [s, s, s, 1.0]);
+      }
+    }
+    raster.composite(&shading, mode, opacity);
+  }
+
+  /// Draws the paper color under a nannou drawing
+  pub fn draw_under(&self, app: &App, draw: &Draw, win: &Rect) {
+    self
+      .colors(self.heights.width, self.heights.height)
+      .draw(app, draw, win);
+  }
+
+  /// Multiplies the paper texture over a nannou drawing.
+  /// Multiplying by a gray value is the same as drawing black with alpha 1 - gray,
+  /// so this works with plain alpha blending
+  pub fn draw_over(&self, app: &App, draw: &Draw, win: &Rect, opacity: f32) {
+    let (width, height) = (self.heights.width, self.heights.height);
+    Raster::from_fn(width, height, |i, j| {
+      [0.0, 0.0, 0.0, (1.0 - self.shade(i, j)) * opacity]
+    })
+    .draw(app, draw, win);
+  }
+}
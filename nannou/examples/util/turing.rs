@@ -0,0 +1,220 @@
+// Multi-scale Turing patterns, as described by Jonathan McCabe
+// http://www.jonathanmccabe.com/Cyclic_Symmetric_Multi-Scale_Turing_Patterns.pdf
+// https://softologyblog.wordpress.com/2011/07/05/multi-scale-turing-patterns/
+//
+// Algorithm in a nutshell:
+// 1. fill the grid with random values in [-1, 1]
+// 2. for each scale, blur the grid with a small radius (the "activator") and a large radius (the "inhibitor")
+// 3. for each cell, find the scale where activator and inhibitor are closest together
+// 4. nudge the cell up by that scale's amount if the activator is bigger, down otherwise
+// 5. normalize the grid back into [-1, 1] and repeat
+//
+// Each scale makes its own Turing pattern, and they all compete, so big patterns end up made of smaller patterns.
+extern crate rayon;
+
+use self::rayon::prelude::*;
+
+use super::reaction_diffusion::{offset_index, Boundary};
+use super::rng::random_range;
+use super::simulation::Simulation;
+
+#[derive(Debug, Copy, Clone)]
+pub struct TuringScale {
+  // blur radii in cells. The inhibitor should be bigger than the activator.
+  pub activator: usize,
+  pub inhibitor: usize,
+  // how much a cell changes per step when this scale wins
+  pub amount: f32,
+}
+
+impl TuringScale {
+  pub fn new(activator: usize, inhibitor: usize, amount: f32) -> Self {
+    TuringScale {
+      activator,
+      inhibitor,
+      amount,
+    }
+  }
+}
+
+pub struct MultiScaleTuring {
+  pub width: usize,
+  pub height: usize,
+  values: Vec<f32>,
+  scales: Vec<TuringScale>,
+  boundary: Boundary,
+  iterations: usize,
+}
+
+impl MultiScaleTuring {
+  /// Starts with random values and five scales sized relative to the grid
+  pub fn new(width: usize, height: usize) -> Self {
+    let size = width.min(height) as f32;
+    // McCabe's defaults were for roughly 1000px images, so scale them to the grid
+    let scale = |activator: f32, inhibitor: f32, amount: f32| {
+      let activator = ((activator * size / 1000.0).round() as usize).max(1);
+      // a scale with equal radii would always win, so keep the inhibitor bigger
+      let inhibitor = ((inhibitor * size / 1000.0).round() as usize).max(activator + 1);
+      TuringScale::new(activator, inhibitor, amount)
+    };
+    let scales = vec![
+      scale(100.0, 200.0, 0.05),
+      scale(50.0, 100.0, 0.04),
+      scale(20.0, 40.0, 0.03),
+      scale(10.0, 20.0, 0.02),
+      scale(5.0, 10.0, 0.01),
+    ];
+    MultiScaleTuring {
+      width,
+      height,
+      values: (0..width * height)
+        .map(|_| random_range(-1.0, 1.0))
+        .collect(),
+      scales,
+      boundary: Boundary::Wrap,
+      iterations: 0,
+    }
+  }
+
+  pub fn scales(mut self, scales: Vec<TuringScale>) -> Self {
+    self.scales = scales;
+    self
+  }
+
+  pub fn boundary(mut self, boundary: Boundary) -> Self {
+    self.boundary = boundary;
+    self
+  }
+
+  /// The raw value of a cell, in [-1, 1]
+  pub fn at(&self, i: usize, j: usize) -> f32 {
+    self.values[j * self.width + i]
+  }
+
+  pub fn iterations(&self) -> usize {
+    self.iterations
+  }
+
+  // Box blur, done as a horizontal pass and a vertical pass.
+  // The vertical pass is just the horizontal pass on the transposed grid.
+  fn blur(&self, values: &[f32], radius: usize) -> Vec<f32> {
+    let (w, h) = (self.width, self.height);
+    let horizontal = blur_rows(values, w, radius, self.boundary);
+    let vertical = blur_rows(&transpose(&horizontal, w, h), h, radius, self.boundary);
+    transpose(&vertical, h, w)
+  }
+}
+
+impl Simulation for MultiScaleTuring {
+  fn width(&self) -> usize {
+    self.width
+  }
+
+  fn height(&self) -> usize {
+    self.height
+  }
+
+  fn step(&mut self) {
+    if self.scales.is_empty() {
+      return;
+    }
+    // (activator, inhibitor) for each scale
+    let blurred: Vec<(Vec<f32>, Vec<f32>)> = self
+      .scales
+      .iter()
+      .map(|scale| {
+        (
+          self.blur(&self.values, scale.activator),
+          self.blur(&self.values, scale.inhibitor),
+        )
+      })
+      .collect();
+
+    let scales = &self.scales;
+    self
+      .values
+      .par_iter_mut()
+      .enumerate()
+      .for_each(|(index, value)| {
+        let (best, (activator, inhibitor)) = blurred
+          .iter()
+          .enumerate()
+          .min_by(|(_, (a1, i1)), (_, (a2, i2))| {
+            (a1[index] - i1[index])
+              .abs()
+              .partial_cmp(&(a2[index] - i2[index]).abs())
+              .unwrap()
+          })
+          .unwrap();
+        if activator[index] > inhibitor[index] {
+          *value += scales[best].amount;
+        } else {
+          *value -= scales[best].amount;
+        }
+      });
+
+    // normalize back into [-1, 1]
+    let (min, max) = self
+      .values
+      .iter()
+      .fold((std::f32::MAX, std::f32::MIN), |(min, max), v| {
+        (min.min(*v), max.max(*v))
+      });
+    if max - min > std::f32::EPSILON {
+      for value in self.values.iter_mut() {
+        *value = (*value - min) / (max - min) * 2.0 - 1.0;
+      }
+    }
+    self.iterations += 1;
+  }
+
+  fn sample(&self, i: usize, j: usize) -> f32 {
+    (self.at(i, j) + 1.0) / 2.0
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every cell
+impl std::fmt::Debug for MultiScaleTuring {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("MultiScaleTuring")
+      .field("width", &self.width)
+      .field("height", &self.height)
+      .field("scales", &self.scales)
+      .field("boundary", &self.boundary)
+      .field("iterations", &self.iterations)
+      .finish()
+  }
+}
+
+// average of the cells within `radius` along each row, using a running sum
+// so the cost doesn't depend on the radius
+fn blur_rows(values: &[f32], width: usize, radius: usize, boundary: Boundary) -> Vec<f32> {
+  let mut blurred = vec![0.0; values.len()];
+  let r = radius as isize;
+  let n = (2 * radius + 1) as f32;
+  blurred
+    .par_chunks_mut(width)
+    .zip(values.par_chunks(width))
+    .for_each(|(out, row)| {
+      let mut sum: f32 = (-r..=r)
+        .map(|offset| row[offset_index(0, offset, width, boundary)])
+        .sum();
+      for i in 0..width {
+        out[i] = sum / n;
+        sum += row[offset_index(i, r + 1, width, boundary)];
+        sum -= row[offset_index(i, -r, width, boundary)];
+      }
+    });
+  blurred
+}
+
+fn transpose(values: &[f32], width: usize, height: usize) -> Vec<f32> {
+  let mut transposed = vec![0.0; values.len()];
+  for j in 0..height {
+    for i in 0..width {
+      transposed[i * height + j] = values[j * width + i];
+    }
+  }
+  transposed
+}
@@ -1,4 +1,5 @@
 // Random walker implementations
+use super::rng::random_f32;
 use super::Line2;
 use nannou::prelude::*;
 
@@ -0,0 +1,413 @@
+// Watercolor washes, generalized from the watercolor1/2/3 sketches.
+// Same technique as described by Tyler Hobbs:
+// https://tylerxhobbs.com/essays/2017/a-generative-approach-to-simulating-watercolor-paints
+//
+// 1. The base polygon is deformed a few times. Every vertex carries a variance,
+//    which says how far the edge after it can wander, and new vertices inherit it (with a bit of jitter)
+//    so some sides stay crisp while others bleed.
+// 2. Each layer deforms the deformed base again, and gets drawn with a very low alpha.
+//    Where the layers disagree the edge goes soft, where they agree the color builds up.
+//
+// On top of that, when painting into a Raster:
+// - texture masking: every layer has blotches of noise cut out of it, so the inside of the wash is uneven
+// - edge darkening: pigment collects at the rim of each layer, like paint drying from the outside in
+// - granulation: pigment settles into the valleys of the paper
+//
+// Drawing straight to a Draw gets the layers and edge darkening,
+// masking and granulation are per-pixel so those need the raster.
+use nannou::noise::{NoiseFn, Perlin, Seedable};
+use nannou::prelude::*;
+
+use super::polygon::{bounds, close_ring, open_ring};
+use super::raster::BlendMode;
+use super::rng::{random_f32, random_range};
+use super::{Line2, Paper, Raster};
+
+pub struct Watercolor {
+  base: Line2,
+  variances: Vec<f32>,
+  color: [f32; 3],
+  alpha: f32,
+  layers: usize,
+  base_depth: usize,
+  layer_depth: usize,
+  mask: f32,
+  mask_scale: f32,
+  edge_darkening: f32,
+  edge_width: f32,
+  granulation: f32,
+  paper: Option<Paper>,
+  blend_mode: BlendMode,
+  noise: Perlin,
+}
+
+impl Watercolor {
+  /// Any polygon works as a base: a Blob's or Hexagon's points, a circle, a glyph outline.
+  /// A repeated closing point is fine
+  pub fn new(polygon: &Line2) -> Self {
+    let base = open_ring(polygon);
+    Watercolor {
+      variances: vec![0.5; base.len()],
+      base,
+      color: [0.2, 0.3, 0.6],
+      alpha: 0.04,
+      layers: 30,
+      base_depth: 3,
+      layer_depth: 3,
+      mask: 0.3,
+      mask_scale: 40.0,
+      edge_darkening: 0.6,
+      edge_width: 4.0,
+      granulation: 0.4,
+      paper: None,
+      blend_mode: BlendMode::Normal,
+      noise: Perlin::new(),
+    }
+  }
+
+  /// Same variance for every vertex.
+  /// Variance is relative to the edge length, 0.5 is already pretty wobbly
+  pub fn variance(mut self, variance: f32) -> Self {
+    self.variances = vec![variance; self.base.len()];
+    self
+  }
+
+  /// One variance per vertex of the base polygon
+  pub fn variances(mut self, variances: Vec<f32>) -> Self {
+    assert_eq!(
+      variances.len(),
+      self.base.len(),
+      "Watercolor needs one variance per vertex"
+    );
+    self.variances = variances;
+    self
+  }
+
+  /// Variance as a function of where the vertex is, e.g. from noise or a DensityMap
+  pub fn variance_fn(mut self, variance: impl Fn(Point2) -> f32) -> Self {
+    self.variances = self.base.iter().map(|p| variance(*p)).collect();
+    self
+  }
+
+  pub fn color(mut self, rgb: [f32; 3]) -> Self {
+    self.color = rgb;
+    self
+  }
+
+  /// Alpha of a single layer
+  pub fn alpha(mut self, alpha: f32) -> Self {
+    self.alpha = alpha;
+    self
+  }
+
+  pub fn layers(mut self, layers: usize) -> Self {
+    self.layers = layers;
+    self
+  }
+
+  /// How many times the base polygon is deformed before layering.
+  /// Each round doubles the number of vertices
+  pub fn base_depth(mut self, depth: usize) -> Self {
+    self.base_depth = depth;
+    self
+  }
+
+  /// How many more times each layer is deformed
+  pub fn layer_depth(mut self, depth: usize) -> Self {
+    self.layer_depth = depth;
+    self
+  }
+
+  /// Roughly the fraction of each layer that gets masked out, 0 turns masking off
+  pub fn mask(mut self, mask: f32) -> Self {
+    self.mask = mask.max(0.0).min(1.0);
+    self
+  }
+
+  /// Size of the mask blotches, in pixels
+  pub fn mask_scale(mut self, scale: f32) -> Self {
+    self.mask_scale = scale.max(1.0);
+    self
+  }
+
+  /// How much darker the rim of a layer is than its middle, 0 turns it off
+  pub fn edge_darkening(mut self, amount: f32) -> Self {
+    self.edge_darkening = amount.max(0.0);
+    self
+  }
+
+  /// Width of the dark rim, in pixels
+  pub fn edge_width(mut self, width: f32) -> Self {
+    self.edge_width = width.max(1.0);
+    self
+  }
+
+  /// 0 is an even wash, 1 leaves the peaks of the paper almost bare
+  pub fn granulation(mut self, amount: f32) -> Self {
+    self.granulation = amount.max(0.0).min(1.0);
+    self
+  }
+
+  /// Paper for the pigment to settle into.
+  /// Without one, granulation uses fine noise
+  pub fn paper(mut self, paper: Paper) -> Self {
+    self.paper = Some(paper);
+    self
+  }
+
+  /// How the finished wash is composited onto the raster.
+  /// Multiply is closer to how transparent paint glazes over other colors
+  pub fn blend_mode(mut self, mode: BlendMode) -> Self {
+    self.blend_mode = mode;
+    self
+  }
+
+  pub fn seed(mut self, seed: u32) -> Self {
+    self.noise = self.noise.set_seed(seed);
+    self
+  }
+
+  /// Every layer of the wash as a polygon (without a repeated closing point).
+  /// Random on every call, so hold on to the result to draw the same wash twice
+  pub fn shapes(&self) -> Vec<Line2> {
+    let mut base: Vec<(Point2, f32)> = self
+      .base
+      .iter()
+      .cloned()
+      .zip(self.variances.iter().cloned())
+      .collect();
+    for _ in 0..self.base_depth {
+      base = deform(&base);
+    }
+    (0..self.layers)
+      .map(|_| {
+        let mut layer = base.clone();
+        for _ in 0..self.layer_depth {
+          layer = deform(&layer);
+        }
+        layer.into_iter().map(|(p, _)| p).collect()
+      })
+      .collect()
+  }
+
+  /// Draws the layers as transparent polygons, with a darker outline for edge darkening
+  pub fn draw(&self, draw: &Draw) {
+    let [r, g, b] = self.color;
+    for shape in self.shapes() {
+      draw
+        .polygon()
+        .rgba(r, g, b, self.alpha)
+        .points(shape.clone());
+      if self.edge_darkening > 0.0 {
+        draw
+          .polyline()
+          .weight(self.edge_width)
+          .rgba(r, g, b, self.alpha * self.edge_darkening)
+          .points(close_ring(&shape));
+      }
+    }
+  }
+
+  /// Paints the wash into `raster`, which is stretched over `win`
+  pub fn paint(&self, raster: &mut Raster, win: &Rect) {
+    let shapes: Vec<Line2> = self
+      .shapes()
+      .iter()
+      .map(|shape| {
+        shape
+          .iter()
+          .map(|p| raster.window_to_pixel(*p, win))
+          .collect()
+      })
+      .collect();
+    if shapes.is_empty() {
+      return;
+    }
+    // only work on the pixels the wash can reach, plus room for the blur
+    let mut all = vec![];
+    for shape in &shapes {
+      all.extend(shape.iter().cloned());
+    }
+    let area = bounds(&all).pad(-self.edge_width - 1.0);
+    let i0 = area.left().floor().max(0.0) as usize;
+    let j0 = area.bottom().floor().max(0.0) as usize;
+    let i1 = (area.right().ceil().max(0.0) as usize).min(raster.width);
+    let j1 = (area.top().ceil().max(0.0) as usize).min(raster.height);
+    if i1 <= i0 || j1 <= j0 {
+      return;
+    }
+    let (w, h) = (i1 - i0, j1 - j0);
+
+    // optical density, which adds up across layers where alpha wouldn't
+    let mut density = vec![0.0; w * h];
+    let radius = self.edge_width.round().max(1.0) as usize;
+    for (n, shape) in shapes.iter().enumerate() {
+      let coverage = fill(shape, i0, j0, w, h);
+      let blurred = box_blur(&coverage, w, h, radius);
+      for j in 0..h {
+        for i in 0..w {
+          let index = j * w + i;
+          if coverage[index] <= 0.0 {
+            continue;
+          }
+          // the blurred coverage drops to about half at the boundary
+          let edge = ((1.0 - blurred[index]) * 2.0).min(1.0);
+          let alpha = self.alpha
+            * (1.0 + self.edge_darkening * edge)
+            * self.mask_at((i0 + i) as f32, (j0 + j) as f32, n);
+          density[index] -= (1.0 - alpha.min(0.99)).ln();
+        }
+      }
+    }
+
+    let [r, g, b] = self.color;
+    let mut wash = Raster::new(raster.width, raster.height);
+    for j in 0..h {
+      for i in 0..w {
+        let d = density[j * w + i];
+        if d <= 0.0 {
+          continue;
+        }
+        let settled = 1.0 + self.granulation * (1.0 - 2.0 * self.height(i0 + i, j0 + j));
+        let alpha = 1.0 - (-d * settled.max(0.0)).exp();
+        wash.set(i0 + i, j0 + j, [r, g, b, alpha]);
+      }
+    }
+    raster.composite(&wash, self.blend_mode, 1.0);
+  }
+
+  // 0 where layer `n` is masked out, 1 where it isn't, with a soft edge in between
+  fn mask_at(&self, x: f32, y: f32, n: usize) -> f32 {
+    if self.mask <= 0.0 {
+      return 1.0;
+    }
+    let value = self.noise.get([
+      (x / self.mask_scale) as f64,
+      (y / self.mask_scale) as f64,
+      n as f64 * 3.7,
+    ]) as f32;
+    // perlin mostly stays within [-0.5, 0.5]
+    let threshold = map_range(self.mask, 0.0, 1.0, -0.5, 0.5);
+    ((value - threshold) / 0.1 + 0.5).max(0.0).min(1.0)
+  }
+
+  // paper height at a raster pixel in [0, 1]
+  fn height(&self, i: usize, j: usize) -> f32 {
+    match self.paper {
+      Some(ref paper) => paper.height(i, j),
+      None => {
+        let value = self.noise.get([i as f64 / 2.5, j as f64 / 2.5, 100.5]) as f32;
+        (value + 0.5).max(0.0).min(1.0)
+      }
+    }
+  }
+}
+
+// implement Debug custom because otherwise
+// capture_model logs every vertex
+impl std::fmt::Debug for Watercolor {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Watercolor")
+      .field("n_vertices", &self.base.len())
+      .field("color", &self.color)
+      .field("alpha", &self.alpha)
+      .field("layers", &self.layers)
+      .field("base_depth", &self.base_depth)
+      .field("layer_depth", &self.layer_depth)
+      .field("mask", &self.mask)
+      .field("edge_darkening", &self.edge_darkening)
+      .field("granulation", &self.granulation)
+      .finish()
+  }
+}
+
+// One round of deformation: a new vertex goes in the middle of every edge (including the closing one),
+// pushed off by a gaussian amount scaled by the edge length and the variance of the vertex starting the edge.
+// Like `meander` but for rings, and with variance per vertex
+fn deform(ring: &[(Point2, f32)]) -> Vec<(Point2, f32)> {
+  let n = ring.len();
+  let mut deformed = Vec::with_capacity(n * 2);
+  for i in 0..n {
+    let (a, variance) = ring[i];
+    let (b, _) = ring[(i + 1) % n];
+    let spread = a.distance(b) * variance;
+    let mid = (a + b) / 2.0 + vec2(gaussian(), gaussian()) * spread / 2.0;
+    deformed.push((a, variance));
+    deformed.push((mid, variance * random_range(0.7, 1.05)));
+  }
+  deformed
+}
+
+// standard normal, Box-Muller
+fn gaussian() -> f32 {
+  let u = random_f32().max(1e-6);
+  let v = random_f32();
+  (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+}
+
+// 1 for pixels whose center is inside the polygon (in pixel space), 0 elsewhere.
+// Scanline fill with the even-odd rule, within the w x h window starting at (i0, j0)
+fn fill(polygon: &Line2, i0: usize, j0: usize, w: usize, h: usize) -> Vec<f32> {
+  let mut coverage = vec![0.0; w * h];
+  let n = polygon.len();
+  let mut crossings = vec![];
+  for j in 0..h {
+    let y = (j0 + j) as f32 + 0.5;
+    crossings.clear();
+    for k in 0..n {
+      let a = polygon[k];
+      let b = polygon[(k + 1) % n];
+      if (a.y <= y) != (b.y <= y) {
+        crossings.push(a.x + (y - a.y) / (b.y - a.y) * (b.x - a.x));
+      }
+    }
+    crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    for pair in crossings.chunks(2) {
+      if pair.len() < 2 {
+        continue;
+      }
+      // pixel centers between the crossings
+      let start = (pair[0] - 0.5 - i0 as f32).ceil().max(0.0) as usize;
+      let end = ((pair[1] - 0.5 - i0 as f32).floor() + 1.0).max(0.0) as usize;
+      for i in start..end.min(w) {
+        coverage[j * w + i] = 1.0;
+      }
+    }
+  }
+  coverage
+}
+
+// separable box blur, repeating the border pixels past the edges
+fn box_blur(values: &[f32], w: usize, h: usize, radius: usize) -> Vec<f32> {
+  let size = (radius * 2 + 1) as f32;
+  let mut horizontal = vec![0.0; w * h];
+  for j in 0..h {
+    let row = &values[j * w..(j + 1) * w];
+    let at = |i: isize| row[i.max(0).min(w as isize - 1) as usize];
+    let mut sum: f32 = (-(radius as isize)..=radius as isize).map(at).sum();
+    for i in 0..w {
+      horizontal[j * w + i] = sum / size;
+      sum += at(i as isize + radius as isize + 1) - at(i as isize - radius as isize);
+    }
+  }
+  let mut blurred = vec![0.0; w * h];
+  for i in 0..w {
+    let at = |j: isize| horizontal[j.max(0).min(h as isize - 1) as usize * w + i];
+    let mut sum: f32 = (-(radius as isize)..=radius as isize).map(at).sum();
+    for j in 0..h {
+      blurred[j * w + i] = sum / size;
+      sum += at(j as isize + radius as isize + 1) - at(j as isize - radius as isize);
+    }
+  }
+  blurred
+}
+
+/// The base polygon of the watercolor sketches: a regular polygon with `n` sides
+pub fn regular_polygon(n: usize, center: Point2, radius: f32) -> Line2 {
+  (0..n)
+    .map(|i| {
+      let angle = i as f32 / n as f32 * 2.0 * PI;
+      center + vec2(angle.cos(), angle.sin()) * radius
+    })
+    .collect()
+}
@@ -11,9 +11,9 @@ use nannou::prelude::*;
 mod util;
 use util::args::ArgParser;
 use util::draw_paper_texture_color;
-use util::formatted_frame_path;
 use util::grid;
 use util::PrismaticWalker;
+use util::{embed_provenance, formatted_frame_path};
 
 fn main() {
   nannou::app(model).update(update).run();
@@ -99,12 +99,14 @@ fn view(app: &App, model: &Model, frame: Frame) {
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
   println!("Loop # {}", frame.nth() + 1);
-  app.main_window().capture_frame(formatted_frame_path(
+  let path = formatted_frame_path(
     app,
     &frame,
     format!(
       "n_lines-{}-velocity-{}-stroke_weight-{}-padding-{}",
       model.n_lines, model.velocity, model.stroke_weight, model.padding
     ),
-  ));
+  );
+  app.main_window().capture_frame(path.clone());
+  embed_provenance(app, path);
 }
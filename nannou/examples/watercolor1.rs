@@ -23,7 +23,7 @@ mod util;
 use util::args::ArgParser;
 use util::color::{palette_to_hsl, rgb_from_hex, select_random};
 use util::meander;
-use util::{capture_model, captured_frame_path};
+use util::{capture_model, capture_threaded};
 
 const PALETTE: [&str; 9] = [
   "#DF4E8F", // pink
@@ -87,9 +87,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
   draw_mountains(&draw, &win);
 
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame_threaded(captured_frame_path(app, &frame));
+  capture_threaded(app, &frame);
   capture_model(app, &frame, model);
 }
 
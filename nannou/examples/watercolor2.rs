@@ -29,7 +29,7 @@ use util::args::ArgParser;
 use util::color::palette_to_hsl;
 use util::draw_paper_texture;
 use util::meander;
-use util::{capture_model, captured_frame_path};
+use util::{capture, capture_model};
 
 const PALETTE: [&str; 4] = [
   "#814F46", // rusty brown
@@ -97,9 +97,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
   draw_paper_texture(&draw, &win, 4000, 0.01);
 
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
   capture_model(app, &frame, model);
 }
 
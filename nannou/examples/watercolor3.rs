@@ -14,7 +14,7 @@ use nannou::noise::{NoiseFn, OpenSimplex, Terrace};
 use util::args::ArgParser;
 use util::color::palette_to_hsl;
 use util::meander;
-use util::{capture_model, captured_frame_path};
+use util::{capture, capture_model};
 
 const PALETTE1: [&str; 4] = [
   "#FAAA0A", // orange1
@@ -111,9 +111,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
   draw_shapes_layer2(&draw, model, &win);
 
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
   capture_model(app, &frame, model);
 }
 
@@ -0,0 +1,172 @@
+// The watercolor1/2/3 technique through util::watercolor::Watercolor.
+// Like watercolor2, the colors come from the palette by height in the frame,
+// but the washes are painted into a raster on top of a paper texture
+// so they get masking, edge darkening and granulation.
+// The paper and the washes are separate layers (util::layers), with vignette and grain on top
+// of the flattened frame.
+// Each wash is crisp on one side and bleeds on the other (--soft-side, in degrees).
+// --mode draw skips the raster and draws the layers directly, which is much faster but flatter.
+//
+// Shapes: polygon, hexagon, blob
+//
+// cargo run --release --example watercolor_layers
+// cargo run --release --example watercolor_layers -- --shape blob --n-polygons 8 --granulation 0.8
+// cargo run --release --example watercolor_layers -- --n-layers 50 --alpha 0.03 --mask 0.5 --texture canvas
+// cargo run --release --example watercolor_layers -- --vignette 0.6 --film-grain 0.05 --save-layers true
+// cargo run --release --example watercolor_layers -- --mode draw --palette "muzli3"
+extern crate chrono;
+extern crate nannou;
+
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::blob::Blob;
+use util::color::{get_palette, rgb_from_hex};
+use util::hexagon::Hexagon;
+use util::raster::BlendMode;
+use util::texture::{texture_kind, Texture, TEXTURES};
+use util::watercolor::regular_polygon;
+use util::{capture, capture_model, Layer, Layers, Line2, Paper, Raster, Watercolor};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+#[derive(Debug)]
+struct Model {
+  paper: Paper,
+  washes: Vec<Watercolor>,
+  // None when drawing the washes directly
+  layers: Option<Layers>,
+  vignette: f32,
+  film_grain: f32,
+  seed: u32,
+  save_layers: bool,
+}
+
+fn base_shape(shape: &str, center: Point2, radius: f32) -> Line2 {
+  match shape {
+    "hexagon" => Hexagon::new(center.x, center.y, radius).points(),
+    "blob" => Blob::new()
+      .x_y(center.x, center.y)
+      .radius(radius * 2.0)
+      .resolution(24)
+      .points(),
+    _ => regular_polygon(random_range(4, 9), center, radius),
+  }
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+  let win = app.window_rect();
+  let seed = args.get("seed", 1);
+
+  let texture = args.get("texture", String::from("grain"));
+  let kind = texture_kind(&texture).unwrap_or_else(|| {
+    panic!(
+      "Unknown texture \"{}\", expected one of {:?}",
+      texture, TEXTURES
+    )
+  });
+  let paper = Texture::new(kind, 1024, 1024).seed(seed).generate();
+
+  let palette_name = args.get("palette", String::from("random"));
+  let palette = get_palette(&palette_name);
+  let shape = args.get("shape", String::from("polygon"));
+  let soft_side = deg_to_rad(args.get("soft-side", 30.0));
+  let washes = (0..args.get("n-polygons", 12))
+    .map(|n| {
+      let center = pt2(
+        random_range(win.left(), win.right()) * 0.8,
+        random_range(win.bottom(), win.top()) * 0.8,
+      );
+      let radius = random_range(win.w() / 10.0, win.w() / 5.0);
+      // bottom of the frame is the first color in the palette, top is the last
+      let index =
+        map_range(center.y, win.bottom(), win.top(), 0, palette.len()).min(palette.len() - 1);
+      let rgb = rgb_from_hex(palette[index]);
+      let direction = vec2(soft_side.cos(), soft_side.sin());
+      Watercolor::new(&base_shape(&shape, center, radius))
+        .variance_fn(move |p| {
+          map_range((p - center).normalize().dot(direction), -1.0, 1.0, 0.2, 0.8)
+        })
+        .color([rgb.red, rgb.green, rgb.blue])
+        .layers(args.get("n-layers", 30))
+        .alpha(args.get("alpha", 0.04))
+        .mask(args.get("mask", 0.3))
+        .edge_darkening(args.get("edge", 0.6))
+        .granulation(args.get("granulation", 0.4))
+        .paper(paper.clone())
+        .blend_mode(BlendMode::Multiply)
+        .seed(seed + n as u32)
+    })
+    .collect::<Vec<Watercolor>>();
+
+  let layers = if args.get("mode", String::from("raster")) == "draw" {
+    None
+  } else {
+    let mut layers = Layers::new(1024, 1024);
+    layers.push(Layer::new("paper", paper.colors(1024, 1024)));
+    // the washes multiply with each other, and then with the paper
+    let mut washes_raster = Raster::new(1024, 1024);
+    for (n, wash) in washes.iter().enumerate() {
+      wash.paint(&mut washes_raster, &win);
+      println!("wash {}/{}", n + 1, washes.len());
+    }
+    layers.push(Layer::new("washes", washes_raster).mode(BlendMode::Multiply));
+    Some(layers)
+  };
+
+  Model {
+    paper,
+    washes,
+    layers,
+    vignette: args.get("vignette", 0.3),
+    film_grain: args.get("film-grain", 0.02),
+    seed,
+    save_layers: args.get("save-layers", false),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  let win = app.window_rect();
+
+  let flat = model.layers.as_ref().map(|layers| {
+    let mut flat = layers.flatten();
+    flat.vignette(model.vignette, 0.4);
+    flat.grain(model.film_grain, model.seed);
+    flat
+  });
+  match flat {
+    Some(ref flat) => flat.draw(app, &draw, &win),
+    None => {
+      model.paper.draw_under(app, &draw, &win);
+      for wash in &model.washes {
+        wash.draw(&draw);
+      }
+    }
+  }
+
+  draw.to_frame(app, &frame).unwrap();
+
+  let path = capture(app, &frame);
+  capture_model(app, &frame, model);
+  if let Some(ref flat) = flat {
+    flat.save(&path.with_file_name(format!(
+      "{}_paint.png",
+      path.file_stem().unwrap().to_string_lossy()
+    )));
+  }
+  if let (true, Some(ref layers)) = (model.save_layers, &model.layers) {
+    layers.save_layers(&path);
+  }
+}
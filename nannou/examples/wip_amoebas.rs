@@ -10,7 +10,7 @@ mod util;
 use util::args::ArgParser;
 use util::blob::*;
 use util::color::*;
-use util::{captured_frame_path, Line2};
+use util::{capture, Line2};
 
 fn main() {
   nannou::app(model).run();
@@ -53,9 +53,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
 }
 
 fn blobs(origin: Point2, max_radius: i32) -> Vec<Line2> {
@@ -8,7 +8,7 @@ use nannou::prelude::*;
 
 mod util;
 use util::args::ArgParser;
-use util::{captured_frame_path, draw_paper_texture, rotate, Line2};
+use util::{capture, draw_paper_texture, rotate, Line2};
 
 fn main() {
   nannou::app(model).run();
@@ -50,9 +50,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // Write to the window frame. and capture image
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame(captured_frame_path(app, &frame));
+  capture(app, &frame);
 }
 
 // simple gritty texture
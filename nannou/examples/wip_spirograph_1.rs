@@ -9,6 +9,7 @@ use nannou::prelude::*;
 
 mod util;
 use util::captured_frame_path;
+use util::harmonograph::{Curve, Spirograph};
 use util::interp::{Interp, Interpolate};
 
 fn main() {
@@ -35,34 +36,18 @@ fn view(app: &App, frame: Frame) {
   let cusps = 0.9;
   let resolution = 40.;
   let line_weight = 1.0;
-  let line_length = 1000;
 
-  // https://en.wikipedia.org/wiki/Epicycloid
-  let points = (0..=(360 * line_length)).map(|i| {
-    let theta: f32 = deg_to_rad(i as f32) / resolution;
-    let x = secondary_radius * (cusps + 1.) * theta.cos()
-      - secondary_radius * 1.2 * ((cusps + 1.) * theta).cos();
-    let y = secondary_radius * (cusps + 1.) * theta.sin()
-      - secondary_radius * 1.2 * ((cusps + 1.) * theta).sin();
-
-    // let x = (primary_radius - secondary_radius) * theta.cos()
-    //   + secondary_radius
-    //     * (((primary_radius - secondary_radius) / secondary_radius) * theta).cos();
-    // let y = (primary_radius - secondary_radius) * theta.sin()
-    //   - secondary_radius
-    //     * (((primary_radius - secondary_radius) / secondary_radius) * theta).sin();
-
-    // // https://en.wikipedia.org/wiki/Hypocycloid
-    // let x = (primary_radius - secondary_radius) * theta.cos()
-    //   + secondary_radius
-    //     * (((primary_radius - secondary_radius) / secondary_radius) * theta).cos();
-    // let y = (primary_radius - secondary_radius) * theta.sin()
-    //   - secondary_radius
-    //     * (((primary_radius - secondary_radius) / secondary_radius) * theta).sin();
-    let point = pt2(x, y);
-    let color = hsl(i as f32 / 360. / line_length as f32 / resolution, 0.5, 0.5);
-    (point, color)
-  });
+  // https://en.wikipedia.org/wiki/Epitrochoid
+  let points = Spirograph::epitrochoid(
+    secondary_radius * cusps,
+    secondary_radius,
+    secondary_radius * 1.2,
+  )
+  .resolution(360. * resolution / (2. * PI))
+  .points_t()
+  .into_iter()
+  // the hue only drifts a little (0 to 0.025) along the curve
+  .map(|(point, t)| (point, hsl(t / resolution, 0.5, 0.5)));
   draw
     .polyline()
     .start_cap_round()
@@ -0,0 +1,230 @@
+// Interactive evolution with util::gallery.
+// Every thumbnail is a genome. Click the ones you like, then press space to breed them.
+//
+// Keys:
+//   space / return  breed the favorites into the next generation
+//   f               toggle a full size view of the thumbnail under the mouse
+//   s               save the current frame (and the genome, if in full size view)
+//   r               start over with a random population
+//
+// Favorites are appended to assets/xp_aesthetic/<timestamp>_lineage.txt.
+// Copy any line from there to render it on its own, at full size:
+//
+// cargo run --release --example xp_aesthetic
+// cargo run --release --example xp_aesthetic -- --columns 5 --population 25 --mutation-rate 0.1
+// cargo run --release --example xp_aesthetic -- --genome "petals=5.2 inner=0.4 twist=1.1 layers=4 hue=0.6 spread=0.1 weight=1.5"
+extern crate chrono;
+extern crate nannou;
+
+use chrono::offset::Local;
+use nannou::prelude::*;
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+mod util;
+use util::args::ArgParser;
+use util::evolve::{Crossover, Mutation, Population, Selection};
+use util::gallery::{param, Gallery, ParamSpec, Params};
+use util::{capture, capture_model, captured_frame_path_txt};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+#[derive(Debug)]
+struct Model {
+  specs: Rc<Vec<ParamSpec>>,
+  gallery: Gallery<Params>,
+  // genome shown at full size instead of the grid
+  focus: Option<Params>,
+  population_size: usize,
+  columns: usize,
+  mutation_rate: f32,
+  // view only gets &Model, so a Cell lets it reset the flag once the frame is saved
+  capture: Cell<bool>,
+}
+
+fn specs() -> Rc<Vec<ParamSpec>> {
+  Rc::new(vec![
+    param("petals", 2.0, 12.0),
+    param("inner", 0.1, 0.9),
+    param("twist", 0.0, PI),
+    param("layers", 1.0, 8.0),
+    param("hue", 0.0, 1.0),
+    param("spread", 0.0, 0.3),
+    param("weight", 0.5, 3.0),
+  ])
+}
+
+fn gallery(specs: &Rc<Vec<ParamSpec>>, size: usize, columns: usize, rate: f32) -> Gallery<Params> {
+  let population = Population::from_fn(size, |_| Params::random(specs))
+    .selection(Selection::Roulette)
+    .crossover(Crossover::Blend(0.25))
+    .mutation(Mutation::Creep(0.15), rate);
+  Gallery::new(population, columns)
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  let specs = specs();
+  let population_size = args.get("population", 16);
+  let columns = args.get("columns", 4);
+  let mutation_rate = args.get("mutation-rate", 0.5);
+
+  let genome = args.get("genome", String::from(""));
+  let focus = if genome.is_empty() {
+    None
+  } else {
+    Some(Params::parse(&specs, &genome))
+  };
+
+  let mut window = app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024);
+  if focus.is_some() {
+    app.set_loop_mode(LoopMode::loop_once());
+  } else {
+    window = window.mouse_pressed(mouse_pressed).key_pressed(key_pressed);
+    // only redraw when something happens
+    app.set_loop_mode(LoopMode::wait());
+  }
+  window.build().unwrap();
+
+  Model {
+    gallery: gallery(&specs, population_size, columns, mutation_rate)
+      .lineage_path(lineage_path(app)),
+    specs,
+    // rendering a single genome from the command line always saves it
+    capture: Cell::new(focus.is_some()),
+    focus,
+    population_size,
+    columns,
+    mutation_rate,
+  }
+}
+
+fn lineage_path(app: &App) -> PathBuf {
+  app
+    .project_path()
+    .expect("failed to locate `project_path`")
+    .join("assets")
+    .join(app.exe_name().unwrap())
+    .join(format!(
+      "{}_lineage",
+      Local::now().format("%Y-%m-%dT%H-%M-%S")
+    ))
+    .with_extension("txt")
+}
+
+fn mouse_pressed(app: &App, model: &mut Model, button: MouseButton) {
+  if button != MouseButton::Left || model.focus.is_some() {
+    return;
+  }
+  if let Some(index) = model
+    .gallery
+    .index_at(pt2(app.mouse.x, app.mouse.y), &app.window_rect())
+  {
+    model.gallery.toggle(index);
+  }
+}
+
+fn key_pressed(app: &App, model: &mut Model, key: Key) {
+  match key {
+    Key::Space | Key::Return => {
+      model.gallery.breed();
+      println!(
+        "generation {} ({} favorites so far)",
+        model.gallery.generation(),
+        model.gallery.lineage().len()
+      );
+    }
+    Key::F => {
+      model.focus = match model.focus {
+        Some(_) => None,
+        None => model
+          .gallery
+          .index_at(pt2(app.mouse.x, app.mouse.y), &app.window_rect())
+          .map(|index| model.gallery.genome(index).clone()),
+      };
+    }
+    Key::S => model.capture.set(true),
+    Key::R => {
+      model.gallery = gallery(
+        &model.specs,
+        model.population_size,
+        model.columns,
+        model.mutation_rate,
+      )
+      .lineage_path(lineage_path(app));
+      model.focus = None;
+    }
+    _ => {}
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  let win = app.window_rect();
+  draw.background().color(hsl(0.1, 0.2, 0.96));
+
+  match &model.focus {
+    Some(params) => draw_params(&draw, &win.pad(win.w() * 0.05), params),
+    None => {
+      for (index, cell) in model.gallery.cells(&win).iter().enumerate() {
+        if model.gallery.is_favorite(index) {
+          draw
+            .rect()
+            .xy(cell.xy())
+            .wh(cell.wh())
+            .color(hsla(0.1, 0.8, 0.5, 0.25));
+        }
+        draw_params(&draw, cell, model.gallery.genome(index));
+      }
+    }
+  }
+
+  draw.to_frame(app, &frame).unwrap();
+
+  if model.capture.replace(false) {
+    capture(app, &frame);
+    match &model.focus {
+      // save the genome as a single line so it can be passed straight back to --genome
+      Some(params) => {
+        std::fs::write(captured_frame_path_txt(app, &frame), format!("{}", params)).ok();
+      }
+      None => capture_model(app, &frame, model),
+    }
+  }
+}
+
+// The sketch being evolved: layers of rose-like curves.
+// Everything is relative to `rect` so it looks the same as a thumbnail and at full size.
+fn draw_params(draw: &Draw, rect: &Rect, params: &Params) {
+  let petals = params.get("petals");
+  let inner = params.get("inner");
+  let twist = params.get("twist");
+  let layers = params.get_usize("layers").max(1);
+  let hue = params.get("hue");
+  let spread = params.get("spread");
+  // stroke weights are designed for a 1024px window
+  let weight = params.get("weight") * rect.w() / 1024.0;
+  let radius = rect.w().min(rect.h()) * 0.45;
+
+  for layer in 0..layers {
+    let t = layer as f32 / layers as f32;
+    let layer_radius = radius * (1.0 - t * (1.0 - inner));
+    let rotation = t * twist;
+    let points = (0..=720).map(|i| {
+      let angle = i as f32 / 720.0 * 2.0 * PI;
+      let r = layer_radius * (inner + (1.0 - inner) * (petals * angle).cos().abs());
+      rect.xy() + pt2((angle + rotation).cos(), (angle + rotation).sin()) * r
+    });
+    draw
+      .polyline()
+      .stroke_weight(weight.max(0.5))
+      .color(hsla((hue + t * spread) % 1.0, 0.6, 0.25 + t * 0.35, 0.9))
+      .points(points);
+  }
+}
@@ -164,9 +164,7 @@ fn view(app: &App, frame: Frame) {
   draw.to_frame(app, &frame).unwrap();
 
   // save image
-  app
-    .main_window()
-    .capture_frame_threaded(util::captured_frame_path(app, &frame));
+  util::capture_threaded(app, &frame);
   // if frame.nth() > 0 {
   // }
   // std::process::exit(0)
@@ -25,7 +25,7 @@ use nannou::prelude::*;
 
 mod util;
 use util::args::ArgParser;
-use util::{capture_model, captured_frame_path};
+use util::{capture_model, capture_threaded};
 
 fn main() {
   nannou::app(model).update(update).view(view).run();
@@ -288,9 +288,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // save image
   if frame.nth() >= model.loops - 1 {
-    app
-      .main_window()
-      .capture_frame_threaded(captured_frame_path(app, &frame));
+    capture_threaded(app, &frame);
     capture_model(app, &frame, model);
   }
 
@@ -6,7 +6,7 @@ use nannou::prelude::*;
 
 mod util;
 use util::args::ArgParser;
-use util::captured_frame_path;
+use util::capture_threaded;
 
 fn main() {
   nannou::app(model).view(view).run();
@@ -71,7 +71,5 @@ fn view(app: &App, model: &Model, frame: Frame) {
   }
 
   draw.to_frame(app, &frame).unwrap();
-  app
-    .main_window()
-    .capture_frame_threaded(captured_frame_path(app, &frame));
+  capture_threaded(app, &frame);
 }
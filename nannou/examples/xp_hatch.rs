@@ -0,0 +1,113 @@
+// Testing out util::hatch on a few different kinds of shapes.
+// Top row: blobs filled with each hatch style.
+// Bottom row: a prism with each face hatched at a different tone, and a honeycomb with random tones.
+//
+// cargo run --release --example xp_hatch
+// cargo run --release --example xp_hatch -- --spacing 4 --angle 30 --stroke-weight 1
+extern crate chrono;
+extern crate nannou;
+
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::blob::Blob;
+use util::capture;
+use util::hatch::{Hatch, HatchStyle};
+use util::hexagon::{honeycomb_hex, Hexagon};
+use util::polygon::close_ring;
+use util::Prism;
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+struct Model {
+  spacing: f32,
+  angle: f32,
+  stroke_weight: f32,
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+
+  Model {
+    spacing: args.get("spacing", 6.0),
+    angle: args.get("angle", 45.0),
+    stroke_weight: args.get("stroke-weight", 1.5),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  draw.background().color(WHITE);
+  let win = app.window_rect();
+  let color = hsla(0.0, 0.0, 0.1, 1.0);
+
+  let hatch = Hatch::new().spacing(model.spacing).angle_deg(model.angle);
+
+  // one blob per style along the top
+  let styles = [
+    HatchStyle::Parallel,
+    HatchStyle::Cross,
+    HatchStyle::Contour,
+    HatchStyle::Noise,
+  ];
+  let column_width = win.w() / styles.len() as f32;
+  for (i, style) in styles.iter().enumerate() {
+    let blob = Blob::new()
+      .x_y(win.left() + column_width * (i as f32 + 0.5), win.h() * 0.2)
+      .radius(column_width * 0.4)
+      .points();
+    draw_outline(&draw, &blob, color, model.stroke_weight);
+    hatch
+      .style(*style)
+      .draw(&blob, &draw, color, model.stroke_weight);
+  }
+
+  // prism faces get lighter towards the top, like a light source from above
+  let prism = Prism::new(pt2(win.w() * -0.25, win.h() * -0.2))
+    .w(200.0)
+    .h(200.0)
+    .d(150.0);
+  let tones = [1.0, 0.6, 0.25];
+  for (face, tone) in prism.faces().iter().zip(tones.iter()) {
+    draw_outline(&draw, face, color, model.stroke_weight);
+    hatch
+      .tone(*tone)
+      .draw(face, &draw, color, model.stroke_weight);
+  }
+
+  // honeycomb with random tones and angles
+  let center = pt2(win.w() * 0.25, win.h() * -0.25);
+  for hex in honeycomb_hex(40.0, 2, 4.0, false) {
+    let points = Hexagon::new(hex.x + center.x, hex.y + center.y, hex.radius).points();
+    draw_outline(&draw, &points, color, model.stroke_weight);
+    hatch
+      .cross()
+      .angle(random_range(0.0, PI))
+      .tone(random_f32())
+      .draw(&points, &draw, color, model.stroke_weight);
+  }
+
+  draw.to_frame(app, &frame).unwrap();
+
+  capture(app, &frame);
+}
+
+fn draw_outline(draw: &Draw, points: &[Point2], color: Hsla, weight: f32) {
+  draw
+    .polyline()
+    .caps_round()
+    .join_round()
+    .weight(weight)
+    .color(color)
+    .points(close_ring(&points.to_vec()));
+}
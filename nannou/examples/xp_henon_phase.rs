@@ -10,7 +10,8 @@ use nannou::prelude::*;
 
 mod util;
 use util::args::ArgParser;
-use util::{capture_model, captured_frame_path};
+use util::attractors::{HenonPhase, Map2};
+use util::{capture_model, capture_threaded};
 
 fn main() {
   nannou::app(model).update(update).view(view).run();
@@ -34,9 +35,8 @@ struct Model {
 
 #[derive(Debug)]
 struct Traveller {
-  x: f32,
+  map: HenonPhase,
   px: f32,
-  y: f32,
   py: f32,
   out_of_bounds: bool,
   // color: String,
@@ -51,12 +51,11 @@ const offx: f32 = 0.5;
 const offy: f32 = -0.1;
 
 impl Traveller {
-  fn new(x: f32, y: f32) -> Self {
+  fn new(phase: f32) -> Self {
     let mut traveller = Self {
-      x,
-      y,
-      px: x,
-      py: y,
+      map: HenonPhase::new(phase),
+      px: 0.0,
+      py: 0.0,
       out_of_bounds: false,
       color: random_range(0.0, 1.0),
     };
@@ -65,10 +64,10 @@ impl Traveller {
   }
 
   fn rebirth(&mut self) {
-    self.x = random_range(0.0, 1.0);
-    self.y = random_range(0.0, 1.0);
+    let (x, y) = (random_range(0.0, 1.0), random_range(0.0, 1.0));
+    self.map = self.map.clone().start(pt2(x, y));
     self.out_of_bounds = false;
-    let d = self.x.hypot(self.y);
+    let d = x.hypot(y);
     // let idx = int(numpal*d)%numpal;
     let idx = (NUMPAL * d.ceil() as i32) % NUMPAL;
     let idx = if idx >= NUMPAL {
@@ -84,11 +83,9 @@ impl Traveller {
     // println!("{} {} {}", idx, MAXPAL, self.color);
   }
 
-  fn update(&mut self, ga: f32) {
+  fn update(&mut self) {
     // move through time
-    let t = self.x * ga.cos() - (self.y - self.x.powi(2)) * ga.sin();
-    self.y = self.x * ga.sin() + (self.y - self.x.powi(2)) * ga.cos();
-    self.x = t;
+    let Point2 { x, y } = self.map.next();
     let fuzx = random_range(-0.004, 0.004);
     let fuzy = random_range(-0.004, 0.004);
 
@@ -96,8 +93,8 @@ impl Traveller {
     // ??????????????????????????????
     let gs = 1.4;
 
-    self.px = fuzx + (self.x / gs + offx) * dim - dim / 2.0;
-    self.py = fuzy + (self.y / gs + offy) * dim - dim / 2.0;
+    self.px = fuzx + (x / gs + offx) * dim - dim / 2.0;
+    self.py = fuzy + (y / gs + offy) * dim - dim / 2.0;
   }
 
   fn draw(&self, draw: &Draw, win: &Rect) {
@@ -121,11 +118,10 @@ fn model(app: &App) -> Model {
   // grid holds our quantity concentrations in the form [a, b]
   let nx = args.get("size", dim as usize);
   let ny = args.get("size", dim as usize);
+  let phase = args.get("phase", PI);
   let n_travellers = args.get("n-travellers", 4000);
   // grid holds our quantity concentrations in the form [a, b]
-  let travellers: Vec<Traveller> = (0..n_travellers)
-    .map(|_| Traveller::new(0.0, 0.0))
-    .collect();
+  let travellers: Vec<Traveller> = (0..n_travellers).map(|_| Traveller::new(phase)).collect();
 
   app
     .new_window()
@@ -140,7 +136,7 @@ fn model(app: &App) -> Model {
     ny,
     n_travellers,
     travellers,
-    phase: args.get("phase", PI),
+    phase,
     update_times: vec![],
     loops,
   }
@@ -154,7 +150,7 @@ fn update(_app: &App, model: &mut Model, _update: Update) {
   for _ in 0..20 {
     // draw all travelers
     for traveller in &mut model.travellers {
-      traveller.update(model.phase);
+      traveller.update();
     }
   }
 
@@ -191,9 +187,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
   // save image
   if frame.nth() >= model.loops - 1 {
-    app
-      .main_window()
-      .capture_frame_threaded(captured_frame_path(app, &frame));
+    capture_threaded(app, &frame);
     capture_model(app, &frame, model);
   }
 
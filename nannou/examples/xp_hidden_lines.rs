@@ -0,0 +1,102 @@
+// Joy Division style ridge plot with real hidden-line removal (util::hidden_line) instead of
+// painting background-colored polygons over the lines behind, so every line in the output is actually visible.
+// That makes it safe for SVG/plotter output, unlike the approach in cloth2.rs.
+//
+// --depth controls how far below each ridge the hidden region reaches.
+// The default hides everything below a ridge; a small depth gives the see-through "skirts" of cloth2.rs.
+//
+// cargo run --release --example xp_hidden_lines
+// cargo run --release --example xp_hidden_lines -- --lines 80 --depth 60 --seed 1234
+extern crate chrono;
+extern crate nannou;
+
+use nannou::noise::{NoiseFn, Perlin};
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::hidden_line::Horizon;
+use util::{capture, capture_model, Line2};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+#[derive(Debug)]
+struct Model {
+  n_lines: usize,
+  n_steps: usize,
+  depth: f32,
+  seed: f64,
+  noise_scale: f64,
+  height: f32,
+  stroke_weight: f32,
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+
+  Model {
+    n_lines: args.get("lines", 60),
+    n_steps: args.get("steps", 300),
+    depth: args.get("depth", std::f32::INFINITY),
+    seed: args.get("seed", random_range(1.0, 100000.0)),
+    noise_scale: args.get("noise-scale", 150.0),
+    height: args.get("height", 120.0),
+    stroke_weight: args.get("stroke-weight", 1.5),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  draw.background().color(WHITE);
+  let win = app.window_rect();
+  let left = win.left() + 150.0;
+  let right = win.right() - 150.0;
+  let perlin = Perlin::new();
+
+  // bottom (front) ridge first
+  let ridges: Vec<Line2> = (0..model.n_lines)
+    .map(|i| {
+      let y = map_range(i, 0, model.n_lines, win.bottom() + 150.0, win.top() - 250.0);
+      (0..=model.n_steps)
+        .map(|n| {
+          let x = map_range(n, 0, model.n_steps, left, right);
+          // ridges are tallest in the middle, and flat towards the edges
+          let envelope = (-(x / (win.w() * 0.15)).powi(2)).exp();
+          let noise = perlin.get([
+            x as f64 / model.noise_scale,
+            y as f64 / model.noise_scale,
+            model.seed,
+          ]) as f32;
+          pt2(x, y + (noise + 0.5).max(0.0) * envelope * model.height)
+        })
+        .collect()
+    })
+    .collect();
+
+  let mut horizon = Horizon::new(left, right).depth(model.depth);
+  for ridge in ridges.iter() {
+    for visible in horizon.add(ridge) {
+      draw
+        .polyline()
+        .caps_round()
+        .join_round()
+        .weight(model.stroke_weight)
+        .color(BLACK)
+        .points(visible);
+    }
+  }
+
+  draw.to_frame(app, &frame).unwrap();
+
+  capture(app, &frame);
+  capture_model(app, &frame, model);
+}
@@ -0,0 +1,115 @@
+// One view for all the pattern-forming simulations in util.
+// Pick a model with --model, everything else is shared through the Simulation trait.
+//
+// Models:
+//   gray-scott, fitzhugh-nagumo, brusselator, schnakenberg  (util::reaction_diffusion)
+//   turing                                                  (util::turing)
+//   life, cyclic                                            (util::automata)
+//
+// cargo run --release --example xp_patterns -- --model fitzhugh-nagumo
+// cargo run --release --example xp_patterns -- --model turing --size 400 --steps 200
+// cargo run --release --example xp_patterns -- --model life --rule B3678/S34678 --steps 300
+// cargo run --release --example xp_patterns -- --model cyclic --size 300 --steps 500 --hue 0.6
+extern crate chrono;
+extern crate nannou;
+
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::automata::{Cyclic, LifeLike};
+use util::circle::Circle;
+use util::reaction_diffusion::{GrayScott, Reaction, ReactionDiffusion};
+use util::simulation::{draw_simulation, Simulation};
+use util::turing::MultiScaleTuring;
+use util::{capture, capture_model};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+#[derive(Debug)]
+struct Model {
+  simulation: Box<dyn Simulation>,
+  hue: f32,
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  let size: usize = args.get("size", 200);
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+  let win = app.window_rect();
+
+  let model_name = args.get("model", String::from("gray-scott"));
+  let (mut simulation, default_steps): (Box<dyn Simulation>, usize) = match model_name.as_str() {
+    "fitzhugh-nagumo" => (
+      Box::new(ReactionDiffusion::new(
+        size,
+        size,
+        Reaction::fitzhugh_nagumo(),
+      )),
+      5000,
+    ),
+    "brusselator" => (
+      Box::new(ReactionDiffusion::new(size, size, Reaction::brusselator())),
+      5000,
+    ),
+    "schnakenberg" => (
+      Box::new(ReactionDiffusion::new(size, size, Reaction::schnakenberg())),
+      5000,
+    ),
+    "turing" => (Box::new(MultiScaleTuring::new(size, size)), 100),
+    "life" => {
+      let mut life = LifeLike::new(size, size, &args.get("rule", String::from("B3/S23")));
+      life.randomize(args.get("density", 0.3));
+      (Box::new(life), 100)
+    }
+    "cyclic" => (
+      Box::new(
+        Cyclic::new(size, size)
+          .states(args.get("states", 14))
+          .threshold(args.get("threshold", 1)),
+      ),
+      300,
+    ),
+    _ => {
+      let mut gray_scott = GrayScott::new(size, size)
+        .feed(args.get("f", 0.055))
+        .kill(args.get("k", 0.062));
+      gray_scott.seed_circle(&Circle::new(0.0, 0.0, win.w() * 0.05), &win);
+      (Box::new(gray_scott), 5000)
+    }
+  };
+
+  let steps = args.get("steps", default_steps);
+  println!("Running {} steps of {} ...", steps, model_name);
+  simulation.steps(steps);
+
+  Model {
+    simulation,
+    hue: args.get("hue", 0.0),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  draw.background().color(WHITE);
+  let win = app.window_rect();
+
+  let hue = model.hue;
+  let saturation = if hue > 0.0 { 0.5 } else { 0.0 };
+  draw_simulation(model.simulation.as_ref(), &draw, &win, |sample| {
+    hsla(hue, saturation, 1.0 - sample * 0.9, 1.0)
+  });
+
+  draw.to_frame(app, &frame).unwrap();
+
+  capture(app, &frame);
+  capture_model(app, &frame, model);
+}
@@ -0,0 +1,85 @@
+// Overlapping blobs drawn as pure outlines, with real occlusion instead of painting over the back ones.
+// Each blob is clipped against every blob in front of it, so the output is plotter-friendly.
+// A few inward offsets of each blob give it some depth.
+//
+// cargo run --release --example xp_polygon_occlusion
+// cargo run --release --example xp_polygon_occlusion -- --blobs 12 --rings 6 --ring-spacing 8
+extern crate chrono;
+extern crate nannou;
+
+use nannou::prelude::*;
+
+mod util;
+use util::args::ArgParser;
+use util::blob::Blob;
+use util::polygon::{clip_outside_all, close_ring, offset};
+use util::{capture, Line2};
+
+fn main() {
+  nannou::app(model).view(view).run();
+}
+
+struct Model {
+  n_blobs: usize,
+  n_rings: usize,
+  ring_spacing: f32,
+  stroke_weight: f32,
+}
+
+fn model(app: &App) -> Model {
+  let args = ArgParser::new();
+  app
+    .new_window()
+    .title(app.exe_name().unwrap())
+    .size(1024, 1024)
+    .build()
+    .unwrap();
+  app.set_loop_mode(LoopMode::loop_once());
+
+  Model {
+    n_blobs: args.get("blobs", 8),
+    n_rings: args.get("rings", 4),
+    ring_spacing: args.get("ring-spacing", 10.0),
+    stroke_weight: args.get("stroke-weight", 1.5),
+  }
+}
+
+fn view(app: &App, model: &Model, frame: Frame) {
+  let draw = app.draw();
+  draw.background().color(WHITE);
+  let win = app.window_rect();
+
+  // front-most blob first
+  let blobs: Vec<Line2> = (0..model.n_blobs)
+    .map(|_| {
+      Blob::new()
+        .x_y(
+          random_range(win.left() * 0.6, win.right() * 0.6),
+          random_range(win.bottom() * 0.6, win.top() * 0.6),
+        )
+        .radius(random_range(200.0, 450.0))
+        .noise_scale(random_range(0.8, 1.2))
+        .points()
+    })
+    .collect();
+
+  for (i, blob) in blobs.iter().enumerate() {
+    let in_front = &blobs[..i];
+    let rings = (0..model.n_rings).flat_map(|n| offset(blob, -(n as f32) * model.ring_spacing));
+    for ring in rings {
+      for visible in clip_outside_all(&close_ring(&ring), in_front) {
+        draw
+          .polyline()
+          .caps_round()
+          .join_round()
+          .weight(model.stroke_weight)
+          .color(BLACK)
+          .points(visible);
+      }
+    }
+  }
+
+  draw.to_frame(app, &frame).unwrap();
+
+  capture(app, &frame);
+}